@@ -79,10 +79,13 @@ fn main() {
     println!("  Token: {} (9 decimals)", TOKEN_TO_ADD);
 
     env.set_gas(150_000_000_000u64); // 150 CSPR gas
-    let lp_received = pool.with_tokens(U512::from(CSPR_TO_ADD)).add_liquidity(
+    let (cspr_used, token_used, lp_received) = pool.with_tokens(U512::from(CSPR_TO_ADD)).add_liquidity(
         U512::from(TOKEN_TO_ADD),
+        U512::zero(), // token_amount_min
+        U512::zero(), // cspr_amount_min
         U512::zero(), // min_lp_tokens
     );
+    println!("  Used - CSPR: {:?}, Token: {:?}", cspr_used, token_used);
     println!("  LP tokens received: {:?}", lp_received);
 
     // Check updated reserves
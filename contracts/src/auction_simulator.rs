@@ -0,0 +1,185 @@
+//! Auction Simulator - deterministic in-memory System Auction for native tests
+//!
+//! OdraVM cannot delegate, so pools pointed at this contract delegate here instead.
+//! Tests drive eras by hand, inject rewards and slash delegations.
+//!
+//! Only compiled natively or with the `test-mode` feature; never part of a production build.
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::casper_types::{PublicKey, U512};
+
+/// CSPR undelegated from the simulator, released once its era is reached
+#[odra::odra_type]
+pub struct UnbondingEntry {
+    /// Delegator receiving the CSPR
+    pub delegator: Address,
+    /// Validator the CSPR was delegated to
+    pub validator: PublicKey,
+    /// CSPR amount unbonding
+    pub amount: U512,
+    /// Era in which the CSPR is paid back
+    pub release_era: u64,
+}
+
+/// In-memory auction with eras, unbonding delay and minimum delegation
+#[odra::module]
+pub struct AuctionSimulator {
+    /// Current era
+    era_id: Var<u64>,
+    /// Eras an undelegation waits before it is paid back
+    unbonding_delay: Var<u64>,
+    /// Minimum total delegation per delegator and validator
+    minimum_delegation: Var<U512>,
+    /// Delegated CSPR by (delegator, validator)
+    delegations: Mapping<(Address, PublicKey), U512>,
+    /// Undelegations waiting for their release era
+    unbonding: Var<Vec<UnbondingEntry>>,
+}
+
+#[odra::module]
+impl AuctionSimulator {
+    /// Initialize the simulator
+    pub fn init(&mut self, unbonding_delay: u64, minimum_delegation: U512) {
+        self.era_id.set(0);
+        self.unbonding_delay.set(unbonding_delay);
+        self.minimum_delegation.set(minimum_delegation);
+        self.unbonding.set(Vec::new());
+    }
+
+    // ============ AUCTION INTERFACE ============
+
+    /// Delegate the attached CSPR to a validator
+    #[odra(payable)]
+    pub fn delegate(&mut self, validator: PublicKey) {
+        let delegator = self.env().caller();
+        let amount = self.env().attached_value();
+
+        if amount == U512::zero() {
+            self.env().revert(AuctionSimulatorError::ZeroAmount);
+        }
+
+        let key = (delegator, validator);
+        let delegated = self.delegations.get(&key).unwrap_or_default() + amount;
+        if delegated < self.minimum_delegation.get_or_default() {
+            self.env().revert(AuctionSimulatorError::BelowMinimumDelegation);
+        }
+
+        self.delegations.set(&key, delegated);
+    }
+
+    /// Undelegate CSPR from a validator; it is paid back after the unbonding delay
+    pub fn undelegate(&mut self, validator: PublicKey, amount: U512) {
+        let delegator = self.env().caller();
+
+        if amount == U512::zero() {
+            self.env().revert(AuctionSimulatorError::ZeroAmount);
+        }
+
+        let key = (delegator, validator.clone());
+        let delegated = self.delegations.get(&key).unwrap_or_default();
+        if amount > delegated {
+            self.env().revert(AuctionSimulatorError::InsufficientDelegation);
+        }
+        self.delegations.set(&key, delegated - amount);
+
+        let release_era = self.era_id.get_or_default() + self.unbonding_delay.get_or_default();
+        let mut unbonding = self.unbonding.get_or_default();
+        unbonding.push(UnbondingEntry {
+            delegator,
+            validator,
+            amount,
+            release_era,
+        });
+        self.unbonding.set(unbonding);
+    }
+
+    /// Get CSPR delegated by a delegator to a validator (rewards included)
+    pub fn delegated_amount(&self, delegator: Address, validator: PublicKey) -> U512 {
+        self.delegations.get(&(delegator, validator)).unwrap_or_default()
+    }
+
+    /// Get the minimum total delegation
+    pub fn minimum_delegation(&self) -> U512 {
+        self.minimum_delegation.get_or_default()
+    }
+
+    /// Get the current era
+    pub fn era_id(&self) -> u64 {
+        self.era_id.get_or_default()
+    }
+
+    /// Get the unbonding delay in eras
+    pub fn unbonding_delay(&self) -> u64 {
+        self.unbonding_delay.get_or_default()
+    }
+
+    /// Get CSPR still unbonding for a delegator
+    pub fn unbonding_amount(&self, delegator: Address) -> U512 {
+        self.unbonding
+            .get_or_default()
+            .iter()
+            .filter(|entry| entry.delegator == delegator)
+            .fold(U512::zero(), |total, entry| total + entry.amount)
+    }
+
+    // ============ TEST CONTROLS ============
+
+    /// Add the attached CSPR to a delegation as staking rewards
+    #[odra(payable)]
+    pub fn distribute_rewards(&mut self, delegator: Address, validator: PublicKey) {
+        let amount = self.env().attached_value();
+        let key = (delegator, validator);
+        let delegated = self.delegations.get(&key).unwrap_or_default();
+        self.delegations.set(&key, delegated + amount);
+    }
+
+    /// Slash a delegation by `slash_bps` and return the amount removed
+    pub fn slash(&mut self, delegator: Address, validator: PublicKey, slash_bps: u64) -> U512 {
+        let key = (delegator, validator);
+        let delegated = self.delegations.get(&key).unwrap_or_default();
+        let slashed = (delegated * U512::from(slash_bps)) / U512::from(10000u64);
+        self.delegations.set(&key, delegated - slashed);
+        slashed
+    }
+
+    /// Move to the next era and pay back matured undelegations
+    pub fn advance_era(&mut self) -> U512 {
+        let era = self.era_id.get_or_default() + 1;
+        self.era_id.set(era);
+
+        let mut released = U512::zero();
+        let mut still_unbonding = Vec::new();
+        for entry in self.unbonding.get_or_default() {
+            if entry.release_era <= era {
+                self.env().transfer_tokens(&entry.delegator, &entry.amount);
+                released += entry.amount;
+            } else {
+                still_unbonding.push(entry);
+            }
+        }
+        self.unbonding.set(still_unbonding);
+
+        released
+    }
+
+    /// Advance several eras at once
+    pub fn advance_eras(&mut self, eras: u64) -> U512 {
+        let mut released = U512::zero();
+        for _ in 0..eras {
+            released += self.advance_era();
+        }
+        released
+    }
+}
+
+/// Auction simulator errors
+#[odra::odra_error]
+pub enum AuctionSimulatorError {
+    /// Zero amount provided
+    ZeroAmount = 1,
+    /// Delegation would stay below the minimum
+    BelowMinimumDelegation = 2,
+    /// Undelegating more than delegated
+    InsufficientDelegation = 3,
+}
@@ -0,0 +1,263 @@
+//! Tick and liquidity math for concentrated-liquidity pools
+//!
+//! Prices are token per CSPR, stored as `sqrt(price) * 2^96` ("Q96"). Tick `t` is the
+//! price `1.0001^t`. CSPR is the first asset of the pair, so selling CSPR moves the
+//! price (and tick) down and selling the token moves it up.
+
+use odra::casper_types::U512;
+
+use crate::types::BPS_DENOMINATOR;
+
+/// Lowest tick, where `1.0001^tick` still fits the Q96 range
+pub const MIN_TICK: i32 = -887272;
+
+/// Highest tick
+pub const MAX_TICK: i32 = 887272;
+
+/// `2^128 / sqrt(1.0001)^(2^i)`, floored; each bit of |tick| multiplies one in
+const INVERSE_SQRT_POWERS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e2139,
+    0xfff2e50f5f656932ef12357cf3c7fdcb,
+    0xffe5caca7e10e4e61c3624eaa0941ccf,
+    0xffcb9843d60f6159c9db58835c926643,
+    0xff973b41fa98c081472e6896dfb254bf,
+    0xff2ea16466c96a3843ec78b326b52860,
+    0xfe5dee046a99a2a811c461f1969c3052,
+    0xfcbe86c7900a88aedcffc83b479aa3a3,
+    0xf987a7253ac413176f2b074cf7815e53,
+    0xf3392b0822b70005940c7a398e4b70f2,
+    0xe7159475a2c29b7443b29c7fa6e889d8,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e4,
+    0x70d869a156d2a1b890bb3df62baf32f6,
+    0x31be135f97d08fd981231505542fcfa5,
+    0x9aa508b5b7a84e1c677de54f3e99bc8,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe97,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// 2^96, the Q96 fixed-point one
+pub fn q96() -> U512 {
+    U512::one() << 96
+}
+
+/// 2^128, the scale of fee growth accumulators
+pub fn q128() -> U512 {
+    U512::one() << 128
+}
+
+/// Lowest sqrt price, at `MIN_TICK`
+pub fn min_sqrt_price() -> U512 {
+    sqrt_price_at_tick(MIN_TICK)
+}
+
+/// Highest sqrt price, at `MAX_TICK`
+pub fn max_sqrt_price() -> U512 {
+    sqrt_price_at_tick(MAX_TICK)
+}
+
+/// `sqrt(1.0001^tick) * 2^96`, rounded up
+pub fn sqrt_price_at_tick(tick: i32) -> U512 {
+    let abs_tick = tick.unsigned_abs();
+
+    // Q128 product of the inverse powers selected by the bits of |tick|
+    let mut ratio = U512::one() << 128;
+    for (bit, factor) in INVERSE_SQRT_POWERS.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U512::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = ((U512::one() << 256) - U512::one()) / ratio;
+    }
+
+    // Q128 -> Q96, rounding up so the tick's price is never underestimated
+    let remainder = ratio & ((U512::one() << 32) - U512::one());
+    let sqrt_price = ratio >> 32;
+    if remainder == U512::zero() {
+        sqrt_price
+    } else {
+        sqrt_price + U512::one()
+    }
+}
+
+/// Greatest tick whose sqrt price is at most `sqrt_price` (binary search)
+pub fn tick_at_sqrt_price(sqrt_price: U512) -> i32 {
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        // Upper midpoint so `low = mid` always makes progress
+        let mid = low + (high - low + 1) / 2;
+        if sqrt_price_at_tick(mid) <= sqrt_price {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+/// CSPR held by `liquidity` between two sqrt prices
+/// `L * 2^96 * (b - a) / (a * b)`
+pub fn cspr_delta(sqrt_a: U512, sqrt_b: U512, liquidity: U512, round_up: bool) -> U512 {
+    let (sqrt_a, sqrt_b) = if sqrt_a > sqrt_b { (sqrt_b, sqrt_a) } else { (sqrt_a, sqrt_b) };
+    if sqrt_a == U512::zero() {
+        return U512::zero();
+    }
+
+    let numerator = (liquidity << 96) * (sqrt_b - sqrt_a);
+    if round_up {
+        div_up(div_up(numerator, sqrt_b), sqrt_a)
+    } else {
+        numerator / sqrt_b / sqrt_a
+    }
+}
+
+/// Tokens held by `liquidity` between two sqrt prices
+/// `L * (b - a) / 2^96`
+pub fn token_delta(sqrt_a: U512, sqrt_b: U512, liquidity: U512, round_up: bool) -> U512 {
+    let (sqrt_a, sqrt_b) = if sqrt_a > sqrt_b { (sqrt_b, sqrt_a) } else { (sqrt_a, sqrt_b) };
+
+    let numerator = liquidity * (sqrt_b - sqrt_a);
+    if round_up {
+        div_up(numerator, q96())
+    } else {
+        numerator / q96()
+    }
+}
+
+/// Sqrt price after adding `cspr_in` to `liquidity` (rounded up, price moves down)
+pub fn next_sqrt_price_from_cspr_in(sqrt_price: U512, liquidity: U512, cspr_in: U512) -> U512 {
+    if cspr_in == U512::zero() {
+        return sqrt_price;
+    }
+    let numerator = liquidity << 96;
+    div_up(numerator * sqrt_price, numerator + cspr_in * sqrt_price)
+}
+
+/// Sqrt price after adding `token_in` to `liquidity` (rounded down, price moves up)
+pub fn next_sqrt_price_from_token_in(sqrt_price: U512, liquidity: U512, token_in: U512) -> U512 {
+    sqrt_price + (token_in << 96) / liquidity
+}
+
+/// Liquidity that `cspr` and `token` can back in [sqrt_lower, sqrt_upper) at `sqrt_price`
+pub fn liquidity_for_amounts(
+    sqrt_price: U512,
+    sqrt_lower: U512,
+    sqrt_upper: U512,
+    cspr: U512,
+    token: U512,
+) -> U512 {
+    let for_cspr = |a: U512, b: U512| (cspr * ((a * b) >> 96)) / (b - a);
+    let for_token = |a: U512, b: U512| (token << 96) / (b - a);
+
+    if sqrt_price <= sqrt_lower {
+        for_cspr(sqrt_lower, sqrt_upper)
+    } else if sqrt_price < sqrt_upper {
+        for_cspr(sqrt_price, sqrt_upper).min(for_token(sqrt_lower, sqrt_price))
+    } else {
+        for_token(sqrt_lower, sqrt_upper)
+    }
+}
+
+/// (CSPR, token) backing `liquidity` in [sqrt_lower, sqrt_upper) at `sqrt_price`
+pub fn amounts_for_liquidity(
+    sqrt_price: U512,
+    sqrt_lower: U512,
+    sqrt_upper: U512,
+    liquidity: U512,
+    round_up: bool,
+) -> (U512, U512) {
+    if sqrt_price <= sqrt_lower {
+        (cspr_delta(sqrt_lower, sqrt_upper, liquidity, round_up), U512::zero())
+    } else if sqrt_price < sqrt_upper {
+        (
+            cspr_delta(sqrt_price, sqrt_upper, liquidity, round_up),
+            token_delta(sqrt_lower, sqrt_price, liquidity, round_up),
+        )
+    } else {
+        (U512::zero(), token_delta(sqrt_lower, sqrt_upper, liquidity, round_up))
+    }
+}
+
+/// One exact-input swap step towards `sqrt_target` within constant liquidity
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SwapStep {
+    /// Sqrt price after the step
+    pub sqrt_price_next: U512,
+    /// Input consumed, excluding the fee
+    pub amount_in: U512,
+    /// Output produced
+    pub amount_out: U512,
+    /// Fee charged on the input
+    pub fee: U512,
+}
+
+/// Swap as much of `amount_remaining` as fits before reaching `sqrt_target`
+/// `cspr_in` selects the direction (CSPR in moves the price down).
+pub fn compute_swap_step(
+    sqrt_price: U512,
+    sqrt_target: U512,
+    liquidity: U512,
+    amount_remaining: U512,
+    swap_fee_bps: u64,
+    cspr_in: bool,
+) -> SwapStep {
+    let scale = U512::from(BPS_DENOMINATOR);
+    let fee_bps = U512::from(swap_fee_bps);
+    let remaining_less_fee = (amount_remaining * (scale - fee_bps)) / scale;
+
+    let input_to = |next: U512| {
+        if cspr_in {
+            cspr_delta(next, sqrt_price, liquidity, true)
+        } else {
+            token_delta(sqrt_price, next, liquidity, true)
+        }
+    };
+
+    let to_target = input_to(sqrt_target);
+    let sqrt_price_next = if remaining_less_fee >= to_target {
+        sqrt_target
+    } else if cspr_in {
+        next_sqrt_price_from_cspr_in(sqrt_price, liquidity, remaining_less_fee)
+    } else {
+        next_sqrt_price_from_token_in(sqrt_price, liquidity, remaining_less_fee)
+    };
+
+    let reached_target = sqrt_price_next == sqrt_target;
+    let amount_in = if reached_target { to_target } else { input_to(sqrt_price_next) };
+    let amount_out = if cspr_in {
+        token_delta(sqrt_price_next, sqrt_price, liquidity, false)
+    } else {
+        cspr_delta(sqrt_price, sqrt_price_next, liquidity, false)
+    };
+
+    // A step that stops short of the target consumes the whole remainder
+    let fee = if reached_target {
+        div_up(amount_in * fee_bps, scale - fee_bps)
+    } else {
+        amount_remaining - amount_in
+    };
+
+    SwapStep {
+        sqrt_price_next,
+        amount_in,
+        amount_out,
+        fee,
+    }
+}
+
+/// `a - b` modulo 2^512; fee growth accumulators only ever compare by difference
+pub fn wrapping_sub(a: U512, b: U512) -> U512 {
+    a.overflowing_sub(b).0
+}
+
+fn div_up(numerator: U512, denominator: U512) -> U512 {
+    let quotient = numerator / denominator;
+    if numerator % denominator == U512::zero() {
+        quotient
+    } else {
+        quotient + U512::one()
+    }
+}
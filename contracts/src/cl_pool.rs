@@ -0,0 +1,1014 @@
+//! Ghost Pool CL - Concentrated-liquidity CSPR/token pool with auto-staking CSPR
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{PublicKey, U256, U512};
+
+use crate::cl_math::{self, MAX_TICK, MIN_TICK};
+use crate::events::*;
+use crate::pool::Cep18TokenContractRef;
+use crate::position_nft::PositionNft;
+use crate::staking::StakingBackend;
+use crate::tick_book::TickBook;
+use crate::types::*;
+
+/// Concentrated-liquidity pool between CSPR and a CEP-18 token
+///
+/// An opt-in alternative to `GhostPoolPool`: liquidity is provided over tick ranges
+/// (price `1.0001^tick`, token per CSPR) and each position is a non-fungible ID with
+/// its own fee accounting, Uniswap v3 style. The CSPR backing all positions is split
+/// between a swap buffer and stake exactly like the full-range pool.
+///
+/// CSPR removed from a position comes out of the buffer when it can; the rest is
+/// undelegated and becomes collectable after the unbonding period. Staking rewards
+/// are shared by the liquidity in range when `compound` runs and, being undelegated,
+/// unlock for collection once they have unbonded.
+/// The pool is standalone: the factory and router only know full-range pools.
+#[odra::module]
+pub struct GhostPoolClPool {
+    // ============ TOKEN ADDRESSES ============
+    /// Paired CEP-18 token address
+    token_address: Var<Address>,
+
+    // ============ PRICE AND LIQUIDITY ============
+    /// Current price, tick, in-range liquidity and global fee growth
+    slot: Var<ClSlot>,
+    /// Initialized ticks
+    ticks: SubModule<TickBook>,
+
+    // ============ POSITIONS ============
+    /// Positions by ID
+    positions: Mapping<u64, Position>,
+    /// Position ownership
+    nft: SubModule<PositionNft>,
+
+    // ============ BALANCES ============
+    /// Reserves, buffer/stake split and owed balances
+    balances: Var<ClBalances>,
+    /// When CSPR undelegated last returns from the auction
+    unbonding_release_time: Var<u64>,
+    /// Delegation backend (System Auction, or a simulator in tests)
+    staking: SubModule<StakingBackend>,
+
+    // ============ CONFIG ============
+    /// Pool configuration (fees and buffer target)
+    config: Var<PoolConfig>,
+    /// Treasury address for protocol fees
+    treasury: Var<Address>,
+
+    // ============ ADMIN ============
+    /// Admin address
+    admin: Var<Address>,
+}
+
+#[odra::module]
+impl GhostPoolClPool {
+    /// Initialize the pool at a starting price
+    #[odra(init)]
+    pub fn init(
+        &mut self,
+        token_address: Address,
+        validator: PublicKey,
+        treasury: Address,
+        admin: Address,
+        tick_spacing: i32,
+        sqrt_price_x96: U512,
+    ) {
+        if tick_spacing <= 0 || tick_spacing > MAX_TICK_SPACING {
+            self.env().revert(ClPoolError::InvalidTickSpacing);
+        }
+        if sqrt_price_x96 < cl_math::min_sqrt_price() || sqrt_price_x96 >= cl_math::max_sqrt_price() {
+            self.env().revert(ClPoolError::InvalidPrice);
+        }
+
+        self.token_address.set(token_address);
+        self.staking.init(validator);
+        self.treasury.set(treasury);
+        self.admin.set(admin);
+
+        self.ticks.init(tick_spacing);
+        self.slot.set(ClSlot {
+            sqrt_price_x96,
+            tick: cl_math::tick_at_sqrt_price(sqrt_price_x96),
+            ..Default::default()
+        });
+        self.balances.set(ClBalances::default());
+        self.config.set(PoolConfig::with_defaults());
+    }
+
+    // ============ POSITIONS ============
+
+    /// Open a position over [tick_lower, tick_upper)
+    /// User sends CSPR as attached value + must have approved token transfer
+    ///
+    /// Deposits the most liquidity the amounts allow at the current price: only the
+    /// tokens needed are pulled and any surplus CSPR is refunded. Returns the position ID.
+    #[odra(payable, non_reentrant)]
+    pub fn mint(
+        &mut self,
+        tick_lower: i32,
+        tick_upper: i32,
+        token_amount_desired: U512,
+        cspr_amount_min: U512,
+        token_amount_min: U512,
+    ) -> u64 {
+        let caller = self.env().caller();
+        self.require_valid_range(tick_lower, tick_upper);
+
+        let id = self.nft.mint(caller);
+        let mut position = Position {
+            id,
+            tick_lower,
+            tick_upper,
+            liquidity: U512::zero(),
+            fee_growth_inside_cspr_last: U512::zero(),
+            fee_growth_inside_token_last: U512::zero(),
+            reward_growth_inside_last: U512::zero(),
+            cspr_owed: U512::zero(),
+            token_owed: U512::zero(),
+            cspr_unlock_time: 0,
+            rewards_owed: U512::zero(),
+            rewards_unlock_time: 0,
+        };
+
+        self.env().emit_event(PositionMinted {
+            owner: caller,
+            position_id: id,
+            tick_lower,
+            tick_upper,
+        });
+
+        self.add_liquidity(&mut position, token_amount_desired, cspr_amount_min, token_amount_min);
+        id
+    }
+
+    /// Add liquidity to an existing position (owner or approved spender)
+    /// Returns (liquidity added, CSPR used, tokens used).
+    #[odra(payable, non_reentrant)]
+    pub fn increase_liquidity(
+        &mut self,
+        position_id: u64,
+        token_amount_desired: U512,
+        cspr_amount_min: U512,
+        token_amount_min: U512,
+    ) -> (U512, U512, U512) {
+        self.require_owner_or_approved(position_id);
+        let mut position = self.get_position(position_id);
+        self.add_liquidity(&mut position, token_amount_desired, cspr_amount_min, token_amount_min)
+    }
+
+    /// Remove liquidity from a position (owner or approved spender)
+    ///
+    /// The CSPR and tokens are credited to the position for `collect`. CSPR the buffer
+    /// cannot cover is undelegated and unlocks after the unbonding period.
+    /// Returns (CSPR credited, tokens credited).
+    #[odra(non_reentrant)]
+    pub fn decrease_liquidity(
+        &mut self,
+        position_id: u64,
+        liquidity: U512,
+        cspr_amount_min: U512,
+        token_amount_min: U512,
+    ) -> (U512, U512) {
+        self.require_owner_or_approved(position_id);
+        let mut position = self.get_position(position_id);
+
+        if liquidity == U512::zero() {
+            self.env().revert(ClPoolError::ZeroLiquidity);
+        }
+        if liquidity > position.liquidity {
+            self.env().revert(ClPoolError::InsufficientLiquidity);
+        }
+
+        let (sqrt_lower, sqrt_upper) = self.range_sqrt_prices(&position);
+        let (cspr_amount, token_amount) = cl_math::amounts_for_liquidity(
+            self.slot().sqrt_price_x96,
+            sqrt_lower,
+            sqrt_upper,
+            liquidity,
+            false,
+        );
+        if cspr_amount < cspr_amount_min {
+            self.env().revert(ClPoolError::CsprSlippage);
+        }
+        if token_amount < token_amount_min {
+            self.env().revert(ClPoolError::TokenSlippage);
+        }
+
+        self.modify_position(&mut position, liquidity, false);
+
+        let mut balances = self.balances.get_or_default();
+        balances.reserve_cspr = balances.reserve_cspr.saturating_sub(cspr_amount);
+        balances.reserve_token = balances.reserve_token.saturating_sub(token_amount);
+        balances.owed_cspr += cspr_amount;
+        balances.owed_token += token_amount;
+
+        // Release the CSPR from the buffer first, then from stake
+        if cspr_amount <= balances.buffer_cspr {
+            balances.buffer_cspr -= cspr_amount;
+        } else {
+            let from_staked = cspr_amount - balances.buffer_cspr;
+            balances.buffer_cspr = U512::zero();
+            balances.staked_cspr = balances.staked_cspr.saturating_sub(from_staked);
+            self.undelegate_from_validator(&mut balances, from_staked);
+            position.cspr_unlock_time = self.unbonding_release_time.get_or_default();
+        }
+        self.balances.set(balances);
+
+        position.cspr_owed += cspr_amount;
+        position.token_owed += token_amount;
+        let cspr_unlock_time = position.cspr_unlock_time;
+        self.positions.set(&position_id, position);
+
+        self.env().emit_event(PositionDecreased {
+            position_id,
+            liquidity,
+            cspr_amount,
+            token_amount,
+            cspr_unlock_time,
+        });
+
+        (cspr_amount, token_amount)
+    }
+
+    /// Pay out a position's fees and removed liquidity to its owner (owner or approved spender)
+    /// Owed CSPR that is still unbonding stays in the position. Returns (CSPR paid, tokens paid).
+    #[odra(non_reentrant)]
+    pub fn collect(&mut self, position_id: u64) -> (U512, U512) {
+        let owner = self.require_owner_or_approved(position_id);
+        let mut position = self.get_position(position_id);
+
+        let slot = self.slot();
+        self.accrue_fees(&mut position, &slot);
+
+        let now = self.env().get_block_time();
+        let fees_amount = if now >= position.cspr_unlock_time { position.cspr_owed } else { U512::zero() };
+        let rewards_amount = if now >= position.rewards_unlock_time { position.rewards_owed } else { U512::zero() };
+        let cspr_amount = fees_amount + rewards_amount;
+        let token_amount = position.token_owed;
+
+        position.cspr_owed -= fees_amount;
+        position.rewards_owed -= rewards_amount;
+        position.token_owed = U512::zero();
+        self.positions.set(&position_id, position);
+
+        let mut balances = self.balances.get_or_default();
+        balances.owed_cspr = balances.owed_cspr.saturating_sub(cspr_amount);
+        balances.owed_token = balances.owed_token.saturating_sub(token_amount);
+        self.balances.set(balances);
+
+        if cspr_amount > U512::zero() {
+            self.env().transfer_tokens(&owner, &cspr_amount);
+        }
+        if token_amount > U512::zero() {
+            self.transfer_token(&owner, token_amount);
+        }
+
+        self.env().emit_event(PositionCollected {
+            position_id,
+            recipient: owner,
+            cspr_amount,
+            token_amount,
+        });
+
+        (cspr_amount, token_amount)
+    }
+
+    /// Close a position with no liquidity and nothing left to collect (owner or approved spender)
+    #[odra(non_reentrant)]
+    pub fn burn(&mut self, position_id: u64) {
+        let owner = self.require_owner_or_approved(position_id);
+        let position = self.get_position(position_id);
+
+        if position.liquidity > U512::zero()
+            || position.cspr_owed > U512::zero()
+            || position.rewards_owed > U512::zero()
+            || position.token_owed > U512::zero()
+        {
+            self.env().revert(ClPoolError::PositionNotEmpty);
+        }
+
+        self.nft.burn(position_id);
+
+        self.env().emit_event(PositionBurned { position_id, owner });
+    }
+
+    /// Approve (or with None, revoke) a spender to manage and transfer a position
+    #[odra(non_reentrant)]
+    pub fn approve_position(&mut self, position_id: u64, spender: Option<Address>) {
+        let caller = self.env().caller();
+        if self.nft.owner_of(position_id) != Some(caller) {
+            self.env().revert(ClPoolError::NotApproved);
+        }
+
+        self.nft.approve(position_id, spender);
+
+        self.env().emit_event(PositionApproval {
+            position_id,
+            owner: caller,
+            spender,
+        });
+    }
+
+    /// Transfer a position with its liquidity and owed balances (owner or approved spender)
+    #[odra(non_reentrant)]
+    pub fn transfer_position(&mut self, position_id: u64, to: Address) {
+        let from = self.require_owner_or_approved(position_id);
+        self.nft.transfer(position_id, to);
+
+        self.env().emit_event(PositionTransferred { position_id, from, to });
+    }
+
+    // ============ SWAP FUNCTIONS ============
+
+    /// Swap CSPR for tokens, crossing ticks as needed
+    /// CSPR left over once the price reaches the lowest tick is refunded.
+    #[odra(payable, non_reentrant)]
+    pub fn swap_cspr_for_token(&mut self, min_token_out: U512) -> U512 {
+        let caller = self.env().caller();
+        let cspr_attached = self.env().attached_value();
+
+        if cspr_attached == U512::zero() {
+            self.env().revert(ClPoolError::ZeroCsprAmount);
+        }
+
+        let outcome = self.simulate_swap(cspr_attached, true);
+        let token_out = outcome.amount_out;
+        if token_out == U512::zero() {
+            self.env().revert(ClPoolError::InsufficientLiquidity);
+        }
+        if token_out < min_token_out {
+            self.env().revert(ClPoolError::SlippageExceeded);
+        }
+
+        let mut balances = self.balances.get_or_default();
+        if token_out > balances.reserve_token {
+            self.env().revert(ClPoolError::InsufficientLiquidity);
+        }
+        self.apply_swap(&outcome);
+
+        balances.reserve_cspr += outcome.reserve_in;
+        balances.buffer_cspr += outcome.reserve_in;
+        balances.owed_cspr += outcome.lp_fee;
+        balances.protocol_fees_cspr += outcome.protocol_fee;
+        balances.reserve_token -= token_out;
+        self.rebalance_stake(&mut balances);
+        self.balances.set(balances);
+
+        let cspr_refund = cspr_attached - outcome.amount_in;
+        if cspr_refund > U512::zero() {
+            self.env().transfer_tokens(&caller, &cspr_refund);
+        }
+        self.transfer_token(&caller, token_out);
+
+        self.env().emit_event(Swap {
+            sender: caller,
+            cspr_in: outcome.amount_in,
+            cspr_out: U512::zero(),
+            token_in: U512::zero(),
+            token_out,
+        });
+
+        token_out
+    }
+
+    /// Swap tokens for CSPR, crossing ticks as needed
+    /// Only the tokens used are pulled; the CSPR out must fit in the buffer.
+    #[odra(non_reentrant)]
+    pub fn swap_token_for_cspr(&mut self, token_in: U512, min_cspr_out: U512) -> U512 {
+        let caller = self.env().caller();
+
+        if token_in == U512::zero() {
+            self.env().revert(ClPoolError::ZeroTokenAmount);
+        }
+
+        let outcome = self.simulate_swap(token_in, false);
+        let cspr_out = outcome.amount_out;
+        if cspr_out == U512::zero() {
+            self.env().revert(ClPoolError::InsufficientLiquidity);
+        }
+        if cspr_out < min_cspr_out {
+            self.env().revert(ClPoolError::SlippageExceeded);
+        }
+
+        let mut balances = self.balances.get_or_default();
+        if cspr_out > balances.buffer_cspr {
+            self.env().revert(ClPoolError::InsufficientBuffer);
+        }
+
+        self.transfer_token_from(&caller, &self.env().self_address(), outcome.amount_in);
+        self.apply_swap(&outcome);
+
+        balances.reserve_token += outcome.reserve_in;
+        balances.owed_token += outcome.lp_fee;
+        balances.protocol_fees_token += outcome.protocol_fee;
+        balances.reserve_cspr = balances.reserve_cspr.saturating_sub(cspr_out);
+        balances.buffer_cspr -= cspr_out;
+        self.balances.set(balances);
+
+        self.env().transfer_tokens(&caller, &cspr_out);
+
+        self.env().emit_event(Swap {
+            sender: caller,
+            cspr_in: U512::zero(),
+            cspr_out,
+            token_in: outcome.amount_in,
+            token_out: U512::zero(),
+        });
+
+        cspr_out
+    }
+
+    // ============ COMPOUND ============
+
+    /// Harvest staking rewards and share them with the liquidity in range
+    ///
+    /// Rewards are undelegated, so positions can collect them once they have unbonded.
+    /// With no liquidity in range the whole harvest goes to the treasury.
+    #[odra(non_reentrant)]
+    pub fn compound(&mut self) -> U512 {
+        let mut balances = self.balances.get_or_default();
+        let delegated = self.staking.delegated_amount();
+        if delegated <= balances.staked_cspr {
+            return U512::zero();
+        }
+        let rewards = delegated - balances.staked_cspr;
+
+        self.undelegate_from_validator(&mut balances, rewards);
+
+        let mut slot = self.slot();
+        let config = self.config.get_or_default();
+        let protocol_fee = if slot.liquidity == U512::zero() {
+            rewards
+        } else {
+            (rewards * U512::from(config.protocol_fee_bps.as_u64())) / U512::from(BPS_DENOMINATOR)
+        };
+        let rewards_to_pool = rewards - protocol_fee;
+
+        if rewards_to_pool > U512::zero() {
+            let growth = (rewards_to_pool << 128) / slot.liquidity;
+            slot.reward_growth_global = slot.reward_growth_global.overflowing_add(growth).0;
+            self.slot.set(slot);
+        }
+        balances.owed_cspr += rewards_to_pool;
+        balances.protocol_fees_cspr += protocol_fee;
+        self.balances.set(balances);
+
+        self.env().emit_event(Compounded {
+            rewards_harvested: rewards,
+            protocol_fee,
+            rewards_to_pool,
+        });
+
+        rewards_to_pool
+    }
+
+    // ============ PROTOCOL FEES ============
+
+    /// Send accrued protocol fees to the treasury
+    /// CSPR fees are held back while harvested rewards are still unbonding.
+    #[odra(non_reentrant)]
+    pub fn claim_protocol_fees(&mut self) -> (U512, U512) {
+        let mut balances = self.balances.get_or_default();
+        let treasury = self.treasury.get().expect("Treasury not set");
+
+        let cspr_amount = if self.in_flight_unbonding(&balances) == U512::zero() {
+            balances.protocol_fees_cspr
+        } else {
+            U512::zero()
+        };
+        let token_amount = balances.protocol_fees_token;
+
+        balances.protocol_fees_cspr -= cspr_amount;
+        balances.protocol_fees_token = U512::zero();
+        self.balances.set(balances);
+
+        if cspr_amount > U512::zero() {
+            self.env().transfer_tokens(&treasury, &cspr_amount);
+        }
+        if token_amount > U512::zero() {
+            self.transfer_token(&treasury, token_amount);
+        }
+
+        self.env().emit_event(ProtocolFeesClaimed {
+            treasury,
+            cspr_amount,
+            token_amount,
+        });
+
+        (cspr_amount, token_amount)
+    }
+
+    // ============ ADMIN FUNCTIONS ============
+
+    /// Route staking to an `AuctionSimulator` contract (admin only, before any stake)
+    /// Reverts in production WASM builds without the `test-mode` feature.
+    #[odra(non_reentrant)]
+    pub fn set_auction_simulator(&mut self, simulator: Address) {
+        self.require_admin();
+        if self.balances.get_or_default().staked_cspr > U512::zero() {
+            self.env().revert(ClPoolError::StakeActive);
+        }
+        self.staking.set_simulator(simulator);
+    }
+
+    /// Replace the pool configuration (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_config(&mut self, config: PoolConfig) {
+        self.require_admin();
+        if !config.is_valid() {
+            self.env().revert(ClPoolError::InvalidFee);
+        }
+
+        self.config.set(config.clone());
+        let mut balances = self.balances.get_or_default();
+        self.rebalance_stake(&mut balances);
+        self.balances.set(balances);
+
+        self.env().emit_event(ConfigUpdated {
+            buffer_target_bps: config.buffer_target_bps,
+            swap_fee_bps: config.swap_fee_bps,
+            protocol_fee_bps: config.protocol_fee_bps,
+            protocol_swap_fee_bps: config.protocol_swap_fee_bps,
+        });
+    }
+
+    /// Set the treasury receiving protocol fees (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_treasury(&mut self, treasury: Address) {
+        self.require_admin();
+        self.treasury.set(treasury);
+
+        self.env().emit_event(TreasuryUpdated { treasury });
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Get the current price, tick, in-range liquidity and fee growth
+    pub fn get_slot(&self) -> ClSlot {
+        self.slot()
+    }
+
+    /// Get the tracked balances
+    pub fn get_balances(&self) -> ClBalances {
+        self.balances.get_or_default()
+    }
+
+    /// Get a live position
+    pub fn get_position(&self, position_id: u64) -> Position {
+        if self.nft.owner_of(position_id).is_none() {
+            self.env().revert(ClPoolError::PositionNotFound);
+        }
+        self.positions
+            .get(&position_id)
+            .unwrap_or_revert_with(&self.env(), ClPoolError::PositionNotFound)
+    }
+
+    /// Get the owner of a position (None once burned)
+    pub fn position_owner(&self, position_id: u64) -> Option<Address> {
+        self.nft.owner_of(position_id)
+    }
+
+    /// Get the approved spender of a position
+    pub fn get_position_approved(&self, position_id: u64) -> Option<Address> {
+        self.nft.approved(position_id)
+    }
+
+    /// Get the number of positions held by an owner
+    pub fn position_count(&self, owner: Address) -> u32 {
+        self.nft.balance_of(owner)
+    }
+
+    /// Get a page of position IDs held by an owner
+    pub fn positions_of(&self, owner: Address, offset: u32, limit: u32) -> Vec<u64> {
+        self.nft.tokens_of(owner, offset, limit.min(MAX_POSITIONS_PAGE_SIZE))
+    }
+
+    /// Get the (CSPR, token) a position's liquidity is worth at the current price
+    pub fn get_position_amounts(&self, position_id: u64) -> (U512, U512) {
+        let position = self.get_position(position_id);
+        let (sqrt_lower, sqrt_upper) = self.range_sqrt_prices(&position);
+        cl_math::amounts_for_liquidity(
+            self.slot().sqrt_price_x96,
+            sqrt_lower,
+            sqrt_upper,
+            position.liquidity,
+            false,
+        )
+    }
+
+    /// Get the (CSPR, token) a position can collect, including fees not yet accrued to it
+    pub fn get_position_owed(&self, position_id: u64) -> (U512, U512) {
+        let mut position = self.get_position(position_id);
+        self.accrue_fees(&mut position, &self.slot());
+        (position.cspr_owed + position.rewards_owed, position.token_owed)
+    }
+
+    /// Get a tick's liquidity and fee growth outside
+    pub fn get_tick(&self, tick: i32) -> TickInfo {
+        self.ticks.get(tick)
+    }
+
+    /// Get up to `limit` initialized ticks at or above `from_tick`, ascending
+    pub fn initialized_ticks(&self, from_tick: i32, limit: u32) -> Vec<i32> {
+        self.ticks.initialized_ticks(from_tick, limit)
+    }
+
+    /// Get the tick spacing
+    pub fn tick_spacing(&self) -> i32 {
+        self.ticks.tick_spacing()
+    }
+
+    /// Quote tokens out for a CSPR input
+    pub fn quote_cspr_for_token(&self, cspr_in: U512) -> U512 {
+        self.simulate_swap(cspr_in, true).amount_out
+    }
+
+    /// Quote CSPR out for a token input
+    pub fn quote_token_for_cspr(&self, token_in: U512) -> U512 {
+        self.simulate_swap(token_in, false).amount_out
+    }
+
+    /// Get admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    /// Get treasury address
+    pub fn treasury(&self) -> Address {
+        self.treasury.get().expect("Treasury not set")
+    }
+
+    /// Get the pool configuration
+    pub fn get_config(&self) -> PoolConfig {
+        self.config.get_or_default()
+    }
+
+    /// Get the validator the pool delegates to
+    pub fn validator(&self) -> PublicKey {
+        self.staking.validator()
+    }
+
+    /// Get token address
+    pub fn token_address(&self) -> Address {
+        self.token_address.get().expect("Token not set")
+    }
+
+    // ============ INTERNAL FUNCTIONS ============
+
+    fn slot(&self) -> ClSlot {
+        self.slot.get_or_default()
+    }
+
+    /// Deposit the attached CSPR and up to `token_amount_desired` into a position
+    fn add_liquidity(
+        &mut self,
+        position: &mut Position,
+        token_amount_desired: U512,
+        cspr_amount_min: U512,
+        token_amount_min: U512,
+    ) -> (U512, U512, U512) {
+        let caller = self.env().caller();
+        let cspr_attached = self.env().attached_value();
+
+        let sqrt_price = self.slot().sqrt_price_x96;
+        let (sqrt_lower, sqrt_upper) = self.range_sqrt_prices(position);
+        let liquidity =
+            cl_math::liquidity_for_amounts(sqrt_price, sqrt_lower, sqrt_upper, cspr_attached, token_amount_desired);
+        if liquidity == U512::zero() {
+            self.env().revert(ClPoolError::ZeroLiquidity);
+        }
+
+        let (cspr_amount, token_amount) =
+            cl_math::amounts_for_liquidity(sqrt_price, sqrt_lower, sqrt_upper, liquidity, true);
+        if cspr_amount > cspr_attached || token_amount > token_amount_desired {
+            self.env().revert(ClPoolError::SlippageExceeded);
+        }
+        if cspr_amount < cspr_amount_min {
+            self.env().revert(ClPoolError::CsprSlippage);
+        }
+        if token_amount < token_amount_min {
+            self.env().revert(ClPoolError::TokenSlippage);
+        }
+
+        self.modify_position(position, liquidity, true);
+        self.positions.set(&position.id, position.clone());
+
+        if token_amount > U512::zero() {
+            self.transfer_token_from(&caller, &self.env().self_address(), token_amount);
+        }
+
+        let mut balances = self.balances.get_or_default();
+        balances.reserve_cspr += cspr_amount;
+        balances.reserve_token += token_amount;
+        balances.buffer_cspr += cspr_amount;
+        self.rebalance_stake(&mut balances);
+        self.balances.set(balances);
+
+        // Refund CSPR the range did not use
+        let cspr_refund = cspr_attached - cspr_amount;
+        if cspr_refund > U512::zero() {
+            self.env().transfer_tokens(&caller, &cspr_refund);
+        }
+
+        self.env().emit_event(PositionIncreased {
+            position_id: position.id,
+            liquidity,
+            cspr_amount,
+            token_amount,
+        });
+
+        (liquidity, cspr_amount, token_amount)
+    }
+
+    /// Add or remove liquidity from a position, its ticks and (when in range) the slot
+    fn modify_position(&mut self, position: &mut Position, liquidity: U512, add: bool) {
+        let mut slot = self.slot();
+
+        // New ticks must exist before fees are measured; removed ones are cleared after
+        if add {
+            self.ticks.update(position.tick_lower, &slot, liquidity, true, false);
+            self.ticks.update(position.tick_upper, &slot, liquidity, true, true);
+        }
+        self.accrue_fees(position, &slot);
+        if !add {
+            self.ticks.update(position.tick_lower, &slot, liquidity, false, false);
+            self.ticks.update(position.tick_upper, &slot, liquidity, false, true);
+        }
+
+        position.liquidity = if add { position.liquidity + liquidity } else { position.liquidity - liquidity };
+
+        if position.tick_lower <= slot.tick && slot.tick < position.tick_upper {
+            slot.liquidity = if add { slot.liquidity + liquidity } else { slot.liquidity - liquidity };
+            self.slot.set(slot);
+        }
+    }
+
+    /// Credit the fees and staking rewards earned inside a position's range since its last update
+    /// Rewards credited while harvested rewards are unbonding unlock with them.
+    fn accrue_fees(&self, position: &mut Position, slot: &ClSlot) {
+        let (inside_cspr, inside_token, inside_rewards) =
+            self.ticks.growth_inside(position.tick_lower, position.tick_upper, slot);
+
+        let earned = |inside: U512, last: U512| {
+            (cl_math::wrapping_sub(inside, last) * position.liquidity) / cl_math::q128()
+        };
+        let cspr_earned = earned(inside_cspr, position.fee_growth_inside_cspr_last);
+        let token_earned = earned(inside_token, position.fee_growth_inside_token_last);
+        let rewards_earned = earned(inside_rewards, position.reward_growth_inside_last);
+
+        if rewards_earned > U512::zero() {
+            position.rewards_owed += rewards_earned;
+            if self.in_flight_unbonding(&self.balances.get_or_default()) > U512::zero() {
+                let release_time = self.unbonding_release_time.get_or_default();
+                position.rewards_unlock_time = position.rewards_unlock_time.max(release_time);
+            }
+        }
+        position.cspr_owed += cspr_earned;
+        position.token_owed += token_earned;
+        position.fee_growth_inside_cspr_last = inside_cspr;
+        position.fee_growth_inside_token_last = inside_token;
+        position.reward_growth_inside_last = inside_rewards;
+    }
+
+    /// Walk the price through initialized ticks for an exact input
+    fn simulate_swap(&self, amount: U512, cspr_in: bool) -> SwapOutcome {
+        let config = self.config.get_or_default();
+        let swap_fee_bps = config.swap_fee_bps.as_u64();
+        let protocol_bps = U512::from(config.protocol_swap_fee_bps.as_u64());
+
+        let mut outcome = SwapOutcome {
+            slot: self.slot(),
+            ..Default::default()
+        };
+        let bound = if cspr_in { cl_math::min_sqrt_price() } else { cl_math::max_sqrt_price() };
+        let mut remaining = amount;
+
+        while remaining > U512::zero() && outcome.slot.sqrt_price_x96 != bound {
+            let slot = &mut outcome.slot;
+            // Steps end at the next initialized tick or the edge of its bitmap word
+            let (next_tick, _) = if cspr_in {
+                self.ticks.next_at_or_below(slot.tick)
+            } else {
+                self.ticks.next_above(slot.tick)
+            };
+            let sqrt_next = cl_math::sqrt_price_at_tick(next_tick);
+
+            let step = cl_math::compute_swap_step(
+                slot.sqrt_price_x96,
+                sqrt_next,
+                slot.liquidity,
+                remaining,
+                swap_fee_bps,
+                cspr_in,
+            );
+            remaining -= step.amount_in + step.fee;
+            outcome.amount_in += step.amount_in + step.fee;
+            outcome.reserve_in += step.amount_in;
+            outcome.amount_out += step.amount_out;
+
+            // Fees earned with no liquidity in range go to the treasury
+            let protocol_fee = if slot.liquidity == U512::zero() {
+                step.fee
+            } else {
+                (step.fee * protocol_bps) / U512::from(BPS_DENOMINATOR)
+            };
+            let lp_fee = step.fee - protocol_fee;
+            outcome.protocol_fee += protocol_fee;
+            outcome.lp_fee += lp_fee;
+            if lp_fee > U512::zero() {
+                let growth = (lp_fee << 128) / slot.liquidity;
+                let global = if cspr_in { &mut slot.fee_growth_global_cspr } else { &mut slot.fee_growth_global_token };
+                *global = global.overflowing_add(growth).0;
+            }
+
+            slot.sqrt_price_x96 = step.sqrt_price_next;
+            if step.sqrt_price_next == sqrt_next {
+                let info = self.ticks.get(next_tick);
+                if info.liquidity_gross() > U512::zero() {
+                    outcome.crossings.push((next_tick, slot.fee_growth_global_cspr, slot.fee_growth_global_token));
+                    slot.liquidity = if cspr_in {
+                        slot.liquidity + info.liquidity_upper - info.liquidity_lower
+                    } else {
+                        slot.liquidity + info.liquidity_lower - info.liquidity_upper
+                    };
+                }
+                slot.tick = if cspr_in { next_tick - 1 } else { next_tick };
+            } else {
+                slot.tick = cl_math::tick_at_sqrt_price(slot.sqrt_price_x96);
+            }
+        }
+
+        outcome
+    }
+
+    /// Commit a simulated swap's tick crossings and final slot
+    fn apply_swap(&mut self, outcome: &SwapOutcome) {
+        let reward_growth = outcome.slot.reward_growth_global;
+        for (tick, growth_cspr, growth_token) in outcome.crossings.iter() {
+            self.ticks.cross(*tick, *growth_cspr, *growth_token, reward_growth);
+        }
+        self.slot.set(outcome.slot.clone());
+    }
+
+    fn range_sqrt_prices(&self, position: &Position) -> (U512, U512) {
+        (
+            cl_math::sqrt_price_at_tick(position.tick_lower),
+            cl_math::sqrt_price_at_tick(position.tick_upper),
+        )
+    }
+
+    fn require_valid_range(&self, tick_lower: i32, tick_upper: i32) {
+        let spacing = self.ticks.tick_spacing();
+        if tick_lower >= tick_upper
+            || tick_lower < MIN_TICK
+            || tick_upper > MAX_TICK
+            || tick_lower % spacing != 0
+            || tick_upper % spacing != 0
+        {
+            self.env().revert(ClPoolError::InvalidTickRange);
+        }
+    }
+
+    /// Revert unless the caller owns the position or is approved for it; returns the owner
+    fn require_owner_or_approved(&self, position_id: u64) -> Address {
+        let caller = self.env().caller();
+        let owner = self
+            .nft
+            .owner_of(position_id)
+            .unwrap_or_revert_with(&self.env(), ClPoolError::PositionNotFound);
+
+        if caller != owner && self.nft.approved(position_id) != Some(caller) {
+            self.env().revert(ClPoolError::NotApproved);
+        }
+        owner
+    }
+
+    /// Revert unless the caller is the admin
+    fn require_admin(&self) {
+        let admin = self.admin.get().expect("Admin not set");
+        if self.env().caller() != admin {
+            self.env().revert(ClPoolError::NotAdmin);
+        }
+    }
+
+    /// Stake the buffer's excess over its target share of the CSPR reserve
+    fn rebalance_stake(&mut self, balances: &mut ClBalances) {
+        let config = self.config.get_or_default();
+        let target_buffer =
+            (balances.reserve_cspr * U512::from(config.buffer_target_bps.as_u64())) / U512::from(BPS_DENOMINATOR);
+
+        if balances.buffer_cspr > target_buffer {
+            let excess = balances.buffer_cspr - target_buffer;
+
+            // The auction rejects delegations below its minimum; keep it buffered until then
+            if balances.staked_cspr + excess < self.staking.minimum_delegation() {
+                return;
+            }
+
+            self.staking.delegate(excess);
+            balances.buffer_cspr = target_buffer;
+            balances.staked_cspr += excess;
+
+            self.env().emit_event(Delegated { amount: excess });
+        }
+    }
+
+    /// Undelegate CSPR from the validator (initiates 14h unbonding period)
+    fn undelegate_from_validator(&mut self, balances: &mut ClBalances, amount: U512) {
+        if amount == U512::zero() {
+            return;
+        }
+
+        // Track the CSPR in flight until the unbonding period ends
+        balances.unbonding_cspr = self.in_flight_unbonding(balances) + amount;
+        let release_time = self.env().get_block_time() + UNBONDING_PERIOD_MS;
+        self.unbonding_release_time.set(release_time);
+
+        self.staking.undelegate(amount);
+
+        self.env().emit_event(Undelegated { amount });
+    }
+
+    /// Undelegated CSPR still on its way back from the auction
+    fn in_flight_unbonding(&self, balances: &ClBalances) -> U512 {
+        if self.env().get_block_time() >= self.unbonding_release_time.get_or_default() {
+            U512::zero()
+        } else {
+            balances.unbonding_cspr
+        }
+    }
+
+    // ============ TOKEN HELPERS ============
+
+    fn transfer_token(&self, to: &Address, amount: U512) {
+        let token_address = self.token_address.get().expect("Token not set");
+        let amount_u256 = U256::from(amount.as_u128());
+        Cep18TokenContractRef::new(self.env(), token_address).transfer(to, &amount_u256);
+    }
+
+    fn transfer_token_from(&self, from: &Address, to: &Address, amount: U512) {
+        let token_address = self.token_address.get().expect("Token not set");
+        let amount_u256 = U256::from(amount.as_u128());
+        Cep18TokenContractRef::new(self.env(), token_address).transfer_from(from, to, &amount_u256);
+    }
+}
+
+/// Result of walking a swap through the ticks, applied only once it is accepted
+#[derive(Default)]
+struct SwapOutcome {
+    /// Input consumed, fees included
+    amount_in: U512,
+    /// Input added to the reserve (fees excluded)
+    reserve_in: U512,
+    /// Output produced
+    amount_out: U512,
+    /// Fees owed to positions
+    lp_fee: U512,
+    /// Fees owed to the treasury
+    protocol_fee: U512,
+    /// Slot after the swap
+    slot: ClSlot,
+    /// Ticks crossed, with the global (CSPR, token) fee growth at the crossing
+    crossings: Vec<(i32, U512, U512)>,
+}
+
+/// Concentrated-liquidity pool errors
+#[odra::odra_error]
+pub enum ClPoolError {
+    /// Zero CSPR amount provided
+    ZeroCsprAmount = 1,
+    /// Zero token amount provided
+    ZeroTokenAmount = 2,
+    /// Tick spacing out of range
+    InvalidTickSpacing = 3,
+    /// Starting price outside the tick range
+    InvalidPrice = 4,
+    /// Ticks unordered, out of range or not on the tick spacing
+    InvalidTickRange = 5,
+    /// Amounts back no liquidity
+    ZeroLiquidity = 6,
+    /// Slippage exceeded
+    SlippageExceeded = 7,
+    /// CSPR slippage exceeded
+    CsprSlippage = 8,
+    /// Token slippage exceeded
+    TokenSlippage = 9,
+    /// Position does not exist or was burned
+    PositionNotFound = 10,
+    /// Caller is neither owner nor approved for the position
+    NotApproved = 11,
+    /// Insufficient liquidity
+    InsufficientLiquidity = 12,
+    /// Insufficient buffer for swap
+    InsufficientBuffer = 13,
+    /// Position still holds liquidity or owed balances
+    PositionNotEmpty = 14,
+    /// Caller is not the admin
+    NotAdmin = 15,
+    /// Fee parameter out of range
+    InvalidFee = 16,
+    /// Operation requires no active stake
+    StakeActive = 17,
+}
@@ -1,7 +1,9 @@
 //! Events emitted by Ghost Pool AMM
 
 use odra::prelude::*;
-use odra::casper_types::U512;
+use odra::casper_types::{PublicKey, U256, U512};
+
+use crate::types::{TokenAccounting, ZapAsset};
 
 /// Emitted when liquidity is added to the pool
 #[odra::event]
@@ -81,3 +83,464 @@ pub struct Undelegated {
     /// Amount undelegated
     pub amount: U512,
 }
+
+/// Emitted when the share of swap fees diverted to the treasury changes
+#[odra::event]
+pub struct ProtocolSwapFeeUpdated {
+    /// New share of each swap fee sent to the treasury
+    pub protocol_swap_fee_bps: U256,
+}
+
+/// Emitted when the pool configuration is replaced
+#[odra::event]
+pub struct ConfigUpdated {
+    /// New target buffer percentage
+    pub buffer_target_bps: U256,
+    /// New swap fee
+    pub swap_fee_bps: U256,
+    /// New protocol fee on staking rewards
+    pub protocol_fee_bps: U256,
+    /// New share of each swap fee sent to the treasury
+    pub protocol_swap_fee_bps: U256,
+}
+
+/// Emitted when the treasury address changes
+#[odra::event]
+pub struct TreasuryUpdated {
+    /// New treasury address
+    pub treasury: Address,
+}
+
+/// Emitted when the admin role changes hands
+#[odra::event]
+pub struct AdminUpdated {
+    /// New admin address
+    pub admin: Address,
+}
+
+/// Emitted when the pool changes how it credits incoming paired tokens
+#[odra::event]
+pub struct TokenAccountingUpdated {
+    /// New accounting mode
+    pub accounting: TokenAccounting,
+}
+
+/// Emitted when the pool switches the validator it delegates to
+#[odra::event]
+pub struct ValidatorUpdated {
+    /// New validator public key
+    pub validator: PublicKey,
+}
+
+/// Emitted when a pool switches to the StableSwap curve
+#[odra::event]
+pub struct StableCurveEnabled {
+    /// Amplification coefficient
+    pub amp: u64,
+}
+
+/// Emitted when the admin starts ramping the amplification coefficient
+#[odra::event]
+pub struct AmpRampStarted {
+    /// Coefficient at the start of the ramp
+    pub initial_amp: u64,
+    /// Coefficient at the end of the ramp
+    pub future_amp: u64,
+    /// Ramp start time
+    pub initial_time: u64,
+    /// Ramp end time
+    pub future_time: u64,
+}
+
+/// Emitted when the admin stops an amplification ramp
+#[odra::event]
+pub struct AmpRampStopped {
+    /// Coefficient the pool stays at
+    pub amp: u64,
+}
+
+/// Emitted when accrued protocol swap fees are sent to the treasury
+#[odra::event]
+pub struct ProtocolFeesClaimed {
+    /// Treasury address receiving the fees
+    pub treasury: Address,
+    /// CSPR fees claimed
+    pub cspr_amount: U512,
+    /// Token fees claimed
+    pub token_amount: U512,
+}
+
+/// Emitted when reserves are reconciled with actual balances
+#[odra::event]
+pub struct Synced {
+    /// CSPR reserve after sync
+    pub reserve_cspr: U512,
+    /// Token reserve after sync
+    pub reserve_token: U512,
+    /// Staked CSPR after sync
+    pub staked_cspr: U512,
+    /// Buffer CSPR after sync
+    pub buffer_cspr: U512,
+}
+
+/// Emitted when surplus balances are sent out of the pool
+#[odra::event]
+pub struct Skimmed {
+    /// Recipient of the surplus
+    pub recipient: Address,
+    /// Surplus CSPR sent
+    pub cspr_amount: U512,
+    /// Surplus tokens sent
+    pub token_amount: U512,
+}
+
+/// Emitted when a delegation shortfall (e.g. slashing) is written down
+#[odra::event]
+pub struct LossRealized {
+    /// Delegated CSPR missing compared to the tracked stake
+    pub shortfall: U512,
+    /// Portion of the loss absorbed by the CSPR reserve (LPs)
+    pub lp_loss: U512,
+    /// Portion of the loss absorbed by pending withdrawals
+    pub queue_loss: U512,
+    /// Withdrawal payout index after the loss
+    pub payout_index: U512,
+}
+
+/// Emitted when a pending withdrawal request changes owner
+#[odra::event]
+pub struct WithdrawalTransferred {
+    /// Withdrawal request ID
+    pub withdrawal_id: u64,
+    /// Previous owner
+    pub from: Address,
+    /// New owner
+    pub to: Address,
+}
+
+/// Emitted when an owner approves (or revokes) a spender for a withdrawal request
+#[odra::event]
+pub struct WithdrawalApproval {
+    /// Withdrawal request ID
+    pub withdrawal_id: u64,
+    /// Current owner
+    pub owner: Address,
+    /// Approved spender (None revokes)
+    pub spender: Option<Address>,
+}
+
+/// Emitted when a pending withdrawal re-enters the pool
+#[odra::event]
+pub struct WithdrawalCancelled {
+    /// Owner re-entering the pool
+    pub user: Address,
+    /// Withdrawal request ID
+    pub withdrawal_id: u64,
+    /// CSPR returned to the reserve
+    pub cspr_amount: U512,
+    /// Tokens supplied back by the owner
+    pub token_amount: U512,
+    /// LP tokens minted
+    pub lp_minted: U512,
+    /// Whether the whole request was cancelled
+    pub fully_cancelled: bool,
+}
+
+/// Emitted when LP is redeemed for a single asset
+#[odra::event]
+pub struct ZappedOut {
+    /// Liquidity provider address
+    pub provider: Address,
+    /// LP tokens burned
+    pub lp_burned: U512,
+    /// Asset paid out
+    pub asset: ZapAsset,
+    /// Amount paid out (or queued, for CSPR)
+    pub amount_out: U512,
+    /// Withdrawal request ID when paid in CSPR
+    pub withdrawal_id: Option<u64>,
+}
+
+/// Emitted when the factory registers a pool
+#[odra::event]
+pub struct PoolRegistered {
+    /// Paired token address
+    pub token: Address,
+    /// Pool address
+    pub pool: Address,
+    /// Validator assigned to the pool
+    pub validator: PublicKey,
+    /// Index in the factory's pool list
+    pub index: u32,
+}
+
+/// Emitted when the factory registers a token/token pair
+#[odra::event]
+pub struct PairRegistered {
+    /// Lower-sorted token address
+    pub token0: Address,
+    /// Higher-sorted token address
+    pub token1: Address,
+    /// Pair address
+    pub pair: Address,
+    /// Index in the factory's pair list
+    pub index: u32,
+}
+
+/// Emitted when liquidity is added to a token/token pair
+#[odra::event]
+pub struct PairLiquidityAdded {
+    /// Liquidity provider address
+    pub provider: Address,
+    /// Token0 amount deposited
+    pub amount0: U512,
+    /// Token1 amount deposited
+    pub amount1: U512,
+    /// LP tokens minted
+    pub lp_minted: U512,
+}
+
+/// Emitted when liquidity is removed from a token/token pair
+#[odra::event]
+pub struct PairLiquidityRemoved {
+    /// Liquidity provider address
+    pub provider: Address,
+    /// LP tokens burned
+    pub lp_burned: U512,
+    /// Token0 amount withdrawn
+    pub amount0: U512,
+    /// Token1 amount withdrawn
+    pub amount1: U512,
+}
+
+/// Emitted on a token/token pair swap
+#[odra::event]
+pub struct PairSwap {
+    /// Sender address
+    pub sender: Address,
+    /// Token sold
+    pub token_in: Address,
+    /// Amount sold
+    pub amount_in: U512,
+    /// Amount of the other token bought
+    pub amount_out: U512,
+}
+
+/// Emitted when a pair's accrued protocol swap fees are sent to the treasury
+#[odra::event]
+pub struct PairFeesClaimed {
+    /// Treasury receiving the fees
+    pub treasury: Address,
+    /// Token0 fees sent
+    pub amount0: U512,
+    /// Token1 fees sent
+    pub amount1: U512,
+}
+
+/// Emitted when a concentrated-liquidity position is opened
+#[odra::event]
+pub struct PositionMinted {
+    /// Position owner
+    pub owner: Address,
+    /// Position ID
+    pub position_id: u64,
+    /// Lower bound of the price range
+    pub tick_lower: i32,
+    /// Upper bound of the price range
+    pub tick_upper: i32,
+}
+
+/// Emitted when liquidity is added to a position
+#[odra::event]
+pub struct PositionIncreased {
+    /// Position ID
+    pub position_id: u64,
+    /// Liquidity added
+    pub liquidity: U512,
+    /// CSPR deposited
+    pub cspr_amount: U512,
+    /// Tokens deposited
+    pub token_amount: U512,
+}
+
+/// Emitted when liquidity is removed from a position
+#[odra::event]
+pub struct PositionDecreased {
+    /// Position ID
+    pub position_id: u64,
+    /// Liquidity removed
+    pub liquidity: U512,
+    /// CSPR credited to the position
+    pub cspr_amount: U512,
+    /// Tokens credited to the position
+    pub token_amount: U512,
+    /// When the position's owed CSPR can be collected
+    pub cspr_unlock_time: u64,
+}
+
+/// Emitted when a position's owed CSPR and tokens are paid out
+#[odra::event]
+pub struct PositionCollected {
+    /// Position ID
+    pub position_id: u64,
+    /// Recipient of the payout
+    pub recipient: Address,
+    /// CSPR paid
+    pub cspr_amount: U512,
+    /// Tokens paid
+    pub token_amount: U512,
+}
+
+/// Emitted when an empty position is closed
+#[odra::event]
+pub struct PositionBurned {
+    /// Position ID
+    pub position_id: u64,
+    /// Last owner
+    pub owner: Address,
+}
+
+/// Emitted when a position changes owner
+#[odra::event]
+pub struct PositionTransferred {
+    /// Position ID
+    pub position_id: u64,
+    /// Previous owner
+    pub from: Address,
+    /// New owner
+    pub to: Address,
+}
+
+/// Emitted when an owner approves (or revokes) a spender for a position
+#[odra::event]
+pub struct PositionApproval {
+    /// Position ID
+    pub position_id: u64,
+    /// Current owner
+    pub owner: Address,
+    /// Approved spender (None revokes)
+    pub spender: Option<Address>,
+}
+
+/// Emitted when CSPR is deposited for stCSPR
+#[odra::event]
+pub struct StakedCsprMinted {
+    /// Depositor
+    pub owner: Address,
+    /// CSPR deposited
+    pub cspr_amount: U512,
+    /// stCSPR minted
+    pub shares: U512,
+}
+
+/// Emitted when stCSPR is burned for a CSPR redemption
+#[odra::event]
+pub struct UnstakeRequested {
+    /// Holder redeeming
+    pub owner: Address,
+    /// Unstake request ID
+    pub request_id: u64,
+    /// stCSPR burned
+    pub shares: U512,
+    /// CSPR owed
+    pub cspr_amount: U512,
+    /// When the CSPR can be claimed
+    pub claimable_time: u64,
+}
+
+/// Emitted when a redeemed CSPR amount is paid out
+#[odra::event]
+pub struct UnstakeClaimed {
+    /// Holder paid
+    pub owner: Address,
+    /// Unstake request ID
+    pub request_id: u64,
+    /// CSPR paid
+    pub cspr_amount: U512,
+}
+
+/// Emitted when stCSPR recognizes staking rewards (or losses) in its exchange rate
+#[odra::event]
+pub struct StakedCsprCompounded {
+    /// Rewards recognized
+    pub rewards: U512,
+    /// Delegation shortfall written off (e.g. slashing)
+    pub loss: U512,
+    /// stCSPR minted to the treasury as its fee
+    pub fee_shares: U512,
+    /// CSPR value of one stCSPR afterwards
+    pub exchange_rate: U512,
+}
+
+/// Emitted when a pool starts staking through a liquid staking token
+#[odra::event]
+pub struct LiquidStakingEnabled {
+    /// Liquid staking token contract
+    pub st_cspr: Address,
+}
+
+/// Emitted when stCSPR is deposited into a pool as staked CSPR
+#[odra::event]
+pub struct StakedCsprDeposited {
+    /// Liquidity provider address
+    pub provider: Address,
+    /// stCSPR transferred to the pool
+    pub shares: U512,
+    /// CSPR value credited to the reserve
+    pub cspr_amount: U512,
+    /// Token amount added
+    pub token_amount: U512,
+    /// LP tokens minted
+    pub lp_minted: U512,
+}
+
+/// Emitted when LP shares are staked in a gauge
+#[odra::event]
+pub struct GaugeDeposited {
+    /// Staker address
+    pub user: Address,
+    /// LP shares staked
+    pub amount: U512,
+}
+
+/// Emitted when LP shares are unstaked from a gauge
+#[odra::event]
+pub struct GaugeWithdrawn {
+    /// Staker address
+    pub user: Address,
+    /// LP shares returned
+    pub amount: U512,
+}
+
+/// Emitted when gauge rewards are paid out
+#[odra::event]
+pub struct RewardHarvested {
+    /// Staker address
+    pub user: Address,
+    /// Reward tokens paid
+    pub amount: U512,
+}
+
+/// Emitted when a staker leaves a gauge forfeiting unharvested rewards
+#[odra::event]
+pub struct GaugeEmergencyWithdrawn {
+    /// Staker address
+    pub user: Address,
+    /// LP shares returned
+    pub amount: U512,
+    /// Rewards forfeited
+    pub forfeited: U512,
+}
+
+/// Emitted when an incentive campaign is scheduled
+#[odra::event]
+pub struct CampaignScheduled {
+    /// Campaign ID
+    pub campaign_id: u32,
+    /// Reward tokens emitted per second
+    pub reward_per_second: U512,
+    /// Emission start time
+    pub start_time: u64,
+    /// Emission end time
+    pub end_time: u64,
+}
@@ -0,0 +1,304 @@
+//! Ghost Pool Factory - registry of CSPR/token pools and token/token pairs with shared defaults
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::PublicKey;
+
+use crate::events::*;
+use crate::pair::GhostPoolPairContractRef;
+use crate::pool::GhostPoolPoolContractRef;
+use crate::stable_curve::StableCurvePoolContractRef;
+use crate::types::*;
+
+/// Admin entry point shared by pools and pairs
+#[odra::external_contract]
+pub trait AdminManaged {
+    /// Hand the admin role over to `admin`
+    fn set_admin(&mut self, admin: Address);
+}
+
+/// Registry of Ghost Pool deployments, one pool per CEP-18 token and one pair per token pair
+///
+/// Pools and pairs are deployed with the factory as their admin and then registered here.
+/// Registration pushes the factory's defaults (config, treasury and, for pools, the next
+/// validator of the set), so every deployment starts from the same settings. Later default
+/// changes only reach future registrations; the factory forwards curve settings to registered
+/// targets, and `transfer_admin` hands a target to an admin that can tune it directly.
+#[odra::module]
+pub struct GhostPoolFactory {
+    /// Admin address
+    admin: Var<Address>,
+    /// Treasury new pools send protocol fees to
+    treasury: Var<Address>,
+    /// Configuration new pools inherit
+    default_config: Var<PoolConfig>,
+    /// Validators assigned to new pools in turn
+    validators: Var<Vec<PublicKey>>,
+    /// Index of the validator the next pool gets
+    next_validator_index: Var<u32>,
+    /// Pool address by paired token
+    pools: Mapping<Address, Address>,
+    /// Pool addresses in registration order
+    all_pools: Mapping<u32, Address>,
+    /// Number of registered pools
+    pool_count: Var<u32>,
+    /// Pair address by sorted (token0, token1)
+    pairs: Mapping<(Address, Address), Address>,
+    /// Pair addresses in registration order
+    all_pairs: Mapping<u32, Address>,
+    /// Number of registered pairs
+    pair_count: Var<u32>,
+}
+
+#[odra::module]
+impl GhostPoolFactory {
+    /// Initialize the factory
+    #[odra(init)]
+    pub fn init(&mut self, admin: Address, treasury: Address, validators: Vec<PublicKey>) {
+        if validators.is_empty() {
+            self.env().revert(FactoryError::NoValidators);
+        }
+
+        self.admin.set(admin);
+        self.treasury.set(treasury);
+        self.default_config.set(PoolConfig::with_defaults());
+        self.validators.set(validators);
+        self.next_validator_index.set(0);
+        self.pool_count.set(0);
+        self.pair_count.set(0);
+    }
+
+    // ============ REGISTRY ============
+
+    /// Register a pool deployed with this factory as admin (admin only)
+    /// Applies the current defaults and returns the pool's index.
+    /// The pool must not have staked CSPR yet, since its validator is reassigned.
+    pub fn register_pool(&mut self, pool: Address) -> u32 {
+        self.require_admin();
+
+        let mut pool_ref = GhostPoolPoolContractRef::new(self.env(), pool);
+        if pool_ref.admin() != self.env().self_address() {
+            self.env().revert(FactoryError::PoolNotOwned);
+        }
+
+        let token = pool_ref.token_address();
+        if self.pools.get(&token).is_some() {
+            self.env().revert(FactoryError::PoolExists);
+        }
+
+        // Inherit shared defaults
+        pool_ref.set_config(self.default_config.get_or_default());
+        pool_ref.set_treasury(self.treasury());
+        let validator = self.take_next_validator();
+        pool_ref.set_validator(validator.clone());
+
+        let index = self.pool_count.get_or_default();
+        self.pools.set(&token, pool);
+        self.all_pools.set(&index, pool);
+        self.pool_count.set(index + 1);
+
+        self.env().emit_event(PoolRegistered {
+            token,
+            pool,
+            validator,
+            index,
+        });
+
+        index
+    }
+
+    /// Register a token/token pair deployed with this factory as admin (admin only)
+    /// Applies the current config and treasury and returns the pair's index
+    pub fn register_pair(&mut self, pair: Address) -> u32 {
+        self.require_admin();
+
+        let mut pair_ref = GhostPoolPairContractRef::new(self.env(), pair);
+        if pair_ref.admin() != self.env().self_address() {
+            self.env().revert(FactoryError::PoolNotOwned);
+        }
+
+        let token0 = pair_ref.token0();
+        let token1 = pair_ref.token1();
+        if self.pairs.get(&(token0, token1)).is_some() {
+            self.env().revert(FactoryError::PoolExists);
+        }
+
+        // Inherit shared defaults
+        pair_ref.set_config(self.default_config.get_or_default());
+        pair_ref.set_treasury(self.treasury());
+
+        let index = self.pair_count.get_or_default();
+        self.pairs.set(&(token0, token1), pair);
+        self.all_pairs.set(&index, pair);
+        self.pair_count.set(index + 1);
+
+        self.env().emit_event(PairRegistered {
+            token0,
+            token1,
+            pair,
+            index,
+        });
+
+        index
+    }
+
+    // ============ ADMIN ============
+
+    /// Set the configuration future pools inherit (admin only)
+    /// Already registered pools and pairs keep their configuration.
+    pub fn set_default_config(&mut self, config: PoolConfig) {
+        self.require_admin();
+        if !config.is_valid() {
+            self.env().revert(FactoryError::InvalidConfig);
+        }
+        self.default_config.set(config);
+    }
+
+    /// Set the treasury future pools inherit (admin only)
+    /// Already registered pools and pairs keep their treasury.
+    pub fn set_treasury(&mut self, treasury: Address) {
+        self.require_admin();
+        self.treasury.set(treasury);
+    }
+
+    /// Replace the validator set future pools are assigned from (admin only)
+    pub fn set_validators(&mut self, validators: Vec<PublicKey>) {
+        self.require_admin();
+        if validators.is_empty() {
+            self.env().revert(FactoryError::NoValidators);
+        }
+        self.validators.set(validators);
+        self.next_validator_index.set(0);
+    }
+
+    /// Switch a registered pool or pair to the StableSwap curve (admin only)
+    /// The target must not hold liquidity yet
+    pub fn set_stable_curve(&mut self, target: Address, amp: u64) {
+        self.require_admin();
+        StableCurvePoolContractRef::new(self.env(), target).set_stable_curve(amp);
+    }
+
+    /// Ramp the amplification coefficient of a registered pool or pair (admin only)
+    pub fn ramp_amp(&mut self, target: Address, future_amp: u64, future_time: u64) {
+        self.require_admin();
+        StableCurvePoolContractRef::new(self.env(), target).ramp_amp(future_amp, future_time);
+    }
+
+    /// Stop the amplification ramp of a registered pool or pair (admin only)
+    pub fn stop_amp_ramp(&mut self, target: Address) {
+        self.require_admin();
+        StableCurvePoolContractRef::new(self.env(), target).stop_amp_ramp();
+    }
+
+    /// Hand admin of a pool or pair this factory administers to `new_admin` (admin only)
+    /// The target stays registered, but the factory can no longer forward settings to it.
+    pub fn transfer_admin(&mut self, target: Address, new_admin: Address) {
+        self.require_admin();
+        AdminManagedContractRef::new(self.env(), target).set_admin(new_admin);
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Get the pool paired with `token`, if registered
+    pub fn get_pool(&self, token: Address) -> Option<Address> {
+        self.pools.get(&token)
+    }
+
+    /// Get the number of registered pools
+    pub fn all_pools_length(&self) -> u32 {
+        self.pool_count.get_or_default()
+    }
+
+    /// Get registered pools at indices [offset, offset + limit)
+    /// At most `MAX_POOLS_PAGE_SIZE` pools are returned per call
+    pub fn all_pools(&self, offset: u32, limit: u32) -> Vec<Address> {
+        let count = self.pool_count.get_or_default();
+        let end = offset
+            .saturating_add(limit.min(MAX_POOLS_PAGE_SIZE))
+            .min(count);
+
+        (offset..end).filter_map(|index| self.all_pools.get(&index)).collect()
+    }
+
+    /// Get the pair trading `token_a` against `token_b` (in either order), if registered
+    pub fn get_pair(&self, token_a: Address, token_b: Address) -> Option<Address> {
+        let key = if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) };
+        self.pairs.get(&key)
+    }
+
+    /// Get the number of registered pairs
+    pub fn all_pairs_length(&self) -> u32 {
+        self.pair_count.get_or_default()
+    }
+
+    /// Get registered pairs at indices [offset, offset + limit)
+    /// At most `MAX_POOLS_PAGE_SIZE` pairs are returned per call
+    pub fn all_pairs(&self, offset: u32, limit: u32) -> Vec<Address> {
+        let count = self.pair_count.get_or_default();
+        let end = offset
+            .saturating_add(limit.min(MAX_POOLS_PAGE_SIZE))
+            .min(count);
+
+        (offset..end).filter_map(|index| self.all_pairs.get(&index)).collect()
+    }
+
+    /// Get the configuration new pools inherit
+    pub fn default_config(&self) -> PoolConfig {
+        self.default_config.get_or_default()
+    }
+
+    /// Get the treasury new pools inherit
+    pub fn treasury(&self) -> Address {
+        self.treasury.get().expect("Treasury not set")
+    }
+
+    /// Get the validator set
+    pub fn validators(&self) -> Vec<PublicKey> {
+        self.validators.get_or_default()
+    }
+
+    /// Get the validator the next registered pool will delegate to
+    pub fn next_validator(&self) -> PublicKey {
+        let validators = self.validators.get_or_default();
+        let index = self.next_validator_index.get_or_default() as usize % validators.len();
+        validators[index].clone()
+    }
+
+    /// Get the admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    // ============ INTERNAL FUNCTIONS ============
+
+    fn require_admin(&self) {
+        if self.env().caller() != self.admin() {
+            self.env().revert(FactoryError::NotAdmin);
+        }
+    }
+
+    /// Round-robin over the validator set
+    fn take_next_validator(&mut self) -> PublicKey {
+        let validator = self.next_validator();
+        let len = self.validators.get_or_default().len() as u32;
+        let index = self.next_validator_index.get_or_default();
+        self.next_validator_index.set((index + 1) % len);
+        validator
+    }
+}
+
+/// Factory errors
+#[odra::odra_error]
+pub enum FactoryError {
+    /// Caller is not the admin
+    NotAdmin = 1,
+    /// A pool for this token is already registered
+    PoolExists = 2,
+    /// Pool admin is not this factory
+    PoolNotOwned = 3,
+    /// Validator set is empty
+    NoValidators = 4,
+    /// Configuration parameter out of range
+    InvalidConfig = 5,
+}
@@ -0,0 +1,114 @@
+//! Fee Token - CEP-18 token taking a fee on every transfer, for testing balance-delta accounting
+//!
+//! Only compiled natively or with the `test-mode` feature; never part of a production build.
+
+use odra::prelude::*;
+use odra::casper_types::U256;
+use odra_modules::cep18_token::Cep18;
+
+use crate::types::BPS_DENOMINATOR;
+
+/// Test token that diverts `fee_bps` of each transfer to the token contract itself
+/// The recipient receives the rest; `mint` and `burn` stand in for rebases.
+/// Collecting into the contract keeps every account, the deployer included, free to transfer.
+#[odra::module]
+pub struct FeeToken {
+    /// Underlying CEP-18 token
+    cep18: SubModule<Cep18>,
+    /// Fee taken from each transfer (100 = 1%)
+    fee_bps: Var<u64>,
+}
+
+#[odra::module]
+impl FeeToken {
+    /// Initialize the token; `Cep18::init` mints the initial supply to the deployer
+    pub fn init(&mut self, name: String, symbol: String, decimals: u8, initial_supply: U256, fee_bps: u64) {
+        self.cep18.init(symbol, name, decimals, initial_supply);
+        self.fee_bps.set(fee_bps);
+    }
+
+    /// Change the transfer fee (for testing)
+    pub fn set_fee_bps(&mut self, fee_bps: u64) {
+        self.fee_bps.set(fee_bps);
+    }
+
+    /// Get the transfer fee
+    pub fn fee_bps(&self) -> u64 {
+        self.fee_bps.get_or_default()
+    }
+
+    /// Mint tokens (for testing; a positive rebase when minting to a pool)
+    pub fn mint(&mut self, to: &Address, amount: &U256) {
+        self.cep18.raw_mint(to, amount);
+    }
+
+    /// Burn tokens (for testing; a negative rebase when burning from a pool)
+    pub fn burn(&mut self, owner: &Address, amount: &U256) {
+        self.cep18.raw_burn(owner, amount);
+    }
+
+    /// Get total supply
+    pub fn total_supply(&self) -> U256 {
+        self.cep18.total_supply()
+    }
+
+    /// Get balance of address
+    pub fn balance_of(&self, address: &Address) -> U256 {
+        self.cep18.balance_of(address)
+    }
+
+    /// Transfer tokens; the recipient receives `amount` minus the fee
+    pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
+        let fee = self.fee_on(amount);
+        self.cep18.transfer(recipient, &(*amount - fee));
+        if fee > U256::zero() {
+            let collector = self.collector();
+            self.cep18.transfer(&collector, &fee);
+        }
+    }
+
+    /// Transfer from; spends `amount` of allowance, the recipient receives `amount` minus the fee
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        let fee = self.fee_on(amount);
+        self.cep18.transfer_from(owner, recipient, &(*amount - fee));
+        if fee > U256::zero() {
+            let collector = self.collector();
+            self.cep18.transfer_from(owner, &collector, &fee);
+        }
+    }
+
+    /// Approve spender
+    pub fn approve(&mut self, spender: &Address, amount: &U256) {
+        self.cep18.approve(spender, amount);
+    }
+
+    /// Get allowance
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        self.cep18.allowance(owner, spender)
+    }
+
+    /// Get token name
+    pub fn name(&self) -> String {
+        self.cep18.name()
+    }
+
+    /// Get token symbol
+    pub fn symbol(&self) -> String {
+        self.cep18.symbol()
+    }
+
+    /// Get token decimals
+    pub fn decimals(&self) -> u8 {
+        self.cep18.decimals()
+    }
+}
+
+impl FeeToken {
+    fn fee_on(&self, amount: &U256) -> U256 {
+        *amount * U256::from(self.fee_bps()) / U256::from(BPS_DENOMINATOR)
+    }
+
+    fn collector(&self) -> Address {
+        self.env().self_address()
+    }
+}
@@ -0,0 +1,386 @@
+//! Gauge - LP staking farm distributing incentive tokens
+
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{U256, U512};
+
+use crate::events::*;
+use crate::pool::Cep18TokenContractRef;
+use crate::types::*;
+
+/// Liquidity-mining gauge for one LP token
+///
+/// Stakers deposit LP shares and earn a CEP-18 reward token, MasterChef style: every
+/// update adds the rewards emitted since the last one, divided by the total stake, to
+/// an accumulated reward per share; a staker is owed their stake times its growth since
+/// their last checkpoint. Emissions come from admin-scheduled campaigns, funded in full
+/// when scheduled and run back to back. Emissions while nothing is staked are kept aside
+/// for the admin to recover.
+#[odra::module]
+pub struct GhostPoolGauge {
+    // ============ TOKENS ============
+    /// LP token staked in the gauge (a pool address)
+    staking_token: Var<Address>,
+    /// CEP-18 token paid as rewards
+    reward_token: Var<Address>,
+
+    // ============ CAMPAIGNS ============
+    /// Campaigns by ID, in emission order
+    campaigns: Mapping<u32, Campaign>,
+    /// Number of campaigns scheduled
+    campaign_count: Var<u32>,
+    /// First campaign that has not ended as of the last update
+    current_campaign: Var<u32>,
+
+    // ============ ACCOUNTING ============
+    /// Rewards per staked share accumulated so far (scaled by REWARD_PER_SHARE_SCALE)
+    acc_reward_per_share: Var<U512>,
+    /// Time rewards were last accumulated up to
+    last_reward_time: Var<u64>,
+    /// Total LP shares staked
+    total_staked: Var<U512>,
+    /// Stake by user
+    stakes: Mapping<Address, GaugeStake>,
+    /// Rewards emitted while nothing was staked, or forfeited
+    undistributed: Var<U512>,
+
+    // ============ ADMIN ============
+    /// Admin address
+    admin: Var<Address>,
+}
+
+#[odra::module]
+impl GhostPoolGauge {
+    /// Initialize the gauge
+    #[odra(init)]
+    pub fn init(&mut self, staking_token: Address, reward_token: Address, admin: Address) {
+        self.staking_token.set(staking_token);
+        self.reward_token.set(reward_token);
+        self.admin.set(admin);
+
+        self.campaign_count.set(0);
+        self.current_campaign.set(0);
+        self.acc_reward_per_share.set(U512::zero());
+        self.last_reward_time.set(self.env().get_block_time());
+        self.total_staked.set(U512::zero());
+        self.undistributed.set(U512::zero());
+    }
+
+    // ============ STAKING ============
+
+    /// Stake LP shares (requires prior approval of the gauge)
+    pub fn deposit(&mut self, amount: U512) {
+        let caller = self.env().caller();
+        if amount == U512::zero() {
+            self.env().revert(GaugeError::ZeroAmount);
+        }
+
+        self.update_rewards();
+        let mut stake = self.checkpoint(caller);
+
+        let self_address = self.env().self_address();
+        Cep18TokenContractRef::new(self.env(), self.staking_token())
+            .transfer_from(&caller, &self_address, &U256::from(amount.as_u128()));
+
+        stake.amount += amount;
+        self.store_stake(caller, stake);
+        self.total_staked.set(self.total_staked.get_or_default() + amount);
+
+        self.env().emit_event(GaugeDeposited { user: caller, amount });
+    }
+
+    /// Unstake LP shares; accrued rewards stay claimable via `harvest`
+    pub fn withdraw(&mut self, amount: U512) {
+        let caller = self.env().caller();
+        if amount == U512::zero() {
+            self.env().revert(GaugeError::ZeroAmount);
+        }
+
+        self.update_rewards();
+        let mut stake = self.checkpoint(caller);
+        if amount > stake.amount {
+            self.env().revert(GaugeError::InsufficientStake);
+        }
+
+        stake.amount -= amount;
+        self.store_stake(caller, stake);
+        self.total_staked.set(self.total_staked.get_or_default() - amount);
+
+        Cep18TokenContractRef::new(self.env(), self.staking_token())
+            .transfer(&caller, &U256::from(amount.as_u128()));
+
+        self.env().emit_event(GaugeWithdrawn { user: caller, amount });
+    }
+
+    /// Pay out the caller's accrued rewards
+    pub fn harvest(&mut self) -> U512 {
+        let caller = self.env().caller();
+
+        self.update_rewards();
+        let mut stake = self.checkpoint(caller);
+        let reward = stake.pending;
+        if reward == U512::zero() {
+            return reward;
+        }
+
+        stake.pending = U512::zero();
+        self.store_stake(caller, stake);
+
+        Cep18TokenContractRef::new(self.env(), self.reward_token())
+            .transfer(&caller, &U256::from(reward.as_u128()));
+
+        self.env().emit_event(RewardHarvested { user: caller, amount: reward });
+
+        reward
+    }
+
+    /// Unstake everything without touching reward accounting, forfeiting unharvested rewards
+    /// For use if reward payouts fail; forfeited rewards become recoverable by the admin.
+    pub fn emergency_withdraw(&mut self) -> U512 {
+        let caller = self.env().caller();
+        let stake = self.stakes.get(&caller).unwrap_or_default();
+        if stake.amount == U512::zero() {
+            self.env().revert(GaugeError::InsufficientStake);
+        }
+
+        let acc = self.acc_reward_per_share.get_or_default();
+        let forfeited = stake.pending + self.accrued_since_checkpoint(&stake, acc);
+        self.stakes.set(&caller, GaugeStake::default());
+        self.total_staked.set(self.total_staked.get_or_default() - stake.amount);
+        self.undistributed.set(self.undistributed.get_or_default() + forfeited);
+
+        Cep18TokenContractRef::new(self.env(), self.staking_token())
+            .transfer(&caller, &U256::from(stake.amount.as_u128()));
+
+        self.env().emit_event(GaugeEmergencyWithdrawn {
+            user: caller,
+            amount: stake.amount,
+            forfeited,
+        });
+
+        stake.amount
+    }
+
+    // ============ ADMIN FUNCTIONS ============
+
+    /// Schedule a campaign emitting `reward_per_second` between `start_time` and `end_time` (admin only)
+    ///
+    /// Campaigns run back to back: a new one may not start before the last one ends. The
+    /// full emission is pulled from the admin up front (requires prior approval).
+    pub fn schedule_campaign(&mut self, reward_per_second: U512, start_time: u64, end_time: u64) -> u32 {
+        self.require_admin();
+
+        let campaign_id = self.campaign_count.get_or_default();
+        let earliest_start = match campaign_id.checked_sub(1) {
+            Some(last) => self.get_campaign(last).end_time.max(self.env().get_block_time()),
+            None => self.env().get_block_time(),
+        };
+        if start_time < earliest_start || end_time <= start_time {
+            self.env().revert(GaugeError::InvalidSchedule);
+        }
+
+        let campaign = Campaign {
+            id: campaign_id,
+            reward_per_second,
+            start_time,
+            end_time,
+        };
+        let total = campaign.total_rewards();
+        if total == U512::zero() {
+            self.env().revert(GaugeError::ZeroAmount);
+        }
+
+        self.update_rewards();
+        self.campaigns.set(&campaign_id, campaign);
+        self.campaign_count.set(campaign_id + 1);
+
+        let caller = self.env().caller();
+        let self_address = self.env().self_address();
+        Cep18TokenContractRef::new(self.env(), self.reward_token())
+            .transfer_from(&caller, &self_address, &U256::from(total.as_u128()));
+
+        self.env().emit_event(CampaignScheduled {
+            campaign_id,
+            reward_per_second,
+            start_time,
+            end_time,
+        });
+
+        campaign_id
+    }
+
+    /// Send rewards emitted while nothing was staked, or forfeited, to `recipient` (admin only)
+    pub fn recover_undistributed(&mut self, recipient: Address) -> U512 {
+        self.require_admin();
+        self.update_rewards();
+
+        let amount = self.undistributed.get_or_default();
+        if amount > U512::zero() {
+            self.undistributed.set(U512::zero());
+            Cep18TokenContractRef::new(self.env(), self.reward_token())
+                .transfer(&recipient, &U256::from(amount.as_u128()));
+        }
+
+        amount
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Rewards `user` could harvest now
+    pub fn pending_reward(&self, user: Address) -> U512 {
+        let stake = self.stakes.get(&user).unwrap_or_default();
+        let (acc, _, _, _) = self.accumulate(self.env().get_block_time());
+        stake.pending + self.accrued_since_checkpoint(&stake, acc)
+    }
+
+    /// Get a user's stake
+    pub fn get_stake(&self, user: Address) -> GaugeStake {
+        self.stakes.get(&user).unwrap_or_default()
+    }
+
+    /// Get total LP shares staked
+    pub fn total_staked(&self) -> U512 {
+        self.total_staked.get_or_default()
+    }
+
+    /// Get a campaign
+    pub fn get_campaign(&self, campaign_id: u32) -> Campaign {
+        self.campaigns
+            .get(&campaign_id)
+            .unwrap_or_revert_with(&self.env(), GaugeError::CampaignNotFound)
+    }
+
+    /// Get the number of campaigns scheduled
+    pub fn campaign_count(&self) -> u32 {
+        self.campaign_count.get_or_default()
+    }
+
+    /// Get the campaign emitting now, if any
+    pub fn active_campaign(&self) -> Option<Campaign> {
+        let now = self.env().get_block_time();
+        (self.current_campaign.get_or_default()..self.campaign_count())
+            .map(|id| self.get_campaign(id))
+            .find(|campaign| campaign.end_time > now)
+            .filter(|campaign| campaign.start_time <= now)
+    }
+
+    /// Get the accumulated reward per share (scaled by REWARD_PER_SHARE_SCALE)
+    pub fn acc_reward_per_share(&self) -> U512 {
+        self.accumulate(self.env().get_block_time()).0
+    }
+
+    /// Get rewards emitted while nothing was staked, or forfeited
+    pub fn undistributed_rewards(&self) -> U512 {
+        self.undistributed.get_or_default()
+    }
+
+    /// Get the staked LP token address
+    pub fn staking_token(&self) -> Address {
+        self.staking_token.get().expect("Staking token not set")
+    }
+
+    /// Get the reward token address
+    pub fn reward_token(&self) -> Address {
+        self.reward_token.get().expect("Reward token not set")
+    }
+
+    /// Get admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    // ============ INTERNAL ============
+
+    /// Accumulated reward per share as of `now`, with the next current campaign, the time
+    /// accounted up to (earlier than `now` if MAX_CAMPAIGN_SCAN campaigns ended) and the
+    /// rewards emitted meanwhile
+    fn accumulate(&self, now: u64) -> (U512, u32, u64, U512) {
+        let mut acc = self.acc_reward_per_share.get_or_default();
+        let last = self.last_reward_time.get_or_default();
+        let mut campaign_id = self.current_campaign.get_or_default();
+        if now <= last {
+            return (acc, campaign_id, last, U512::zero());
+        }
+
+        let count = self.campaign_count.get_or_default();
+        let mut until = now;
+        let mut emitted = U512::zero();
+        let mut scanned = 0;
+        while campaign_id < count {
+            if scanned == MAX_CAMPAIGN_SCAN {
+                until = self.get_campaign(campaign_id - 1).end_time;
+                break;
+            }
+            let campaign = self.get_campaign(campaign_id);
+            emitted += campaign.emitted_between(last, now);
+            if campaign.end_time > now {
+                break;
+            }
+            campaign_id += 1;
+            scanned += 1;
+        }
+
+        let total = self.total_staked.get_or_default();
+        if total > U512::zero() {
+            acc += (emitted * U512::from(REWARD_PER_SHARE_SCALE)) / total;
+        }
+
+        (acc, campaign_id, until, emitted)
+    }
+
+    /// Bring the accumulated reward per share up to the current time
+    fn update_rewards(&mut self) {
+        let now = self.env().get_block_time();
+        let (acc, campaign_id, until, emitted) = self.accumulate(now);
+
+        // Nobody to credit: keep the emission aside
+        if self.total_staked.get_or_default() == U512::zero() && emitted > U512::zero() {
+            self.undistributed.set(self.undistributed.get_or_default() + emitted);
+        }
+
+        self.acc_reward_per_share.set(acc);
+        self.current_campaign.set(campaign_id);
+        self.last_reward_time.set(until);
+    }
+
+    /// Rewards earned by `stake` since its checkpoint, at accumulated reward per share `acc`
+    fn accrued_since_checkpoint(&self, stake: &GaugeStake, acc: U512) -> U512 {
+        ((stake.amount * acc) / U512::from(REWARD_PER_SHARE_SCALE)).saturating_sub(stake.reward_debt)
+    }
+
+    /// Move the user's accrued rewards into `pending` (rewards must be up to date)
+    fn checkpoint(&self, user: Address) -> GaugeStake {
+        let mut stake = self.stakes.get(&user).unwrap_or_default();
+        stake.pending += self.accrued_since_checkpoint(&stake, self.acc_reward_per_share.get_or_default());
+        stake
+    }
+
+    /// Store a stake, resetting its reward debt to the current accumulated reward per share
+    fn store_stake(&mut self, user: Address, mut stake: GaugeStake) {
+        stake.reward_debt =
+            (stake.amount * self.acc_reward_per_share.get_or_default()) / U512::from(REWARD_PER_SHARE_SCALE);
+        self.stakes.set(&user, stake);
+    }
+
+    fn require_admin(&self) {
+        let admin = self.admin.get().expect("Admin not set");
+        if self.env().caller() != admin {
+            self.env().revert(GaugeError::NotAdmin);
+        }
+    }
+}
+
+/// Gauge errors
+#[odra::odra_error]
+pub enum GaugeError {
+    /// Zero amount provided
+    ZeroAmount = 1,
+    /// Stake too low
+    InsufficientStake = 2,
+    /// Caller is not the admin
+    NotAdmin = 3,
+    /// Campaign window invalid or overlapping the previous campaign
+    InvalidSchedule = 4,
+    /// Campaign not found
+    CampaignNotFound = 5,
+}
@@ -7,14 +7,51 @@
 
 extern crate alloc;
 
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub mod auction_simulator;
+pub mod cl_math;
+pub mod cl_pool;
 pub mod events;
+pub mod factory;
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub mod fee_token;
+pub mod gauge;
+pub mod lp_earnings;
 pub mod lp_token;
+pub mod math;
+pub mod paired_token;
+pub mod pair;
 pub mod pool;
+pub mod pool_ledger;
+pub mod position_nft;
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub mod reentrant_token;
+pub mod router;
+pub mod st_cspr;
+pub mod stable_curve;
+pub mod stable_math;
+pub mod staking;
 pub mod test_token;
+pub mod tick_book;
 pub mod types;
+pub mod withdrawal_queue;
 
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub use auction_simulator::AuctionSimulator;
+pub use cl_pool::GhostPoolClPool;
 pub use events::*;
+pub use factory::GhostPoolFactory;
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub use fee_token::FeeToken;
+pub use gauge::GhostPoolGauge;
 pub use lp_token::LpToken;
+pub use pair::GhostPoolPair;
 pub use pool::GhostPoolPool;
+#[cfg(any(not(target_arch = "wasm32"), feature = "test-mode"))]
+pub use reentrant_token::ReentrantToken;
+pub use router::GhostPoolRouter;
+pub use st_cspr::StakedCspr;
+pub use stable_curve::StableCurve;
+pub use staking::StakingBackend;
 pub use test_token::TestToken;
 pub use types::*;
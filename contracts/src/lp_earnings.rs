@@ -0,0 +1,87 @@
+//! LP earnings - per-share indexes attributing pool returns to LP holders
+
+use odra::prelude::*;
+use odra::casper_types::U512;
+
+use crate::types::{LpCheckpoint, LpEarnings, PAYOUT_INDEX_SCALE};
+
+/// Cumulative fees and staking rewards per LP share, with a checkpoint per holder
+///
+/// Each index grows by the amount credited to the reserves divided by the LP supply at
+/// that moment. A holder's earnings are their balance times the index growth since their
+/// checkpoint, which must be taken before every change of their balance.
+#[odra::module]
+pub struct LpEarningsIndex {
+    /// CSPR swap fees per LP share (scaled by PAYOUT_INDEX_SCALE)
+    fees_cspr_per_share: Var<U512>,
+    /// Token swap fees per LP share (scaled by PAYOUT_INDEX_SCALE)
+    fees_token_per_share: Var<U512>,
+    /// Staking rewards per LP share (scaled by PAYOUT_INDEX_SCALE)
+    staking_rewards_per_share: Var<U512>,
+    /// Checkpoint by holder
+    checkpoints: Mapping<Address, LpCheckpoint>,
+}
+
+#[odra::module]
+impl LpEarningsIndex {
+    /// Credit swap fees left in the reserves to the current LP supply
+    pub fn accrue_fees(&mut self, fees_cspr: U512, fees_token: U512, total_supply: U512) {
+        if total_supply == U512::zero() {
+            return;
+        }
+        if fees_cspr > U512::zero() {
+            let index = self.fees_cspr_per_share.get_or_default();
+            self.fees_cspr_per_share.set(index + Self::per_share(fees_cspr, total_supply));
+        }
+        if fees_token > U512::zero() {
+            let index = self.fees_token_per_share.get_or_default();
+            self.fees_token_per_share.set(index + Self::per_share(fees_token, total_supply));
+        }
+    }
+
+    /// Credit compounded staking rewards to the current LP supply
+    pub fn accrue_staking_rewards(&mut self, rewards: U512, total_supply: U512) {
+        if total_supply == U512::zero() || rewards == U512::zero() {
+            return;
+        }
+        let index = self.staking_rewards_per_share.get_or_default();
+        self.staking_rewards_per_share.set(index + Self::per_share(rewards, total_supply));
+    }
+
+    /// Record `owner`'s earnings on `balance` up to now (call before the balance changes)
+    pub fn checkpoint(&mut self, owner: &Address, balance: U512) {
+        let checkpoint = LpCheckpoint {
+            earned: self.earnings(owner, balance),
+            fees_cspr_index: self.fees_cspr_per_share.get_or_default(),
+            fees_token_index: self.fees_token_per_share.get_or_default(),
+            staking_rewards_index: self.staking_rewards_per_share.get_or_default(),
+        };
+        self.checkpoints.set(owner, checkpoint);
+    }
+
+    /// Earnings of `owner` holding `balance` since their last checkpoint, plus those before it
+    pub fn earnings(&self, owner: &Address, balance: U512) -> LpEarnings {
+        let checkpoint = self.checkpoints.get(owner).unwrap_or_default();
+        let earned = checkpoint.earned;
+        LpEarnings {
+            fees_cspr: earned.fees_cspr
+                + Self::accrued(balance, self.fees_cspr_per_share.get_or_default(), checkpoint.fees_cspr_index),
+            fees_token: earned.fees_token
+                + Self::accrued(balance, self.fees_token_per_share.get_or_default(), checkpoint.fees_token_index),
+            staking_rewards: earned.staking_rewards
+                + Self::accrued(
+                    balance,
+                    self.staking_rewards_per_share.get_or_default(),
+                    checkpoint.staking_rewards_index,
+                ),
+        }
+    }
+
+    fn per_share(amount: U512, total_supply: U512) -> U512 {
+        (amount * U512::from(PAYOUT_INDEX_SCALE)) / total_supply
+    }
+
+    fn accrued(balance: U512, index: U512, checkpoint_index: U512) -> U512 {
+        (balance * (index - checkpoint_index)) / U512::from(PAYOUT_INDEX_SCALE)
+    }
+}
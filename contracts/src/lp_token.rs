@@ -4,6 +4,9 @@ use odra::prelude::*;
 use odra::casper_types::{U256, U512};
 use odra_modules::cep18_token::Cep18;
 
+use crate::lp_earnings::LpEarningsIndex;
+use crate::types::LpEarnings;
+
 /// LP Token contract wrapping CEP-18 with pool-only minting/burning
 #[odra::module]
 pub struct LpToken {
@@ -11,6 +14,9 @@ pub struct LpToken {
     cep18: SubModule<Cep18>,
     /// Pool contract address (only pool can mint/burn)
     pool: Var<Address>,
+    /// Fee and staking reward attribution, checkpointed on every balance change
+    /// (credited by the pool holding this token as a submodule)
+    pub(crate) earnings: SubModule<LpEarningsIndex>,
 }
 
 #[odra::module]
@@ -29,15 +35,18 @@ impl LpToken {
     /// Mint LP tokens (pool only)
     pub fn mint(&mut self, to: &Address, amount: U512) {
         self.require_pool();
-        let amount_u256 = U256::from(amount.as_u128());
-        self.cep18.raw_mint(to, &amount_u256);
+        self.raw_mint(to, amount);
     }
 
     /// Burn LP tokens (pool only)
     pub fn burn(&mut self, from: &Address, amount: U512) {
         self.require_pool();
-        let amount_u256 = U256::from(amount.as_u128());
-        self.cep18.raw_burn(from, &amount_u256);
+        self.raw_burn(from, amount);
+    }
+
+    /// Get fees and staking rewards earned by `owner`'s LP tokens since their first deposit
+    pub fn earnings_of(&self, owner: &Address) -> LpEarnings {
+        self.earnings.earnings(owner, self.balance_of(owner))
     }
 
     /// Get total supply
@@ -52,11 +61,15 @@ impl LpToken {
 
     /// Transfer tokens (standard CEP-18)
     pub fn transfer(&mut self, to: &Address, amount: &U256) {
+        self.checkpoint(&self.env().caller());
+        self.checkpoint(to);
         self.cep18.transfer(to, amount);
     }
 
     /// Transfer from (standard CEP-18)
     pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.checkpoint(owner);
+        self.checkpoint(recipient);
         self.cep18.transfer_from(owner, recipient, amount);
     }
 
@@ -87,6 +100,26 @@ impl LpToken {
 
     // ============ INTERNAL ============
 
+    /// Mint without the caller check, for the contract holding this token as a submodule
+    /// (the caller there is the pool's user, not the pool)
+    pub(crate) fn raw_mint(&mut self, to: &Address, amount: U512) {
+        self.checkpoint(to);
+        let amount_u256 = U256::from(amount.as_u128());
+        self.cep18.raw_mint(to, &amount_u256);
+    }
+
+    /// Burn without the caller check, for the contract holding this token as a submodule
+    pub(crate) fn raw_burn(&mut self, from: &Address, amount: U512) {
+        self.checkpoint(from);
+        let amount_u256 = U256::from(amount.as_u128());
+        self.cep18.raw_burn(from, &amount_u256);
+    }
+
+    fn checkpoint(&mut self, owner: &Address) {
+        let balance = self.balance_of(owner);
+        self.earnings.checkpoint(owner, balance);
+    }
+
     fn require_pool(&self) {
         let pool = self.pool.get().expect("Pool not set");
         if self.env().caller() != pool {
@@ -0,0 +1,183 @@
+//! Constant-product math shared by CSPR pools and token/token pairs
+
+use odra::casper_types::U512;
+
+use crate::types::{BPS_DENOMINATOR, PAYOUT_INDEX_SCALE, YEAR_MS};
+
+/// Constant product formula with fee
+pub fn get_amount_out(amount_in: U512, reserve_in: U512, reserve_out: U512, swap_fee_bps: u64) -> U512 {
+    if amount_in == U512::zero() || reserve_in == U512::zero() || reserve_out == U512::zero() {
+        return U512::zero();
+    }
+
+    // amount_in_with_fee = amount_in * (10000 - fee) / 10000
+    let fee_multiplier = U512::from(BPS_DENOMINATOR) - U512::from(swap_fee_bps);
+    let amount_in_with_fee = (amount_in * fee_multiplier) / U512::from(BPS_DENOMINATOR);
+
+    // output = (amount_in_with_fee * reserve_out) / (reserve_in + amount_in_with_fee)
+    let numerator = amount_in_with_fee * reserve_out;
+    let denominator = reserve_in + amount_in_with_fee;
+
+    numerator / denominator
+}
+
+/// Smallest input for which `get_amount_out` yields at least `amount_out`
+/// Returns zero when the reserves cannot cover `amount_out`
+pub fn get_amount_in(amount_out: U512, reserve_in: U512, reserve_out: U512, swap_fee_bps: u64) -> U512 {
+    if amount_out == U512::zero() || reserve_in == U512::zero() || amount_out >= reserve_out {
+        return U512::zero();
+    }
+
+    let scale = U512::from(BPS_DENOMINATOR);
+    let fee_multiplier = scale - U512::from(swap_fee_bps);
+
+    // amount_in_with_fee >= amount_out * reserve_in / (reserve_out - amount_out), rounded up
+    let numerator = amount_out * reserve_in;
+    let denominator = reserve_out - amount_out;
+    let amount_in_with_fee = (numerator + denominator - U512::one()) / denominator;
+
+    // amount_in * (10000 - fee) / 10000 >= amount_in_with_fee, rounded up
+    (amount_in_with_fee * scale + fee_multiplier - U512::one()) / fee_multiplier
+}
+
+/// Swap fee charged on `amount_in`
+pub fn swap_fee(amount_in: U512, swap_fee_bps: u64) -> U512 {
+    (amount_in * U512::from(swap_fee_bps)) / U512::from(BPS_DENOMINATOR)
+}
+
+/// Protocol share of the swap fee charged on `amount_in`
+pub fn protocol_fee_cut(amount_in: U512, swap_fee_bps: u64, protocol_swap_fee_bps: u64) -> U512 {
+    let fee = swap_fee(amount_in, swap_fee_bps);
+    (fee * U512::from(protocol_swap_fee_bps)) / U512::from(BPS_DENOMINATOR)
+}
+
+/// Largest (a, b) within the available amounts that matches the reserve ratio
+pub fn optimal_amounts(a_available: U512, b_available: U512, reserve_a: U512, reserve_b: U512) -> (U512, U512) {
+    let b_optimal = (a_available * reserve_b) / reserve_a;
+    if b_optimal <= b_available {
+        (a_available, b_optimal)
+    } else {
+        let a_optimal = (b_available * reserve_a) / reserve_b;
+        (a_optimal, b_available)
+    }
+}
+
+/// Share of a single-sided deposit to swap so the remainder matches the post-swap ratio
+///
+/// Zapper formula: s = (sqrt(r * (r * (2F - f)^2 + 4 * a * F * (F - f))) - r * (2F - f)) / (2 * (F - f))
+/// with F = 10000 and f the swap fee in bps.
+pub fn optimal_swap_amount(amount_in: U512, reserve_in: U512, swap_fee_bps: u64) -> U512 {
+    let scale = U512::from(BPS_DENOMINATOR);
+    let fee = U512::from(swap_fee_bps);
+
+    let two_f_minus_fee = scale * U512::from(2u64) - fee;
+    let f_minus_fee = scale - fee;
+
+    let radicand = reserve_in
+        * (reserve_in * two_f_minus_fee * two_f_minus_fee + U512::from(4u64) * amount_in * scale * f_minus_fee);
+    (sqrt(radicand) - reserve_in * two_f_minus_fee) / (f_minus_fee * U512::from(2u64))
+}
+
+/// LP tokens for a deposit: geometric mean for the first one, otherwise the smaller share
+/// Returns (LP to the provider, LP to lock forever)
+pub fn liquidity_to_mint(
+    amount_a: U512,
+    amount_b: U512,
+    reserve_a: U512,
+    reserve_b: U512,
+    total_lp: U512,
+    minimum_liquidity: U512,
+) -> (U512, U512) {
+    if total_lp == U512::zero() {
+        let sqrt_product = sqrt(amount_a * amount_b);
+        if sqrt_product <= minimum_liquidity {
+            return (U512::zero(), minimum_liquidity);
+        }
+        return (sqrt_product - minimum_liquidity, minimum_liquidity);
+    }
+
+    let lp_from_a = (amount_a * total_lp) / reserve_a;
+    let lp_from_b = (amount_b * total_lp) / reserve_b;
+    (lp_from_a.min(lp_from_b), U512::zero())
+}
+
+/// `earned` over `elapsed_ms` as an annual rate on `value`, in bps (0 without value or time)
+pub fn annualized_bps(earned: U512, value: U512, elapsed_ms: u64) -> u64 {
+    if value == U512::zero() || elapsed_ms == 0 {
+        return 0;
+    }
+    let bps = (earned * U512::from(YEAR_MS) * U512::from(BPS_DENOMINATOR)) / (value * U512::from(elapsed_ms));
+    bps.min(U512::from(u64::MAX)).as_u64()
+}
+
+/// Annual yield of `apr_bps` compounded `periods` times a year, in bps
+pub fn compounded_apy_bps(apr_bps: u64, periods: u64) -> u64 {
+    if periods == 0 {
+        return apr_bps;
+    }
+    let scale = U512::from(PAYOUT_INDEX_SCALE);
+    let rate = (U512::from(apr_bps) * scale) / (U512::from(BPS_DENOMINATOR) * U512::from(periods));
+
+    // (1 + rate)^periods by squaring, in PAYOUT_INDEX_SCALE fixed point; saturates on overflow
+    let mut base = scale + rate;
+    let mut result = scale;
+    let mut exponent = periods;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            match result.checked_mul(base) {
+                Some(product) => result = product / scale,
+                None => return u64::MAX,
+            }
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            match base.checked_mul(base) {
+                Some(square) => base = square / scale,
+                None => return u64::MAX,
+            }
+        }
+    }
+
+    let bps = ((result - scale) * U512::from(BPS_DENOMINATOR)) / scale;
+    bps.min(U512::from(u64::MAX)).as_u64()
+}
+
+/// `amount` in units of `from_decimals` re-expressed in units of `to_decimals` (rounded down)
+pub fn scale_decimals(amount: U512, from_decimals: u8, to_decimals: u8) -> U512 {
+    if to_decimals >= from_decimals {
+        amount * pow10(to_decimals - from_decimals)
+    } else {
+        amount / pow10(from_decimals - to_decimals)
+    }
+}
+
+/// Whole B per whole A for amounts in base units, as a fixed point with `decimals` places
+/// Returns zero when `amount_a` is zero.
+pub fn price(amount_a: U512, decimals_a: u8, amount_b: U512, decimals_b: u8, decimals: u8) -> U512 {
+    if amount_a == U512::zero() {
+        return U512::zero();
+    }
+    (amount_b * pow10(decimals_a) * pow10(decimals)) / (amount_a * pow10(decimals_b))
+}
+
+/// 10^exp
+pub fn pow10(exp: u8) -> U512 {
+    U512::from(10u64).pow(U512::from(exp))
+}
+
+/// Integer square root (Babylonian method)
+pub fn sqrt(n: U512) -> U512 {
+    if n == U512::zero() {
+        return U512::zero();
+    }
+
+    let mut x = n;
+    let mut y = (x + U512::one()) / 2;
+
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+
+    x
+}
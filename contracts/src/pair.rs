@@ -0,0 +1,559 @@
+//! Ghost Pool Pair - AMM pool between two CEP-18 tokens
+
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{U256, U512};
+
+use crate::events::*;
+use crate::lp_token::LpToken;
+use crate::math;
+use crate::pool::Cep18TokenContractRef;
+use crate::stable_curve::StableCurve;
+use crate::types::*;
+
+/// Token/token pool sharing the CSPR pool's curve, LP and fee logic
+///
+/// Both reserves are CEP-18 balances, so there is no staking and no withdrawal queue:
+/// liquidity is removed immediately. Tokens are stored sorted (`token0 < token1`).
+/// Only the swap fee fields of `PoolConfig` apply. Pairs of correlated tokens
+/// (e.g. stablecoins) can opt into the StableSwap curve before their first deposit.
+#[odra::module]
+pub struct GhostPoolPair {
+    // ============ TOKEN ADDRESSES ============
+    /// Lower-sorted token address
+    token0: Var<Address>,
+    /// Higher-sorted token address
+    token1: Var<Address>,
+    /// LP token (internal submodule)
+    lp_token: SubModule<LpToken>,
+
+    // ============ RESERVES ============
+    /// Reserve of token0
+    reserve0: Var<U512>,
+    /// Reserve of token1
+    reserve1: Var<U512>,
+
+    // ============ CONFIG ============
+    /// Pool configuration (only the swap fees are used)
+    config: Var<PoolConfig>,
+    /// Treasury address for protocol fees
+    treasury: Var<Address>,
+    /// Swap curve (constant product unless switched to StableSwap)
+    curve: SubModule<StableCurve>,
+
+    // ============ PROTOCOL FEES ============
+    /// Token0 swap fees accrued to the treasury (held outside reserves)
+    protocol_fees0: Var<U512>,
+    /// Token1 swap fees accrued to the treasury (held outside reserves)
+    protocol_fees1: Var<U512>,
+
+    // ============ ADMIN ============
+    /// Admin address
+    admin: Var<Address>,
+    /// Minimum liquidity (locked on first deposit)
+    minimum_liquidity: Var<U512>,
+}
+
+#[odra::module]
+impl GhostPoolPair {
+    /// Initialize the pair
+    #[odra(init)]
+    pub fn init(&mut self, token_a: Address, token_b: Address, treasury: Address, admin: Address) {
+        if token_a == token_b {
+            self.env().revert(PairError::IdenticalTokens);
+        }
+        let (token0, token1) = if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) };
+
+        self.token0.set(token0);
+        self.token1.set(token1);
+        self.treasury.set(treasury);
+        self.admin.set(admin);
+
+        self.reserve0.set(U512::zero());
+        self.reserve1.set(U512::zero());
+        self.config.set(PoolConfig::with_defaults());
+        self.minimum_liquidity.set(U512::from(MINIMUM_LIQUIDITY));
+
+        // Initialize LP token
+        self.lp_token.init(
+            String::from("Ghost Pool Pair LP"),
+            String::from("GP-PLP"),
+            9,
+        );
+    }
+
+    // ============ LIQUIDITY ============
+
+    /// Add liquidity at the current reserve ratio
+    /// Only the amounts needed are pulled (requires prior approval of both tokens).
+    /// Returns (token0 used, token1 used, LP minted).
+    #[odra(non_reentrant)]
+    pub fn add_liquidity(
+        &mut self,
+        amount0_desired: U512,
+        amount1_desired: U512,
+        amount0_min: U512,
+        amount1_min: U512,
+        min_lp_tokens: U512,
+    ) -> (U512, U512, U512) {
+        let caller = self.env().caller();
+
+        if amount0_desired == U512::zero() || amount1_desired == U512::zero() {
+            self.env().revert(PairError::ZeroAmount);
+        }
+
+        let reserve0 = self.reserve0.get_or_default();
+        let reserve1 = self.reserve1.get_or_default();
+        let total_lp = self.lp_token.total_supply();
+
+        let (amount0, amount1) = if total_lp == U512::zero() {
+            // First deposit sets the ratio
+            (amount0_desired, amount1_desired)
+        } else {
+            math::optimal_amounts(amount0_desired, amount1_desired, reserve0, reserve1)
+        };
+
+        let min_liq = self.minimum_liquidity.get_or_default();
+        let (lp_to_mint, locked_lp) =
+            math::liquidity_to_mint(amount0, amount1, reserve0, reserve1, total_lp, min_liq);
+        if locked_lp > U512::zero() {
+            if lp_to_mint == U512::zero() {
+                self.env().revert(PairError::InitialLiquidityTooLow);
+            }
+
+            // Lock minimum liquidity forever (mint to contract itself as burn address)
+            let burn_address = self.env().self_address();
+            self.lp_token.raw_mint(&burn_address, locked_lp);
+        }
+
+        if amount0 < amount0_min {
+            self.env().revert(PairError::Amount0Slippage);
+        }
+        if amount1 < amount1_min {
+            self.env().revert(PairError::Amount1Slippage);
+        }
+        if lp_to_mint < min_lp_tokens {
+            self.env().revert(PairError::SlippageExceeded);
+        }
+
+        let self_address = self.env().self_address();
+        self.transfer_token_from(self.token0(), &caller, &self_address, amount0);
+        self.transfer_token_from(self.token1(), &caller, &self_address, amount1);
+
+        self.reserve0.set(reserve0 + amount0);
+        self.reserve1.set(reserve1 + amount1);
+
+        self.lp_token.raw_mint(&caller, lp_to_mint);
+
+        self.env().emit_event(PairLiquidityAdded {
+            provider: caller,
+            amount0,
+            amount1,
+            lp_minted: lp_to_mint,
+        });
+
+        (amount0, amount1, lp_to_mint)
+    }
+
+    /// Remove liquidity - both tokens are returned immediately
+    #[odra(non_reentrant)]
+    pub fn remove_liquidity(&mut self, lp_amount: U512, min_amount0: U512, min_amount1: U512) -> (U512, U512) {
+        let caller = self.env().caller();
+
+        let lp_balance = self.lp_token.balance_of(&caller);
+        if lp_amount > lp_balance {
+            self.env().revert(PairError::InsufficientLpBalance);
+        }
+        if lp_amount == U512::zero() {
+            self.env().revert(PairError::ZeroAmount);
+        }
+
+        let total_lp = self.lp_token.total_supply();
+        let reserve0 = self.reserve0.get_or_default();
+        let reserve1 = self.reserve1.get_or_default();
+
+        let amount0 = (lp_amount * reserve0) / total_lp;
+        let amount1 = (lp_amount * reserve1) / total_lp;
+
+        if amount0 < min_amount0 {
+            self.env().revert(PairError::Amount0Slippage);
+        }
+        if amount1 < min_amount1 {
+            self.env().revert(PairError::Amount1Slippage);
+        }
+
+        self.lp_token.raw_burn(&caller, lp_amount);
+
+        self.reserve0.set(reserve0 - amount0);
+        self.reserve1.set(reserve1 - amount1);
+
+        self.transfer_token(self.token0(), &caller, amount0);
+        self.transfer_token(self.token1(), &caller, amount1);
+
+        self.env().emit_event(PairLiquidityRemoved {
+            provider: caller,
+            lp_burned: lp_amount,
+            amount0,
+            amount1,
+        });
+
+        (amount0, amount1)
+    }
+
+    // ============ SWAP ============
+
+    /// Swap an exact amount of `token_in` for the other token
+    #[odra(non_reentrant)]
+    pub fn swap(&mut self, token_in: Address, amount_in: U512, min_amount_out: U512) -> U512 {
+        let caller = self.env().caller();
+        let zero_for_one = self.is_token0(token_in);
+
+        if amount_in == U512::zero() {
+            self.env().revert(PairError::ZeroAmount);
+        }
+
+        let (reserve_in, reserve_out) = self.ordered_reserves(zero_for_one);
+        let amount_out = self.get_amount_out(amount_in, reserve_in, reserve_out);
+
+        if amount_out < min_amount_out {
+            self.env().revert(PairError::SlippageExceeded);
+        }
+        if amount_out == U512::zero() || amount_out >= reserve_out {
+            self.env().revert(PairError::InsufficientLiquidity);
+        }
+
+        self.transfer_token_from(token_in, &caller, &self.env().self_address(), amount_in);
+
+        // Divert the protocol share of the fee out of the reserves
+        let protocol_cut = self.protocol_fee_cut(amount_in);
+        let new_reserve_in = reserve_in + amount_in - protocol_cut;
+        let new_reserve_out = reserve_out - amount_out;
+
+        let token_out = if zero_for_one {
+            let accrued = self.protocol_fees0.get_or_default();
+            self.protocol_fees0.set(accrued + protocol_cut);
+            self.reserve0.set(new_reserve_in);
+            self.reserve1.set(new_reserve_out);
+            self.token1()
+        } else {
+            let accrued = self.protocol_fees1.get_or_default();
+            self.protocol_fees1.set(accrued + protocol_cut);
+            self.reserve1.set(new_reserve_in);
+            self.reserve0.set(new_reserve_out);
+            self.token0()
+        };
+
+        self.transfer_token(token_out, &caller, amount_out);
+
+        self.env().emit_event(PairSwap {
+            sender: caller,
+            token_in,
+            amount_in,
+            amount_out,
+        });
+
+        amount_out
+    }
+
+    // ============ PROTOCOL FEES ============
+
+    /// Send accrued protocol swap fees to the treasury (callable by anyone)
+    #[odra(non_reentrant)]
+    pub fn claim_protocol_fees(&mut self) -> (U512, U512) {
+        let treasury = self.treasury();
+        let amount0 = self.protocol_fees0.get_or_default();
+        let amount1 = self.protocol_fees1.get_or_default();
+
+        self.protocol_fees0.set(U512::zero());
+        self.protocol_fees1.set(U512::zero());
+
+        if amount0 > U512::zero() {
+            self.transfer_token(self.token0(), &treasury, amount0);
+        }
+        if amount1 > U512::zero() {
+            self.transfer_token(self.token1(), &treasury, amount1);
+        }
+
+        self.env().emit_event(PairFeesClaimed {
+            treasury,
+            amount0,
+            amount1,
+        });
+
+        (amount0, amount1)
+    }
+
+    // ============ ADMIN ============
+
+    /// Replace the pair configuration (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_config(&mut self, config: PoolConfig) {
+        self.require_admin();
+        if !config.is_valid() {
+            self.env().revert(PairError::InvalidFee);
+        }
+
+        self.config.set(config.clone());
+
+        self.env().emit_event(ConfigUpdated {
+            buffer_target_bps: config.buffer_target_bps,
+            swap_fee_bps: config.swap_fee_bps,
+            protocol_fee_bps: config.protocol_fee_bps,
+            protocol_swap_fee_bps: config.protocol_swap_fee_bps,
+        });
+    }
+
+    /// Set the treasury receiving protocol fees (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_treasury(&mut self, treasury: Address) {
+        self.require_admin();
+        self.treasury.set(treasury);
+
+        self.env().emit_event(TreasuryUpdated { treasury });
+    }
+
+    /// Hand the admin role over to `admin` (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_admin(&mut self, admin: Address) {
+        self.require_admin();
+        self.admin.set(admin);
+
+        self.env().emit_event(AdminUpdated { admin });
+    }
+
+    /// Switch the pair to the StableSwap curve (admin only, before any liquidity)
+    /// Both tokens must use the same decimals, since the curve compares raw reserves.
+    #[odra(non_reentrant)]
+    pub fn set_stable_curve(&mut self, amp: u64) {
+        self.require_admin();
+        if self.lp_token.total_supply() > U512::zero() {
+            self.env().revert(PairError::LiquidityPresent);
+        }
+        let decimals0 = Cep18TokenContractRef::new(self.env(), self.token0()).decimals();
+        let decimals1 = Cep18TokenContractRef::new(self.env(), self.token1()).decimals();
+        if decimals0 != decimals1 {
+            self.env().revert(PairError::DecimalsMismatch);
+        }
+        self.curve.enable(amp);
+    }
+
+    /// Ramp the StableSwap amplification coefficient linearly until `future_time` (admin only)
+    #[odra(non_reentrant)]
+    pub fn ramp_amp(&mut self, future_amp: u64, future_time: u64) {
+        self.require_admin();
+        self.curve.ramp(future_amp, future_time);
+    }
+
+    /// Freeze the StableSwap amplification coefficient at its current value (admin only)
+    #[odra(non_reentrant)]
+    pub fn stop_amp_ramp(&mut self) {
+        self.require_admin();
+        self.curve.stop_ramp();
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Get current reserves as (token0, token1)
+    pub fn get_reserves(&self) -> (U512, U512) {
+        (
+            self.reserve0.get_or_default(),
+            self.reserve1.get_or_default(),
+        )
+    }
+
+    /// Get the lower-sorted token
+    pub fn token0(&self) -> Address {
+        self.token0.get().expect("Token0 not set")
+    }
+
+    /// Get the higher-sorted token
+    pub fn token1(&self) -> Address {
+        self.token1.get().expect("Token1 not set")
+    }
+
+    /// Quote swapping `amount_in` of `token_in` for the other token
+    pub fn quote_amount_out(&self, token_in: Address, amount_in: U512) -> U512 {
+        let (reserve_in, reserve_out) = self.ordered_reserves(self.is_token0(token_in));
+        self.get_amount_out(amount_in, reserve_in, reserve_out)
+    }
+
+    /// Input of the other token needed to buy exactly `amount_out` of `token_out`
+    /// (0 if the reserves cannot cover it)
+    pub fn quote_amount_in(&self, token_out: Address, amount_out: U512) -> U512 {
+        let (reserve_out, reserve_in) = self.ordered_reserves(self.is_token0(token_out));
+        let config = self.config.get_or_default();
+        self.curve.get_amount_in(amount_out, reserve_in, reserve_out, config.swap_fee_bps.as_u64())
+    }
+
+    /// Get LP token value in underlying assets as (token0, token1)
+    pub fn get_lp_value(&self, lp_amount: U512) -> (U512, U512) {
+        let total_lp = self.lp_token.total_supply();
+        if total_lp == U512::zero() {
+            return (U512::zero(), U512::zero());
+        }
+
+        let (reserve0, reserve1) = self.get_reserves();
+        ((lp_amount * reserve0) / total_lp, (lp_amount * reserve1) / total_lp)
+    }
+
+    /// Get LP token address (LP token is internal, so this returns the pair address)
+    pub fn lp_token_address(&self) -> Address {
+        self.env().self_address()
+    }
+
+    /// Get LP token balance
+    pub fn lp_balance_of(&self, owner: &Address) -> U512 {
+        self.lp_token.balance_of(owner)
+    }
+
+    /// Get LP token total supply
+    pub fn lp_total_supply(&self) -> U512 {
+        self.lp_token.total_supply()
+    }
+
+    /// Transfer LP tokens (standard CEP-18, the pair address is the LP token)
+    #[odra(non_reentrant)]
+    pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
+        self.lp_token.transfer(recipient, amount);
+    }
+
+    /// Transfer LP tokens from owner to recipient (standard CEP-18, requires prior approval)
+    #[odra(non_reentrant)]
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.lp_token.transfer_from(owner, recipient, amount);
+    }
+
+    /// Approve a spender of the caller's LP tokens (standard CEP-18)
+    #[odra(non_reentrant)]
+    pub fn approve(&mut self, spender: &Address, amount: &U256) {
+        self.lp_token.approve(spender, amount);
+    }
+
+    /// Get LP token allowance (standard CEP-18)
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        self.lp_token.allowance(owner, spender)
+    }
+
+    /// Get protocol swap fees accrued and not yet claimed as (token0, token1)
+    pub fn get_protocol_fees(&self) -> (U512, U512) {
+        (
+            self.protocol_fees0.get_or_default(),
+            self.protocol_fees1.get_or_default(),
+        )
+    }
+
+    /// Get the pair configuration
+    pub fn get_config(&self) -> PoolConfig {
+        self.config.get_or_default()
+    }
+
+    /// Get the swap curve
+    pub fn curve_type(&self) -> CurveType {
+        self.curve.curve()
+    }
+
+    /// Get the current StableSwap amplification coefficient (0 on the constant product curve)
+    pub fn get_amp(&self) -> u64 {
+        self.curve.amp()
+    }
+
+    /// Get the current (or last) amplification ramp
+    pub fn get_amp_ramp(&self) -> AmpRamp {
+        self.curve.ramp_info()
+    }
+
+    /// Get the admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    /// Get the treasury address
+    pub fn treasury(&self) -> Address {
+        self.treasury.get().expect("Treasury not set")
+    }
+
+    // ============ INTERNAL FUNCTIONS ============
+
+    fn is_token0(&self, token: Address) -> bool {
+        if token == self.token0() {
+            true
+        } else if token == self.token1() {
+            false
+        } else {
+            self.env().revert(PairError::UnknownToken)
+        }
+    }
+
+    /// Reserves as (in, out) for a swap direction
+    fn ordered_reserves(&self, zero_for_one: bool) -> (U512, U512) {
+        let (reserve0, reserve1) = self.get_reserves();
+        if zero_for_one {
+            (reserve0, reserve1)
+        } else {
+            (reserve1, reserve0)
+        }
+    }
+
+    fn get_amount_out(&self, amount_in: U512, reserve_in: U512, reserve_out: U512) -> U512 {
+        let config = self.config.get_or_default();
+        self.curve.get_amount_out(amount_in, reserve_in, reserve_out, config.swap_fee_bps.as_u64())
+    }
+
+    fn protocol_fee_cut(&self, amount_in: U512) -> U512 {
+        let config = self.config.get_or_default();
+        math::protocol_fee_cut(
+            amount_in,
+            config.swap_fee_bps.as_u64(),
+            config.protocol_swap_fee_bps.as_u64(),
+        )
+    }
+
+    fn require_admin(&self) {
+        if self.env().caller() != self.admin() {
+            self.env().revert(PairError::NotAdmin);
+        }
+    }
+
+    // ============ TOKEN HELPERS ============
+
+    fn transfer_token(&self, token: Address, to: &Address, amount: U512) {
+        let amount_u256 = U256::from(amount.as_u128());
+        Cep18TokenContractRef::new(self.env(), token).transfer(to, &amount_u256);
+    }
+
+    fn transfer_token_from(&self, token: Address, from: &Address, to: &Address, amount: U512) {
+        let amount_u256 = U256::from(amount.as_u128());
+        Cep18TokenContractRef::new(self.env(), token).transfer_from(from, to, &amount_u256);
+    }
+}
+
+/// Pair errors
+#[odra::odra_error]
+pub enum PairError {
+    /// Both tokens are the same
+    IdenticalTokens = 1,
+    /// Zero amount provided
+    ZeroAmount = 2,
+    /// Initial liquidity too low
+    InitialLiquidityTooLow = 3,
+    /// Slippage exceeded
+    SlippageExceeded = 4,
+    /// Insufficient LP balance
+    InsufficientLpBalance = 5,
+    /// Token0 slippage exceeded
+    Amount0Slippage = 6,
+    /// Token1 slippage exceeded
+    Amount1Slippage = 7,
+    /// Insufficient liquidity
+    InsufficientLiquidity = 8,
+    /// Token is not part of the pair
+    UnknownToken = 9,
+    /// Caller is not the admin
+    NotAdmin = 10,
+    /// Fee parameter out of range
+    InvalidFee = 11,
+    /// Operation requires an empty pair
+    LiquidityPresent = 12,
+    /// Tokens use different decimals
+    DecimalsMismatch = 13,
+}
@@ -0,0 +1,97 @@
+//! Paired token - the pool's CEP-18 side and how incoming transfers are credited
+
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{U256, U512};
+
+use crate::pool::Cep18TokenContractRef;
+use crate::types::TokenAccounting;
+
+/// The CEP-18 token a pool pairs with CSPR
+///
+/// In `Exact` mode a `transfer_from` is assumed to deliver the requested amount. In
+/// `BalanceDelta` mode the pool's balance is read before and after each pull and only the
+/// difference is credited, so fee-on-transfer tokens cannot inflate `reserve_token`.
+/// Outgoing transfers debit the amount sent; any fee is borne by the recipient.
+#[odra::module]
+pub struct PairedToken {
+    /// Token contract address
+    address: Var<Address>,
+    /// How incoming transfers are credited
+    accounting: Var<TokenAccounting>,
+    /// Token decimals, read once at init
+    decimals: Var<u8>,
+}
+
+#[odra::module]
+impl PairedToken {
+    /// Set the token contract and read its decimals; starts in `Exact` mode
+    pub fn init(&mut self, address: Address) {
+        self.address.set(address);
+        self.accounting.set(TokenAccounting::Exact);
+        let decimals = self.token().decimals();
+        self.decimals.set(decimals);
+    }
+
+    /// Token contract address
+    pub fn contract_address(&self) -> Address {
+        self.address.get().expect("Token not set")
+    }
+
+    /// Token decimals
+    pub fn decimals(&self) -> u8 {
+        self.decimals.get_or_default()
+    }
+
+    /// Current accounting mode
+    pub fn accounting(&self) -> TokenAccounting {
+        self.accounting.get_or_default()
+    }
+
+    /// Switch the accounting mode
+    pub fn set_accounting(&mut self, accounting: TokenAccounting) {
+        self.accounting.set(accounting);
+    }
+
+    /// Token balance of `owner`
+    pub fn balance_of(&self, owner: &Address) -> U512 {
+        let balance = self.token().balance_of(owner);
+        U512::from(balance.as_u128())
+    }
+
+    /// Remaining allowance of `spender` over `owner`'s tokens
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U512 {
+        let allowance = self.token().allowance(owner, spender);
+        // Unlimited approvals exceed u128; clamp before converting
+        U512::from(allowance.min(U256::from(u128::MAX)).as_u128())
+    }
+
+    /// Send `amount` from the pool to `to`
+    pub fn transfer(&self, to: &Address, amount: U512) {
+        self.token().transfer(to, &U256::from(amount.as_u128()));
+    }
+
+    /// Pull `amount` from `from` into the pool, returning the amount to credit
+    pub fn pull(&self, from: &Address, amount: U512) -> U512 {
+        match self.accounting() {
+            TokenAccounting::Exact => {
+                let self_address = self.env().self_address();
+                self.token().transfer_from(from, &self_address, &U256::from(amount.as_u128()));
+                amount
+            }
+            TokenAccounting::BalanceDelta => self.pull_measured(from, amount),
+        }
+    }
+
+    /// Pull `amount` from `from` into the pool, returning the change in the pool's balance
+    pub fn pull_measured(&self, from: &Address, amount: U512) -> U512 {
+        let self_address = self.env().self_address();
+        let before = self.balance_of(&self_address);
+        self.token().transfer_from(from, &self_address, &U256::from(amount.as_u128()));
+        self.balance_of(&self_address).saturating_sub(before)
+    }
+
+    fn token(&self) -> Cep18TokenContractRef {
+        Cep18TokenContractRef::new(self.env(), self.contract_address())
+    }
+}
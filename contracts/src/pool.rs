@@ -7,7 +7,14 @@ use odra::casper_types::{PublicKey, U256, U512};
 
 use crate::events::*;
 use crate::lp_token::LpToken;
+use crate::math;
+use crate::paired_token::PairedToken;
+use crate::pool_ledger::PoolLedger;
+use crate::st_cspr::LiquidStakingContractRef;
+use crate::stable_curve::StableCurve;
+use crate::staking::StakingBackend;
 use crate::types::*;
+use crate::withdrawal_queue::WithdrawalQueue;
 
 /// External contract interface for CEP-18 tokens (matches odra-modules CEP-18 signature)
 #[odra::external_contract]
@@ -16,14 +23,32 @@ pub trait Cep18Token {
     fn transfer(&mut self, recipient: &Address, amount: &U256);
     /// Transfer tokens from owner to recipient (requires prior approval)
     fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+    /// Get token balance of an address
+    fn balance_of(&self, address: &Address) -> U256;
+    /// Get remaining allowance of spender over owner's tokens
+    fn allowance(&self, owner: &Address, spender: &Address) -> U256;
+    /// Allow spender to transfer up to amount of the caller's tokens
+    fn approve(&mut self, spender: &Address, amount: &U256);
+    /// Get token decimals
+    fn decimals(&self) -> u8;
+}
+
+/// External contract interface for contracts receiving CSPR from swaps
+///
+/// Purse transfers to contract addresses are rejected on chain, so CSPR owed to a
+/// contract caller is attached to a call of its payable `receive_cspr` instead.
+#[odra::external_contract]
+pub trait CsprReceiver {
+    /// Accept the attached CSPR
+    fn receive_cspr(&mut self);
 }
 
 /// Ghost Pool AMM with auto-staking CSPR liquidity
 #[odra::module]
 pub struct GhostPoolPool {
     // ============ TOKEN ADDRESSES ============
-    /// Paired CEP-18 token address
-    token_address: Var<Address>,
+    /// Paired CEP-18 token and its accounting mode
+    token: SubModule<PairedToken>,
     /// LP token (internal submodule)
     lp_token: SubModule<LpToken>,
 
@@ -38,22 +63,24 @@ pub struct GhostPoolPool {
     staked_cspr: Var<U512>,
     /// Unstaked CSPR for immediate swaps
     buffer_cspr: Var<U512>,
-    /// Validator public key for delegation
-    validator: Var<PublicKey>,
+    /// Delegation backend (System Auction, or a simulator in tests)
+    staking: SubModule<StakingBackend>,
 
     // ============ CONFIG ============
     /// Pool configuration (fees and buffer target)
     config: Var<PoolConfig>,
     /// Treasury address for protocol fees
     treasury: Var<Address>,
+    /// Swap curve (constant product unless switched to StableSwap)
+    curve: SubModule<StableCurve>,
+
+    // ============ ACCOUNTING ============
+    /// Protocol fee accruals and rolling activity stats
+    ledger: SubModule<PoolLedger>,
 
     // ============ WITHDRAWALS ============
-    /// Counter for withdrawal IDs
-    withdrawal_counter: Var<u64>,
-    /// Withdrawal requests by ID
-    withdrawals: Mapping<u64, WithdrawalRequest>,
-    /// User's withdrawal IDs
-    user_withdrawals: Mapping<Address, Vec<u64>>,
+    /// Withdrawal queue storage
+    queue: SubModule<WithdrawalQueue>,
 
     // ============ ADMIN ============
     /// Admin address
@@ -73,8 +100,8 @@ impl GhostPoolPool {
         treasury: Address,
         admin: Address,
     ) {
-        self.token_address.set(token_address);
-        self.validator.set(validator);
+        self.token.init(token_address);
+        self.staking.init(validator);
         self.treasury.set(treasury);
         self.admin.set(admin);
 
@@ -83,14 +110,11 @@ impl GhostPoolPool {
         self.staked_cspr.set(U512::zero());
         self.buffer_cspr.set(U512::zero());
 
-        self.config.set(PoolConfig {
-            buffer_target_bps: U256::from(DEFAULT_BUFFER_TARGET_BPS),
-            swap_fee_bps: U256::from(DEFAULT_SWAP_FEE_BPS),
-            protocol_fee_bps: U256::from(DEFAULT_PROTOCOL_FEE_BPS),
-        });
+        self.config.set(PoolConfig::with_defaults());
 
         self.minimum_liquidity.set(U512::from(MINIMUM_LIQUIDITY));
-        self.withdrawal_counter.set(0);
+        self.queue.withdrawal_counter.set(0);
+        self.queue.withdrawal_payout_index.set(U512::from(PAYOUT_INDEX_SCALE));
 
         // Initialize LP token
         self.lp_token.init(
@@ -104,19 +128,24 @@ impl GhostPoolPool {
 
     /// Add liquidity to pool
     /// User sends CSPR as attached value + must have approved token transfer
-    #[odra(payable)]
+    ///
+    /// Deposits at the current reserve ratio: only the tokens needed are pulled and any
+    /// surplus CSPR is refunded. Returns (CSPR used, tokens used, LP minted).
+    #[odra(payable, non_reentrant)]
     pub fn add_liquidity(
         &mut self,
-        token_amount: U512,
+        token_amount_desired: U512,
+        token_amount_min: U512,
+        cspr_amount_min: U512,
         min_lp_tokens: U512,
-    ) -> U512 {
+    ) -> (U512, U512, U512) {
         let caller = self.env().caller();
-        let cspr_amount = self.env().attached_value();
+        let cspr_attached = self.env().attached_value();
 
-        if cspr_amount == U512::zero() {
+        if cspr_attached == U512::zero() {
             self.env().revert(PoolError::ZeroCsprAmount);
         }
-        if token_amount == U512::zero() {
+        if token_amount_desired == U512::zero() {
             self.env().revert(PoolError::ZeroTokenAmount);
         }
 
@@ -124,42 +153,48 @@ impl GhostPoolPool {
         let reserve_token = self.reserve_token.get_or_default();
         let total_lp = self.lp_token.total_supply();
 
-        let lp_to_mint: U512;
+        let (cspr_amount, token_amount) = if total_lp == U512::zero() {
+            // First deposit sets the ratio
+            (cspr_attached, token_amount_desired)
+        } else {
+            // Subsequent deposits - use the largest amounts matching the reserve ratio
+            math::optimal_amounts(cspr_attached, token_amount_desired, reserve_cspr, reserve_token)
+        };
 
-        if total_lp == U512::zero() {
-            // First deposit - use geometric mean
-            let product = cspr_amount * token_amount;
-            let sqrt_product = self.sqrt(product);
-            let min_liq = self.minimum_liquidity.get_or_default();
+        // Transfer tokens from user; LP is minted against what actually arrived
+        let token_received = self.pull_token(&caller, token_amount);
+        // A transfer fee leaves fewer tokens than planned: match the CSPR side to them
+        // (the difference is refunded below)
+        let (cspr_amount, token_amount) = if total_lp > U512::zero() && token_received < token_amount {
+            math::optimal_amounts(cspr_amount, token_received, reserve_cspr, reserve_token)
+        } else {
+            (cspr_amount, token_received)
+        };
 
-            if sqrt_product <= min_liq {
+        // Geometric mean for the first deposit, otherwise the smaller share
+        let (lp_to_mint, locked_lp) =
+            self.liquidity_to_mint(cspr_amount, token_amount, reserve_cspr, reserve_token, total_lp);
+        if locked_lp > U512::zero() {
+            if lp_to_mint == U512::zero() {
                 self.env().revert(PoolError::InitialLiquidityTooLow);
             }
 
-            lp_to_mint = sqrt_product - min_liq;
-
             // Lock minimum liquidity forever (mint to contract itself as burn address)
             let burn_address = self.env().self_address();
-            self.lp_token.mint(&burn_address, min_liq);
-        } else {
-            // Subsequent deposits - mint proportional to smaller ratio
-            let lp_from_cspr = (cspr_amount * total_lp) / reserve_cspr;
-            let lp_from_token = (token_amount * total_lp) / reserve_token;
-
-            lp_to_mint = if lp_from_cspr < lp_from_token {
-                lp_from_cspr
-            } else {
-                lp_from_token
-            };
+            self.lp_token.raw_mint(&burn_address, locked_lp);
+            self.ledger.start();
         }
 
+        if cspr_amount < cspr_amount_min {
+            self.env().revert(PoolError::CsprSlippage);
+        }
+        if token_amount < token_amount_min {
+            self.env().revert(PoolError::TokenSlippage);
+        }
         if lp_to_mint < min_lp_tokens {
             self.env().revert(PoolError::SlippageExceeded);
         }
 
-        // Transfer tokens from user
-        self.transfer_token_from(&caller, &self.env().self_address(), token_amount);
-
         // Update reserves
         self.reserve_cspr.set(reserve_cspr + cspr_amount);
         self.reserve_token.set(reserve_token + token_amount);
@@ -170,7 +205,13 @@ impl GhostPoolPool {
         self.rebalance_stake();
 
         // Mint LP tokens
-        self.lp_token.mint(&caller, lp_to_mint);
+        self.lp_token.raw_mint(&caller, lp_to_mint);
+
+        // Refund CSPR the ratio did not use
+        let cspr_refund = cspr_attached - cspr_amount;
+        if cspr_refund > U512::zero() {
+            self.env().transfer_tokens(&caller, &cspr_refund);
+        }
 
         // Emit event
         self.env().emit_event(LiquidityAdded {
@@ -180,12 +221,362 @@ impl GhostPoolPool {
             lp_minted: lp_to_mint,
         });
 
-        lp_to_mint
+        (cspr_amount, token_amount, lp_to_mint)
+    }
+
+    /// Add liquidity with stCSPR in place of CSPR (requires the liquid staking route)
+    /// User must have approved both the stCSPR and the token transfer
+    ///
+    /// stCSPR is valued at its exchange rate and joins the pool's stake as is, so the
+    /// CSPR side is never unbonded and re-delegated. Returns (stCSPR used, tokens used,
+    /// LP minted); `cspr_amount_min` applies to the CSPR value of the stCSPR used.
+    #[odra(non_reentrant)]
+    pub fn add_liquidity_st_cspr(
+        &mut self,
+        st_cspr_amount: U512,
+        token_amount_desired: U512,
+        token_amount_min: U512,
+        cspr_amount_min: U512,
+        min_lp_tokens: U512,
+    ) -> (U512, U512, U512) {
+        let caller = self.env().caller();
+        let st_cspr = self.staking.liquid_staking()
+            .unwrap_or_else(|| self.env().revert(PoolError::LiquidStakingDisabled));
+        let mut st_token = LiquidStakingContractRef::new(self.env(), st_cspr);
+
+        let cspr_offered = st_token.shares_to_cspr(st_cspr_amount);
+        if cspr_offered == U512::zero() {
+            self.env().revert(PoolError::ZeroCsprAmount);
+        }
+        if token_amount_desired == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
+        }
+
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+        let total_lp = self.lp_token.total_supply();
+
+        let (cspr_amount, token_amount) = if total_lp == U512::zero() {
+            (cspr_offered, token_amount_desired)
+        } else {
+            math::optimal_amounts(cspr_offered, token_amount_desired, reserve_cspr, reserve_token)
+        };
+
+        // Match the CSPR side to the tokens that actually arrived
+        let token_received = self.pull_token(&caller, token_amount);
+        let (cspr_amount, token_amount) = if total_lp > U512::zero() && token_received < token_amount {
+            math::optimal_amounts(cspr_amount, token_received, reserve_cspr, reserve_token)
+        } else {
+            (cspr_amount, token_received)
+        };
+
+        // Pull only the stCSPR the ratio uses, and credit what it is worth
+        let shares_used = if cspr_amount == cspr_offered {
+            st_cspr_amount
+        } else {
+            st_token.cspr_to_shares_up(cspr_amount).min(st_cspr_amount)
+        };
+        let cspr_amount = st_token.shares_to_cspr(shares_used);
+
+        let (lp_to_mint, locked_lp) =
+            self.liquidity_to_mint(cspr_amount, token_amount, reserve_cspr, reserve_token, total_lp);
+        if locked_lp > U512::zero() {
+            if lp_to_mint == U512::zero() {
+                self.env().revert(PoolError::InitialLiquidityTooLow);
+            }
+
+            let burn_address = self.env().self_address();
+            self.lp_token.raw_mint(&burn_address, locked_lp);
+            self.ledger.start();
+        }
+
+        if cspr_amount < cspr_amount_min {
+            self.env().revert(PoolError::CsprSlippage);
+        }
+        if token_amount < token_amount_min {
+            self.env().revert(PoolError::TokenSlippage);
+        }
+        if lp_to_mint < min_lp_tokens {
+            self.env().revert(PoolError::SlippageExceeded);
+        }
+
+        let self_address = self.env().self_address();
+        st_token.transfer_from(&caller, &self_address, &U256::from(shares_used.as_u128()));
+
+        // The stCSPR is already staked: it joins the stake, not the buffer
+        self.reserve_cspr.set(reserve_cspr + cspr_amount);
+        self.reserve_token.set(reserve_token + token_amount);
+        self.staked_cspr.set(self.staked_cspr.get_or_default() + cspr_amount);
+
+        self.lp_token.raw_mint(&caller, lp_to_mint);
+
+        self.env().emit_event(StakedCsprDeposited {
+            provider: caller,
+            shares: shares_used,
+            cspr_amount,
+            token_amount,
+            lp_minted: lp_to_mint,
+        });
+
+        (shares_used, token_amount, lp_to_mint)
+    }
+
+    // ============ ZAP ============
+
+    /// Add liquidity with CSPR only
+    /// Swaps the optimal share of the attached CSPR for tokens, deposits the rest and refunds dust
+    #[odra(payable, non_reentrant)]
+    pub fn zap_in_cspr(&mut self, min_lp_tokens: U512) -> U512 {
+        let caller = self.env().caller();
+        let cspr_in = self.env().attached_value();
+
+        if cspr_in == U512::zero() {
+            self.env().revert(PoolError::ZeroCsprAmount);
+        }
+
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+        if self.lp_token.total_supply() == U512::zero() {
+            self.env().revert(PoolError::InsufficientLiquidity);
+        }
+
+        let plan = self.plan_zap_in(cspr_in, reserve_cspr, reserve_token);
+
+        if plan.swap_out >= reserve_token {
+            self.env().revert(PoolError::InsufficientLiquidity);
+        }
+        if plan.lp_minted == U512::zero() || plan.lp_minted < min_lp_tokens {
+            self.env().revert(PoolError::SlippageExceeded);
+        }
+
+        if plan.protocol_cut > U512::zero() {
+            let accrued = self.ledger.protocol_fees_cspr.get_or_default();
+            self.ledger.protocol_fees_cspr.set(accrued + plan.protocol_cut);
+        }
+        self.accrue_swap_fee(plan.swap_amount, plan.protocol_cut, true);
+
+        // Swap leg and deposit leg in one reserve update
+        let cspr_added = plan.swap_amount - plan.protocol_cut + plan.in_used;
+        self.reserve_cspr.set(reserve_cspr + cspr_added);
+        self.reserve_token.set(reserve_token - plan.swap_out + plan.out_used);
+
+        let new_buffer = self.buffer_cspr.get_or_default() + cspr_added;
+        self.buffer_cspr.set(new_buffer);
+        self.rebalance_stake();
+
+        self.lp_token.raw_mint(&caller, plan.lp_minted);
+
+        // Refund whatever the deposit ratio could not absorb
+        let cspr_dust = cspr_in - plan.swap_amount - plan.in_used;
+        if cspr_dust > U512::zero() {
+            self.env().transfer_tokens(&caller, &cspr_dust);
+        }
+        let token_dust = plan.swap_out - plan.out_used;
+        if token_dust > U512::zero() {
+            self.transfer_token(&caller, token_dust);
+        }
+
+        self.env().emit_event(Swap {
+            sender: caller,
+            cspr_in: plan.swap_amount,
+            cspr_out: U512::zero(),
+            token_in: U512::zero(),
+            token_out: plan.swap_out,
+        });
+        self.env().emit_event(LiquidityAdded {
+            provider: caller,
+            cspr_amount: plan.in_used,
+            token_amount: plan.out_used,
+            lp_minted: plan.lp_minted,
+        });
+
+        plan.lp_minted
+    }
+
+    /// Add liquidity with tokens only
+    /// Swaps the optimal share of the tokens for CSPR, deposits the rest and refunds CSPR dust
+    #[odra(non_reentrant)]
+    pub fn zap_in_token(&mut self, token_amount: U512, min_lp_tokens: U512) -> U512 {
+        let caller = self.env().caller();
+
+        if token_amount == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
+        }
+
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+        if self.lp_token.total_supply() == U512::zero() {
+            self.env().revert(PoolError::InsufficientLiquidity);
+        }
+
+        // Pull only the tokens actually swapped or deposited, re-planning if fewer arrive
+        let plan = self.plan_zap_in(token_amount, reserve_token, reserve_cspr);
+        let token_requested = plan.swap_amount + plan.in_used;
+        let token_used = self.pull_token(&caller, token_requested);
+        let plan = if token_used == token_requested {
+            plan
+        } else {
+            self.plan_zap_in(token_used, reserve_token, reserve_cspr)
+        };
+
+        if plan.swap_out >= reserve_cspr {
+            self.env().revert(PoolError::InsufficientLiquidity);
+        }
+        if plan.lp_minted == U512::zero() || plan.lp_minted < min_lp_tokens {
+            self.env().revert(PoolError::SlippageExceeded);
+        }
+
+        // Only CSPR dust leaves the pool; the rest of the swap output is deposited back
+        let cspr_dust = plan.swap_out - plan.out_used;
+        let buffer = self.buffer_cspr.get_or_default();
+        if cspr_dust > buffer {
+            self.env().revert(PoolError::InsufficientBuffer);
+        }
+
+        if plan.protocol_cut > U512::zero() {
+            let accrued = self.ledger.protocol_fees_token.get_or_default();
+            self.ledger.protocol_fees_token.set(accrued + plan.protocol_cut);
+        }
+        self.accrue_swap_fee(plan.swap_amount, plan.protocol_cut, false);
+
+        self.reserve_token.set(reserve_token + token_used - plan.protocol_cut);
+        self.reserve_cspr.set(reserve_cspr - cspr_dust);
+        self.buffer_cspr.set(buffer - cspr_dust);
+
+        self.lp_token.raw_mint(&caller, plan.lp_minted);
+
+        if cspr_dust > U512::zero() {
+            self.env().transfer_tokens(&caller, &cspr_dust);
+        }
+
+        self.env().emit_event(Swap {
+            sender: caller,
+            cspr_in: U512::zero(),
+            cspr_out: plan.swap_out,
+            token_in: plan.swap_amount,
+            token_out: U512::zero(),
+        });
+        self.env().emit_event(LiquidityAdded {
+            provider: caller,
+            cspr_amount: plan.out_used,
+            token_amount: plan.in_used,
+            lp_minted: plan.lp_minted,
+        });
+
+        plan.lp_minted
+    }
+
+    /// Redeem LP for a single asset, swapping the other side against the pool
+    /// Tokens are paid immediately; CSPR is queued like `remove_liquidity`.
+    /// Returns the amount paid out (or queued) and the withdrawal ID for CSPR.
+    #[odra(non_reentrant)]
+    pub fn zap_out(&mut self, lp_amount: U512, asset: ZapAsset, min_out: U512) -> (U512, Option<u64>) {
+        let caller = self.env().caller();
+
+        let lp_balance = self.lp_token.balance_of(&caller);
+        if lp_amount > lp_balance {
+            self.env().revert(PoolError::InsufficientLpBalance);
+        }
+        if lp_amount == U512::zero() {
+            self.env().revert(PoolError::ZeroAmount);
+        }
+
+        // Price the LP share net of any unrealized staking loss
+        self.realize_staking_loss();
+
+        let total_lp = self.lp_token.total_supply();
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+
+        let cspr_share = (lp_amount * reserve_cspr) / total_lp;
+        let token_share = (lp_amount * reserve_token) / total_lp;
+
+        // The unwanted side is swapped against the reserves left after the burn
+        let reserve_cspr_after = reserve_cspr - cspr_share;
+        let reserve_token_after = reserve_token - token_share;
+
+        self.lp_token.raw_burn(&caller, lp_amount);
+
+        let (amount_out, withdrawal_id) = match asset {
+            ZapAsset::Token => {
+                let swap_out = self.get_amount_out(cspr_share, reserve_cspr_after, reserve_token_after);
+                let protocol_cut = self.protocol_fee_cut(cspr_share);
+                let token_out = token_share + swap_out;
+                if token_out < min_out {
+                    self.env().revert(PoolError::SlippageExceeded);
+                }
+
+                self.reserve_cspr.set(reserve_cspr - protocol_cut);
+                self.reserve_token.set(reserve_token_after - swap_out);
+
+                // The protocol cut stays in the purse outside the reserves
+                if protocol_cut > U512::zero() {
+                    self.undelegate_for_withdrawal(protocol_cut);
+                    let accrued = self.ledger.protocol_fees_cspr.get_or_default();
+                    self.ledger.protocol_fees_cspr.set(accrued + protocol_cut);
+                }
+                self.accrue_swap_fee(cspr_share, protocol_cut, true);
+
+                self.transfer_token(&caller, token_out);
+
+                self.env().emit_event(Swap {
+                    sender: caller,
+                    cspr_in: cspr_share,
+                    cspr_out: U512::zero(),
+                    token_in: U512::zero(),
+                    token_out: swap_out,
+                });
+
+                (token_out, None)
+            }
+            ZapAsset::Cspr => {
+                let swap_out = self.get_amount_out(token_share, reserve_token_after, reserve_cspr_after);
+                let protocol_cut = self.protocol_fee_cut(token_share);
+                let cspr_out = cspr_share + swap_out;
+                if cspr_out < min_out {
+                    self.env().revert(PoolError::SlippageExceeded);
+                }
+
+                if protocol_cut > U512::zero() {
+                    let accrued = self.ledger.protocol_fees_token.get_or_default();
+                    self.ledger.protocol_fees_token.set(accrued + protocol_cut);
+                }
+                self.accrue_swap_fee(token_share, protocol_cut, false);
+
+                self.reserve_cspr.set(reserve_cspr_after - swap_out);
+                self.reserve_token.set(reserve_token - protocol_cut);
+
+                // Queue CSPR withdrawal (need to undelegate)
+                self.undelegate_for_withdrawal(cspr_out);
+                let withdrawal_id = self.queue_withdrawal(caller, lp_amount, cspr_out, U512::zero());
+
+                self.env().emit_event(Swap {
+                    sender: caller,
+                    cspr_in: U512::zero(),
+                    cspr_out: swap_out,
+                    token_in: token_share,
+                    token_out: U512::zero(),
+                });
+
+                (cspr_out, Some(withdrawal_id))
+            }
+        };
+
+        self.env().emit_event(ZappedOut {
+            provider: caller,
+            lp_burned: lp_amount,
+            asset,
+            amount_out,
+            withdrawal_id,
+        });
+
+        (amount_out, withdrawal_id)
     }
 
     // ============ REMOVE LIQUIDITY ============
 
     /// Remove liquidity - queues withdrawal due to 14h unbonding
+    #[odra(non_reentrant)]
     pub fn remove_liquidity(
         &mut self,
         lp_amount: U512,
@@ -202,6 +593,9 @@ impl GhostPoolPool {
             self.env().revert(PoolError::ZeroAmount);
         }
 
+        // Price the LP share net of any unrealized staking loss
+        self.realize_staking_loss();
+
         let total_lp = self.lp_token.total_supply();
         let reserve_cspr = self.reserve_cspr.get_or_default();
         let reserve_token = self.reserve_token.get_or_default();
@@ -218,7 +612,7 @@ impl GhostPoolPool {
         }
 
         // Burn LP tokens
-        self.lp_token.burn(&caller, lp_amount);
+        self.lp_token.raw_burn(&caller, lp_amount);
 
         // Update reserves
         self.reserve_cspr.set(reserve_cspr - cspr_amount);
@@ -229,29 +623,7 @@ impl GhostPoolPool {
 
         // Queue CSPR withdrawal (need to undelegate)
         self.undelegate_for_withdrawal(cspr_amount);
-
-        let withdrawal_id = self.withdrawal_counter.get_or_default();
-        self.withdrawal_counter.set(withdrawal_id + 1);
-
-        let now = self.env().get_block_time();
-        let claimable = now + UNBONDING_PERIOD_MS;
-
-        let request = WithdrawalRequest {
-            id: withdrawal_id,
-            user: caller,
-            lp_burned: lp_amount,
-            cspr_amount,
-            token_amount,
-            request_time: now,
-            claimable_time: claimable,
-            claimed: false,
-        };
-
-        self.withdrawals.set(&withdrawal_id, request);
-
-        let mut user_ids = self.user_withdrawals.get(&caller).unwrap_or_default();
-        user_ids.push(withdrawal_id);
-        self.user_withdrawals.set(&caller, user_ids);
+        let withdrawal_id = self.queue_withdrawal(caller, lp_amount, cspr_amount, token_amount);
 
         self.env().emit_event(LiquidityRemoved {
             provider: caller,
@@ -265,45 +637,1062 @@ impl GhostPoolPool {
     }
 
     /// Claim CSPR after unbonding period
+    #[odra(non_reentrant)]
     pub fn claim_withdrawal(&mut self, withdrawal_id: u64) -> U512 {
         let caller = self.env().caller();
+        self.staking.settle();
 
-        let mut request = self.withdrawals.get(&withdrawal_id)
+        let request = self.queue.withdrawals.get(&withdrawal_id)
             .unwrap_or_else(|| self.env().revert(PoolError::WithdrawalNotFound));
 
-        if request.user != caller {
-            self.env().revert(PoolError::NotYourWithdrawal);
-        }
-        if request.claimed {
-            self.env().revert(PoolError::AlreadyClaimed);
+        if request.user != caller {
+            self.env().revert(PoolError::NotYourWithdrawal);
+        }
+        if request.claimed {
+            self.env().revert(PoolError::AlreadyClaimed);
+        }
+        if request.cancelled {
+            self.env().revert(PoolError::WithdrawalCancelled);
+        }
+        if self.env().get_block_time() < request.claimable_time {
+            self.env().revert(PoolError::StillUnbonding);
+        }
+
+        let payout = self.settle_withdrawal(request);
+
+        // Transfer CSPR
+        self.env().transfer_tokens(&caller, &payout);
+
+        payout
+    }
+
+    /// Claim every matured request of `user` in one CSPR transfer to `user`
+    ///
+    /// Callable by anyone (e.g. a relayer); funds always go to the owner. Scans at most
+    /// MAX_BATCH_CLAIM positions of the user's list starting at `offset`; page through
+    /// longer lists by calling again with `offset + MAX_BATCH_CLAIM`.
+    #[odra(non_reentrant)]
+    pub fn claim_all_matured(&mut self, user: Address, offset: u32) -> Vec<u64> {
+        self.staking.settle();
+        let now = self.env().get_block_time();
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        let end = offset.saturating_add(MAX_BATCH_CLAIM).min(count);
+
+        let mut claimed_ids = Vec::new();
+        let mut total = U512::zero();
+        for position in offset..end {
+            if let Some(request) = self.user_withdrawal_at(user, position) {
+                if request.is_open() && now >= request.claimable_time {
+                    claimed_ids.push(request.id);
+                    total += self.settle_withdrawal(request);
+                }
+            }
+        }
+
+        if total > U512::zero() {
+            self.env().transfer_tokens(&user, &total);
+        }
+
+        claimed_ids
+    }
+
+    /// Cancel a pending withdrawal and re-enter the pool at the current ratio
+    ///
+    /// The owner supplies the request's token amount back (pulled via `transfer_from`).
+    /// If only part of it is available, that share of the request is cancelled and the
+    /// rest stays queued. The returned CSPR rejoins the buffer and is re-delegated, so the
+    /// request must have matured: before that its CSPR may still be unbonding, and the purse
+    /// only holds CSPR owed to other requests. Requests with no token side (from `zap_out`
+    /// to CSPR) cannot be cancelled; they are claimed instead.
+    #[odra(non_reentrant)]
+    pub fn cancel_withdrawal(&mut self, withdrawal_id: u64, min_lp_tokens: U512) -> U512 {
+        let caller = self.env().caller();
+        self.staking.settle();
+        let mut request = self.get_withdrawal(withdrawal_id);
+
+        if request.user != caller {
+            self.env().revert(PoolError::NotYourWithdrawal);
+        }
+        if !request.is_open() {
+            self.env().revert(PoolError::AlreadyClaimed);
+        }
+        if self.env().get_block_time() < request.claimable_time {
+            self.env().revert(PoolError::StillUnbonding);
+        }
+
+        self.realize_staking_loss();
+
+        // Use as much of the token side as the owner can actually supply
+        let self_address = self.env().self_address();
+        let token_needed = request.token_amount;
+        if token_needed == U512::zero() {
+            self.env().revert(PoolError::CsprOnlyWithdrawal);
+        }
+        let available = self.token_balance_of(&caller).min(self.token_allowance(&caller, &self_address));
+        let token_used = available.min(token_needed);
+        if token_used == U512::zero() {
+            self.env().revert(PoolError::InsufficientTokenForCancel);
+        }
+
+        let payout = self.withdrawal_payout(&request);
+        let cspr_used = (payout * token_used) / token_needed;
+        let token_received = self.pull_token(&caller, token_used);
+
+        let total_lp = self.lp_token.total_supply();
+        if total_lp == U512::zero() {
+            self.env().revert(PoolError::InsufficientLiquidity);
+        }
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+        let lp_from_cspr = (cspr_used * total_lp) / reserve_cspr;
+        let lp_from_token = (token_received * total_lp) / reserve_token;
+        let lp_to_mint = lp_from_cspr.min(lp_from_token);
+
+        if lp_to_mint < min_lp_tokens {
+            self.env().revert(PoolError::SlippageExceeded);
+        }
+
+        // The CSPR liability becomes reserve again
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+        self.queue.pending_withdrawal_cspr.set(pending.saturating_sub(cspr_used));
+        self.reserve_cspr.set(reserve_cspr + cspr_used);
+        self.reserve_token.set(reserve_token + token_received);
+        let buffer = self.buffer_cspr.get_or_default();
+        self.buffer_cspr.set(buffer + cspr_used);
+
+        // Shrink the request, or close it when fully cancelled
+        let fully_cancelled = token_used == token_needed;
+        if fully_cancelled {
+            request.cancelled = true;
+            self.queue.withdrawals.set(&withdrawal_id, request.clone());
+            self.close_withdrawal(&request);
+        } else {
+            request.cspr_amount -= (request.cspr_amount * token_used) / token_needed;
+            request.lp_burned -= (request.lp_burned * token_used) / token_needed;
+            request.token_amount -= token_used;
+            self.queue.withdrawals.set(&withdrawal_id, request);
+        }
+
+        self.rebalance_stake();
+        self.lp_token.raw_mint(&caller, lp_to_mint);
+
+        self.env().emit_event(WithdrawalCancelled {
+            user: caller,
+            withdrawal_id,
+            cspr_amount: cspr_used,
+            token_amount: token_used,
+            lp_minted: lp_to_mint,
+            fully_cancelled,
+        });
+
+        lp_to_mint
+    }
+
+    /// Approve `spender` to transfer a pending withdrawal request (None revokes)
+    #[odra(non_reentrant)]
+    pub fn approve_withdrawal(&mut self, withdrawal_id: u64, spender: Option<Address>) {
+        let caller = self.env().caller();
+        let request = self.get_withdrawal(withdrawal_id);
+
+        if request.user != caller {
+            self.env().revert(PoolError::NotYourWithdrawal);
+        }
+
+        self.queue.withdrawal_approvals.set(&withdrawal_id, spender);
+
+        self.env().emit_event(WithdrawalApproval {
+            withdrawal_id,
+            owner: caller,
+            spender,
+        });
+    }
+
+    /// Transfer a pending withdrawal request; the new owner claims its CSPR
+    /// Callable by the owner or the approved spender
+    #[odra(non_reentrant)]
+    pub fn transfer_withdrawal(&mut self, withdrawal_id: u64, to: Address) {
+        let caller = self.env().caller();
+        let mut request = self.get_withdrawal(withdrawal_id);
+        let from = request.user;
+
+        if caller != from && self.queue.withdrawal_approvals.get(&withdrawal_id).flatten() != Some(caller) {
+            self.env().revert(PoolError::NotApproved);
+        }
+        if request.claimed {
+            self.env().revert(PoolError::AlreadyClaimed);
+        }
+        if request.cancelled {
+            self.env().revert(PoolError::WithdrawalCancelled);
+        }
+
+        // Move the ID between owner lists along with its pending count
+        self.remove_user_withdrawal(from, withdrawal_id);
+        self.push_user_withdrawal(to, withdrawal_id);
+        let from_pending = self.queue.user_pending_count.get(&from).unwrap_or_default();
+        self.queue.user_pending_count.set(&from, from_pending.saturating_sub(1));
+        let to_pending = self.queue.user_pending_count.get(&to).unwrap_or_default();
+        self.queue.user_pending_count.set(&to, to_pending + 1);
+
+        request.user = to;
+        self.queue.withdrawals.set(&withdrawal_id, request);
+        self.queue.withdrawal_approvals.set(&withdrawal_id, None);
+
+        self.env().emit_event(WithdrawalTransferred {
+            withdrawal_id,
+            from,
+            to,
+        });
+    }
+
+    /// Drop claimed or cancelled requests from the caller's list among positions [offset, offset + limit)
+    /// Returns how many were pruned. List order is not preserved.
+    #[odra(non_reentrant)]
+    pub fn prune_claimed_withdrawals(&mut self, offset: u32, limit: u32) -> u32 {
+        let caller = self.env().caller();
+        let mut position = offset;
+        let mut scanned = 0;
+        let mut pruned = 0;
+
+        while scanned < limit.min(MAX_WITHDRAWAL_PAGE_SIZE)
+            && position < self.queue.user_withdrawal_count.get(&caller).unwrap_or_default()
+        {
+            let withdrawal_id = self.user_withdrawal_id(caller, position);
+            let closed = match self.queue.withdrawals.get(&withdrawal_id) {
+                Some(request) => !request.is_open(),
+                None => true,
+            };
+            if closed {
+                // The last entry moves into this position; check it next
+                self.remove_user_withdrawal(caller, withdrawal_id);
+                pruned += 1;
+            } else {
+                position += 1;
+            }
+            scanned += 1;
+        }
+
+        pruned
+    }
+
+    // ============ SWAP FUNCTIONS ============
+
+    /// Swap CSPR for tokens
+    #[odra(payable, non_reentrant)]
+    pub fn swap_cspr_for_token(&mut self, min_token_out: U512) -> U512 {
+        let caller = self.env().caller();
+        let cspr_in = self.env().attached_value();
+        self.execute_swap_cspr_for_token(caller, cspr_in, min_token_out)
+    }
+
+    /// Swap CSPR for a fee-on-transfer token
+    /// `min_token_out` applies to the caller's balance increase, after any transfer fee.
+    /// Returns the amount received.
+    #[odra(payable, non_reentrant)]
+    pub fn swap_cspr_for_token_supporting_fee(&mut self, min_token_out: U512) -> U512 {
+        let caller = self.env().caller();
+        let cspr_in = self.env().attached_value();
+
+        let balance_before = self.token_balance_of(&caller);
+        self.execute_swap_cspr_for_token(caller, cspr_in, U512::zero());
+        let received = self.token_balance_of(&caller).saturating_sub(balance_before);
+        if received < min_token_out {
+            self.env().revert(PoolError::SlippageExceeded);
+        }
+
+        received
+    }
+
+    /// Swap tokens for CSPR
+    /// Under `BalanceDelta` accounting the swap is priced on the tokens actually received.
+    #[odra(non_reentrant)]
+    pub fn swap_token_for_cspr(
+        &mut self,
+        token_in: U512,
+        min_cspr_out: U512,
+    ) -> U512 {
+        let caller = self.env().caller();
+
+        if token_in == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
+        }
+
+        // Transfer tokens from user
+        let token_in = self.pull_token(&caller, token_in);
+        self.execute_swap_token_for_cspr(caller, token_in, min_cspr_out)
+    }
+
+    /// Swap a fee-on-transfer token for CSPR
+    /// Prices the swap on the pool's balance increase, whatever the accounting mode.
+    #[odra(non_reentrant)]
+    pub fn swap_token_for_cspr_supporting_fee(
+        &mut self,
+        token_in: U512,
+        min_cspr_out: U512,
+    ) -> U512 {
+        let caller = self.env().caller();
+
+        if token_in == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
+        }
+
+        let token_in = self.token.pull_measured(&caller, token_in);
+        if token_in == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
+        }
+        self.execute_swap_token_for_cspr(caller, token_in, min_cspr_out)
+    }
+
+    // ============ COMPOUND ============
+
+    /// Harvest and compound staking rewards
+    ///
+    /// Pending rewards are undelegated and only credited once they are back from the
+    /// auction, so each reward is returned by the first `compound` after it has unbonded.
+    #[odra(non_reentrant)]
+    pub fn compound(&mut self) -> U512 {
+        self.realize_staking_loss();
+        self.staking.settle();
+
+        let rewards_to_pool = self.credit_harvested_rewards();
+
+        // Withdraw new rewards from auction; they unbond with the rest of the bucket
+        let rewards = self.get_pending_rewards();
+        if rewards > U512::zero() {
+            self.withdraw_staking_rewards();
+            let harvested = self.ledger.harvested_rewards.get_or_default();
+            self.ledger.harvested_rewards.set(harvested + rewards);
+        }
+
+        rewards_to_pool
+    }
+
+    // ============ PROTOCOL FEES ============
+
+    /// Send accrued protocol swap fees to the treasury
+    #[odra(non_reentrant)]
+    pub fn claim_protocol_fees(&mut self) -> (U512, U512) {
+        let cspr_amount = self.ledger.protocol_fees_cspr.get_or_default();
+        let token_amount = self.ledger.protocol_fees_token.get_or_default();
+        let treasury = self.treasury.get().expect("Treasury not set");
+
+        self.ledger.protocol_fees_cspr.set(U512::zero());
+        self.ledger.protocol_fees_token.set(U512::zero());
+
+        if cspr_amount > U512::zero() {
+            self.env().transfer_tokens(&treasury, &cspr_amount);
+        }
+        if token_amount > U512::zero() {
+            self.transfer_token(&treasury, token_amount);
+        }
+
+        self.env().emit_event(ProtocolFeesClaimed {
+            treasury,
+            cspr_amount,
+            token_amount,
+        });
+
+        (cspr_amount, token_amount)
+    }
+
+    // ============ LOSS HANDLING ============
+
+    /// Write down any delegation shortfall (e.g. slashing) and return its size
+    ///
+    /// The loss is shared pro rata between the CSPR reserve and pending withdrawals,
+    /// which were priced before the shortfall was detected. The queue's share scales
+    /// down every unclaimed request through the withdrawal payout index.
+    #[odra(non_reentrant)]
+    pub fn realize_loss(&mut self) -> U512 {
+        self.realize_staking_loss()
+    }
+
+    // ============ RECONCILIATION ============
+
+    /// Reconcile tracked reserves with actual balances (admin only)
+    ///
+    /// Surplus CSPR and tokens (e.g. from a positive rebase) are credited to the reserves, and
+    /// a token balance below the reserve (negative rebase) shrinks it. Delegation shortfalls
+    /// are realized as losses; delegation gains are left for `compound` to harvest.
+    #[odra(non_reentrant)]
+    pub fn sync(&mut self) {
+        self.require_admin();
+        self.require_settled_unbonding();
+        self.realize_staking_loss();
+
+        let (purse, _) = self.actual_cspr_balances();
+        let new_staked = self.staked_cspr.get_or_default();
+        let new_buffer = purse.saturating_sub(self.cspr_liabilities_in_purse());
+        let new_reserve_cspr = new_staked + new_buffer;
+
+        let token_balance = self.token_balance();
+        let new_reserve_token = token_balance.saturating_sub(self.ledger.protocol_fees_token.get_or_default());
+
+        self.staked_cspr.set(new_staked);
+        self.buffer_cspr.set(new_buffer);
+        self.reserve_cspr.set(new_reserve_cspr);
+        self.reserve_token.set(new_reserve_token);
+
+        self.env().emit_event(Synced {
+            reserve_cspr: new_reserve_cspr,
+            reserve_token: new_reserve_token,
+            staked_cspr: new_staked,
+            buffer_cspr: new_buffer,
+        });
+    }
+
+    /// Send CSPR and tokens held beyond all tracked balances to `to` (admin only)
+    #[odra(non_reentrant)]
+    pub fn skim(&mut self, to: Address) -> (U512, U512) {
+        self.require_admin();
+        self.require_settled_unbonding();
+
+        let (purse, _) = self.actual_cspr_balances();
+        let expected_purse = self.buffer_cspr.get_or_default() + self.cspr_liabilities_in_purse();
+        let cspr_amount = purse.saturating_sub(expected_purse);
+
+        let expected_token = self.reserve_token.get_or_default() + self.ledger.protocol_fees_token.get_or_default();
+        let token_amount = self.token_balance().saturating_sub(expected_token);
+
+        if cspr_amount > U512::zero() {
+            self.env().transfer_tokens(&to, &cspr_amount);
+        }
+        if token_amount > U512::zero() {
+            self.transfer_token(&to, token_amount);
+        }
+
+        self.env().emit_event(Skimmed {
+            recipient: to,
+            cspr_amount,
+            token_amount,
+        });
+
+        (cspr_amount, token_amount)
+    }
+
+    // ============ ADMIN FUNCTIONS ============
+
+    /// Route staking to an `AuctionSimulator` contract (admin only, before any stake)
+    /// Reverts in production WASM builds without the `test-mode` feature.
+    #[odra(non_reentrant)]
+    pub fn set_auction_simulator(&mut self, simulator: Address) {
+        self.require_admin();
+        if self.staked_cspr.get_or_default() > U512::zero() {
+            self.env().revert(PoolError::StakeActive);
+        }
+        self.staking.set_simulator(simulator);
+    }
+
+    /// Set the share of each swap fee diverted to the treasury (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_protocol_swap_fee(&mut self, protocol_swap_fee_bps: U256) {
+        self.require_admin();
+        if protocol_swap_fee_bps > U256::from(MAX_PROTOCOL_SWAP_FEE_BPS) {
+            self.env().revert(PoolError::InvalidFee);
+        }
+
+        let mut config = self.config.get_or_default();
+        config.protocol_swap_fee_bps = protocol_swap_fee_bps;
+        self.config.set(config);
+
+        self.env().emit_event(ProtocolSwapFeeUpdated { protocol_swap_fee_bps });
+    }
+
+    /// Replace the pool configuration (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_config(&mut self, config: PoolConfig) {
+        self.require_admin();
+        if !config.is_valid() {
+            self.env().revert(PoolError::InvalidFee);
+        }
+
+        self.config.set(config.clone());
+        self.rebalance_stake();
+
+        self.env().emit_event(ConfigUpdated {
+            buffer_target_bps: config.buffer_target_bps,
+            swap_fee_bps: config.swap_fee_bps,
+            protocol_fee_bps: config.protocol_fee_bps,
+            protocol_swap_fee_bps: config.protocol_swap_fee_bps,
+        });
+    }
+
+    /// Set the treasury receiving protocol fees (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_treasury(&mut self, treasury: Address) {
+        self.require_admin();
+        self.treasury.set(treasury);
+
+        self.env().emit_event(TreasuryUpdated { treasury });
+    }
+
+    /// Hand the admin role over to `admin` (admin only)
+    #[odra(non_reentrant)]
+    pub fn set_admin(&mut self, admin: Address) {
+        self.require_admin();
+        self.admin.set(admin);
+
+        self.env().emit_event(AdminUpdated { admin });
+    }
+
+    /// Set how incoming paired tokens are credited (admin only)
+    /// Use `BalanceDelta` for fee-on-transfer or rebasing tokens; call `sync` after a rebase.
+    #[odra(non_reentrant)]
+    pub fn set_token_accounting(&mut self, accounting: TokenAccounting) {
+        self.require_admin();
+        self.token.set_accounting(accounting.clone());
+
+        self.env().emit_event(TokenAccountingUpdated { accounting });
+    }
+
+    /// Switch the pool to the StableSwap curve (admin only, before any liquidity)
+    /// The paired token must use CSPR's decimals, since the curve compares raw reserves.
+    #[odra(non_reentrant)]
+    pub fn set_stable_curve(&mut self, amp: u64) {
+        self.require_admin();
+        if self.lp_token.total_supply() > U512::zero() {
+            self.env().revert(PoolError::LiquidityPresent);
+        }
+        if self.token.decimals() != CSPR_DECIMALS {
+            self.env().revert(PoolError::DecimalsMismatch);
+        }
+        self.curve.enable(amp);
+    }
+
+    /// Ramp the StableSwap amplification coefficient linearly until `future_time` (admin only)
+    #[odra(non_reentrant)]
+    pub fn ramp_amp(&mut self, future_amp: u64, future_time: u64) {
+        self.require_admin();
+        self.curve.ramp(future_amp, future_time);
+    }
+
+    /// Freeze the StableSwap amplification coefficient at its current value (admin only)
+    #[odra(non_reentrant)]
+    pub fn stop_amp_ramp(&mut self) {
+        self.require_admin();
+        self.curve.stop_ramp();
+    }
+
+    /// Switch the validator the pool delegates to (admin only)
+    /// Only allowed while nothing is staked or unbonding
+    #[odra(non_reentrant)]
+    pub fn set_validator(&mut self, validator: PublicKey) {
+        self.require_admin();
+        if self.staked_cspr.get_or_default() > U512::zero() || self.in_flight_unbonding() > U512::zero() {
+            self.env().revert(PoolError::StakeActive);
+        }
+        if self.staking.liquid_staking().is_some() {
+            self.env().revert(PoolError::ValidatorMismatch);
+        }
+        self.staking.set_validator(validator.clone());
+
+        self.env().emit_event(ValidatorUpdated { validator });
+    }
+
+    /// Stake through a stCSPR contract instead of delegating directly (admin only)
+    /// Only allowed while nothing is staked or unbonding; stCSPR must use the pool's validator.
+    /// Afterwards LPs can also deposit stCSPR via `add_liquidity_st_cspr`.
+    #[odra(non_reentrant)]
+    pub fn set_liquid_staking(&mut self, st_cspr: Address) {
+        self.require_admin();
+        if self.staked_cspr.get_or_default() > U512::zero() || self.in_flight_unbonding() > U512::zero() {
+            self.env().revert(PoolError::StakeActive);
+        }
+        let validator = LiquidStakingContractRef::new(self.env(), st_cspr).validator();
+        if validator != self.staking.validator() {
+            self.env().revert(PoolError::ValidatorMismatch);
+        }
+        self.staking.set_liquid_staking(st_cspr);
+
+        self.env().emit_event(LiquidStakingEnabled { st_cspr });
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Get how incoming paired tokens are credited
+    pub fn token_accounting(&self) -> TokenAccounting {
+        self.token.accounting()
+    }
+
+    /// Get the swap curve
+    pub fn curve_type(&self) -> CurveType {
+        self.curve.curve()
+    }
+
+    /// Get the current StableSwap amplification coefficient (0 on the constant product curve)
+    pub fn get_amp(&self) -> u64 {
+        self.curve.amp()
+    }
+
+    /// Get the current (or last) amplification ramp
+    pub fn get_amp_ramp(&self) -> AmpRamp {
+        self.curve.ramp_info()
+    }
+
+    /// Get the admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    /// Get the treasury address
+    pub fn treasury(&self) -> Address {
+        self.treasury.get().expect("Treasury not set")
+    }
+
+    /// Get the pool configuration
+    pub fn get_config(&self) -> PoolConfig {
+        self.config.get_or_default()
+    }
+
+    /// Get the validator the pool delegates to
+    pub fn validator(&self) -> PublicKey {
+        self.staking.validator()
+    }
+
+    /// Get the stCSPR contract the pool stakes through, if any
+    pub fn liquid_staking(&self) -> Option<Address> {
+        self.staking.liquid_staking()
+    }
+
+    /// Get reserves, staking split, config, roles and liabilities as one consistent snapshot
+    pub fn get_pool_state(&self) -> PoolState {
+        PoolState {
+            token_address: self.token_address(),
+            token_accounting: self.token.accounting(),
+            token_decimals: self.token.decimals(),
+            reserve_cspr: self.reserve_cspr.get_or_default(),
+            reserve_token: self.reserve_token.get_or_default(),
+            staked_cspr: self.staked_cspr.get_or_default(),
+            buffer_cspr: self.buffer_cspr.get_or_default(),
+            unbonding_cspr: self.in_flight_unbonding(),
+            lp_total_supply: self.lp_token.total_supply(),
+            config: self.config.get_or_default(),
+            curve: self.curve.curve(),
+            validator: self.staking.validator(),
+            liquid_staking: self.staking.liquid_staking(),
+            treasury: self.treasury(),
+            admin: self.admin(),
+            pending_withdrawal_cspr: self.queue.pending_withdrawal_cspr.get_or_default(),
+            protocol_fees_cspr: self.ledger.protocol_fees_cspr.get_or_default(),
+            protocol_fees_token: self.ledger.protocol_fees_token.get_or_default(),
+        }
+    }
+
+    /// Get the paired token's decimals
+    pub fn token_decimals(&self) -> u8 {
+        self.token.decimals()
+    }
+
+    /// Get the spot price of one CSPR in whole tokens, with PRICE_DECIMALS decimals
+    /// Independent of the token's decimals; zero for an empty pool.
+    pub fn get_spot_price(&self) -> U512 {
+        self.spot_price(PRICE_DECIMALS)
+    }
+
+    /// Get the spot price of one CSPR in whole tokens, with `decimals` decimals
+    /// e.g. the token's own decimals gives the token base units one CSPR buys at the margin.
+    pub fn get_price_with_decimals(&self, decimals: u8) -> U512 {
+        if decimals > MAX_PRICE_DECIMALS {
+            self.env().revert(PoolError::InvalidDecimals);
+        }
+        self.spot_price(decimals)
+    }
+
+    /// Get current reserves
+    pub fn get_reserves(&self) -> (U512, U512) {
+        (
+            self.reserve_cspr.get_or_default(),
+            self.reserve_token.get_or_default(),
+        )
+    }
+
+    /// Get staking rewards harvested by `compound` and not yet credited to the reserve
+    pub fn get_harvested_rewards(&self) -> U512 {
+        self.ledger.harvested_rewards.get_or_default()
+    }
+
+    /// Get staking info (staked, buffer)
+    pub fn get_staking_info(&self) -> (U512, U512) {
+        (
+            self.staked_cspr.get_or_default(),
+            self.buffer_cspr.get_or_default(),
+        )
+    }
+
+    /// Quote CSPR to token swap
+    pub fn quote_cspr_for_token(&self, cspr_in: U512) -> U512 {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        self.get_amount_out(cspr_in, reserve_cspr, reserve_token)
+    }
+
+    /// Quote token to CSPR swap
+    pub fn quote_token_for_cspr(&self, token_in: U512) -> U512 {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        self.get_amount_out(token_in, reserve_token, reserve_cspr)
+    }
+
+    /// Quote CSPR to token swap with execution details
+    /// `slippage_bps` sets `min_amount_out` (50 = 0.5%); spot prices are as in `get_spot_price`.
+    pub fn quote_cspr_for_token_detailed(&self, cspr_in: U512, slippage_bps: U256) -> SwapQuote {
+        self.detailed_quote(cspr_in, true, slippage_bps)
+    }
+
+    /// Quote token to CSPR swap with execution details
+    /// `buffer_sufficient` is false when the CSPR out exceeds the buffer and the swap would revert.
+    pub fn quote_token_for_cspr_detailed(&self, token_in: U512, slippage_bps: U256) -> SwapQuote {
+        self.detailed_quote(token_in, false, slippage_bps)
+    }
+
+    /// CSPR needed to buy exactly `token_out` tokens (0 if the reserves cannot cover it)
+    pub fn quote_cspr_for_exact_token(&self, token_out: U512) -> U512 {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        self.get_amount_in(token_out, reserve_cspr, reserve_token)
+    }
+
+    /// Tokens needed to buy exactly `cspr_out` CSPR (0 if the reserves cannot cover it)
+    pub fn quote_token_for_exact_cspr(&self, cspr_out: U512) -> U512 {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        self.get_amount_in(cspr_out, reserve_token, reserve_cspr)
+    }
+
+    /// Quote a CSPR zap as (CSPR swapped, tokens received from the swap, LP minted)
+    pub fn quote_zap_in_cspr(&self, cspr_in: U512) -> (U512, U512, U512) {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        if self.lp_token.total_supply() == U512::zero() {
+            return (U512::zero(), U512::zero(), U512::zero());
+        }
+
+        let plan = self.plan_zap_in(cspr_in, reserve_cspr, reserve_token);
+        (plan.swap_amount, plan.swap_out, plan.lp_minted)
+    }
+
+    /// Quote a token zap as (tokens swapped, CSPR received from the swap, LP minted)
+    pub fn quote_zap_in_token(&self, token_in: U512) -> (U512, U512, U512) {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        if self.lp_token.total_supply() == U512::zero() {
+            return (U512::zero(), U512::zero(), U512::zero());
+        }
+
+        let plan = self.plan_zap_in(token_in, reserve_token, reserve_cspr);
+        (plan.swap_amount, plan.swap_out, plan.lp_minted)
+    }
+
+    /// Get LP token value in underlying assets
+    pub fn get_lp_value(&self, lp_amount: U512) -> (U512, U512) {
+        let total_lp = self.lp_token.total_supply();
+        if total_lp == U512::zero() {
+            return (U512::zero(), U512::zero());
+        }
+
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+
+        let cspr_value = (lp_amount * reserve_cspr) / total_lp;
+        let token_value = (lp_amount * reserve_token) / total_lp;
+
+        (cspr_value, token_value)
+    }
+
+    /// Get user's withdrawal requests
+    /// Loads the whole list; prefer `get_user_withdrawals_page` for active users
+    pub fn get_user_withdrawals(&self, user: Address) -> Vec<WithdrawalRequest> {
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        (0..count)
+            .filter_map(|position| self.user_withdrawal_at(user, position))
+            .collect()
+    }
+
+    /// Get a page of user's withdrawal requests (limit capped at MAX_WITHDRAWAL_PAGE_SIZE)
+    pub fn get_user_withdrawals_page(&self, user: Address, offset: u32, limit: u32) -> Vec<WithdrawalRequest> {
+        let end = self.page_end(user, offset, limit);
+        (offset..end)
+            .filter_map(|position| self.user_withdrawal_at(user, position))
+            .collect()
+    }
+
+    /// Get user's withdrawal requests with `status` among positions [offset, offset + limit)
+    pub fn get_user_withdrawals_by_status(
+        &self,
+        user: Address,
+        status: WithdrawalStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<WithdrawalRequest> {
+        let end = self.page_end(user, offset, limit);
+        (offset..end)
+            .filter_map(|position| self.user_withdrawal_at(user, position))
+            .filter(|request| self.withdrawal_status(request) == status)
+            .collect()
+    }
+
+    /// Get user's withdrawal counts (pending, claimed or cancelled but not yet pruned)
+    pub fn get_user_withdrawal_counts(&self, user: Address) -> (u32, u32) {
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        let pending = self.queue.user_pending_count.get(&user).unwrap_or_default();
+        (pending, count - pending)
+    }
+
+    /// Get the status of a withdrawal request
+    pub fn get_withdrawal_status(&self, withdrawal_id: u64) -> WithdrawalStatus {
+        let request = self.get_withdrawal(withdrawal_id);
+        self.withdrawal_status(&request)
+    }
+
+    /// Get the address approved to transfer a withdrawal request
+    pub fn get_withdrawal_approved(&self, withdrawal_id: u64) -> Option<Address> {
+        self.queue.withdrawal_approvals.get(&withdrawal_id).flatten()
+    }
+
+    /// Get CSPR owed to all unclaimed withdrawal requests
+    pub fn total_pending_withdrawals(&self) -> U512 {
+        self.queue.pending_withdrawal_cspr.get_or_default()
+    }
+
+    /// Get the number of unclaimed withdrawal requests
+    pub fn pending_withdrawal_count(&self) -> u64 {
+        self.queue.pending_withdrawal_count.get_or_default()
+    }
+
+    /// Get when the oldest unclaimed withdrawal becomes claimable
+    pub fn next_claimable_time(&self) -> Option<u64> {
+        // Claimable times grow with IDs, so the first open request is the earliest
+        let counter = self.queue.withdrawal_counter.get_or_default();
+        let mut id = self.queue.queue_head.get_or_default();
+        let mut scanned = 0;
+        while id < counter && scanned < MAX_QUEUE_SCAN {
+            if let Some(request) = self.queue.withdrawals.get(&id) {
+                if request.is_open() {
+                    return Some(request.claimable_time);
+                }
+            }
+            id += 1;
+            scanned += 1;
+        }
+        None
+    }
+
+    /// Get LP token address (returns pool address as LP token is a submodule)
+    pub fn lp_token_address(&self) -> Address {
+        self.env().self_address()
+    }
+
+    /// Get paired token address
+    pub fn token_address(&self) -> Address {
+        self.token.contract_address()
+    }
+
+    /// Get LP token balance for an address
+    pub fn lp_balance_of(&self, owner: &Address) -> U512 {
+        self.lp_token.balance_of(owner)
+    }
+
+    /// Get LP token total supply
+    pub fn lp_total_supply(&self) -> U512 {
+        self.lp_token.total_supply()
+    }
+
+    /// Get pool state with swap volume, LP fees and compounded rewards over the last
+    /// STATS_WINDOW_DAYS days, and the yields they imply
+    ///
+    /// Yields are annualized over the window (shorter while the pool is younger) against the
+    /// current pool value, counting both sides in CSPR at the reserve ratio. Fees and rewards
+    /// both stay in the reserves, so the total APY compounds their sum daily.
+    pub fn get_pool_stats(&self) -> PoolStats {
+        let state = self.get_pool_state();
+        let (totals, window_start) = self.ledger.window();
+        let window_end = self.env().get_block_time();
+        let elapsed = window_end - window_start;
+
+        let pool_value = state.reserve_cspr * U512::from(2u64);
+        let fees_token_in_cspr = if state.reserve_token == U512::zero() {
+            U512::zero()
+        } else {
+            (totals.fees_token * state.reserve_cspr) / state.reserve_token
+        };
+        let fee_apr_bps = math::annualized_bps(totals.fees_cspr + fees_token_in_cspr, pool_value, elapsed);
+        let staking_apr_bps = math::annualized_bps(totals.staking_rewards, pool_value, elapsed);
+        let total_apy_bps = math::compounded_apy_bps(fee_apr_bps.saturating_add(staking_apr_bps), 365);
+
+        PoolStats {
+            state,
+            window_start,
+            window_end,
+            totals,
+            fee_apr_bps,
+            staking_apr_bps,
+            total_apy_bps,
+        }
+    }
+
+    /// Get swap fees and staking rewards earned by `owner`'s LP tokens since their first deposit
+    ///
+    /// Each is attributed when credited to the reserves (fees net of the protocol cut,
+    /// rewards after the protocol fee), pro rata to the LP held at that moment. Amounts are
+    /// nominal: price moves and realized staking losses are not netted out.
+    pub fn get_lp_earnings(&self, owner: Address) -> LpEarnings {
+        self.lp_token.earnings_of(&owner)
+    }
+
+    /// Transfer LP tokens (standard CEP-18, the pool address is the GP-LP token)
+    #[odra(non_reentrant)]
+    pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
+        self.lp_token.transfer(recipient, amount);
+    }
+
+    /// Transfer LP tokens from owner to recipient (standard CEP-18, requires prior approval)
+    #[odra(non_reentrant)]
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.lp_token.transfer_from(owner, recipient, amount);
+    }
+
+    /// Approve a spender of the caller's LP tokens (standard CEP-18)
+    #[odra(non_reentrant)]
+    pub fn approve(&mut self, spender: &Address, amount: &U256) {
+        self.lp_token.approve(spender, amount);
+    }
+
+    /// Get LP token allowance (standard CEP-18)
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        self.lp_token.allowance(owner, spender)
+    }
+
+    /// Get protocol swap fees accrued to the treasury (cspr, token)
+    pub fn get_protocol_fees(&self) -> (U512, U512) {
+        (
+            self.ledger.protocol_fees_cspr.get_or_default(),
+            self.ledger.protocol_fees_token.get_or_default(),
+        )
+    }
+
+    /// Compare tracked balances with actual purse, delegation and token balances
+    pub fn get_solvency_report(&self) -> SolvencyReport {
+        let (purse, delegated) = self.actual_cspr_balances();
+        let unbonding = self.in_flight_unbonding();
+        let token_balance = self.token_balance();
+
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let reserve_token = self.reserve_token.get_or_default();
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+        let fees_cspr = self.ledger.protocol_fees_cspr.get_or_default();
+        let fees_token = self.ledger.protocol_fees_token.get_or_default();
+
+        // CSPR returning from the auction is still owned by the pool
+        let cspr_assets = purse + delegated + unbonding;
+        let harvested = self.ledger.harvested_rewards.get_or_default();
+        let cspr_liabilities = reserve_cspr + pending + fees_cspr + harvested;
+        let token_liabilities = reserve_token + fees_token;
+
+        SolvencyReport {
+            tracked_reserve_cspr: reserve_cspr,
+            tracked_staked_cspr: self.staked_cspr.get_or_default(),
+            tracked_buffer_cspr: self.buffer_cspr.get_or_default(),
+            tracked_reserve_token: reserve_token,
+            actual_purse_cspr: purse,
+            actual_delegated_cspr: delegated,
+            actual_token_balance: token_balance,
+            pending_withdrawal_cspr: pending,
+            unbonding_cspr: unbonding,
+            protocol_fees_cspr: fees_cspr,
+            protocol_fees_token: fees_token,
+            cspr_surplus: cspr_assets.saturating_sub(cspr_liabilities),
+            cspr_deficit: cspr_liabilities.saturating_sub(cspr_assets),
+            token_surplus: token_balance.saturating_sub(token_liabilities),
+            token_deficit: token_liabilities.saturating_sub(token_balance),
+        }
+    }
+
+    /// Get delegated CSPR missing compared to the tracked stake (unrealized loss)
+    pub fn get_staking_shortfall(&self) -> U512 {
+        self.staked_cspr.get_or_default().saturating_sub(self.current_delegated())
+    }
+
+    /// Get the current withdrawal payout index
+    pub fn get_payout_index(&self) -> U512 {
+        self.payout_index()
+    }
+
+    /// Get the CSPR a withdrawal request currently pays out (after realized losses)
+    pub fn get_withdrawal_payout(&self, withdrawal_id: u64) -> U512 {
+        let request = self.get_withdrawal(withdrawal_id);
+        self.withdrawal_payout(&request)
+    }
+
+    /// Get a specific withdrawal request by ID
+    pub fn get_withdrawal(&self, withdrawal_id: u64) -> WithdrawalRequest {
+        self.queue.withdrawals.get(&withdrawal_id)
+            .unwrap_or_else(|| self.env().revert(PoolError::WithdrawalNotFound))
+    }
+
+    // ============ INTERNAL FUNCTIONS ============
+
+    /// Swap output along the pool's curve with the configured swap fee
+    fn get_amount_out(&self, amount_in: U512, reserve_in: U512, reserve_out: U512) -> U512 {
+        let config = self.config.get_or_default();
+        self.curve.get_amount_out(amount_in, reserve_in, reserve_out, config.swap_fee_bps.as_u64())
+    }
+
+    /// Smallest input for which `get_amount_out` yields at least `amount_out`
+    fn get_amount_in(&self, amount_out: U512, reserve_in: U512, reserve_out: U512) -> U512 {
+        let config = self.config.get_or_default();
+        self.curve.get_amount_in(amount_out, reserve_in, reserve_out, config.swap_fee_bps.as_u64())
+    }
+
+    /// Plan a single-sided deposit: swap part of `amount_in`, then pair the rest at the new ratio
+    /// The swap share is optimal for the constant product curve; on the stable curve it is
+    /// close but not exact, and whatever cannot be paired is refunded as dust.
+    fn plan_zap_in(&self, amount_in: U512, reserve_in: U512, reserve_out: U512) -> ZapPlan {
+        let mut plan = ZapPlan::default();
+        let total_lp = self.lp_token.total_supply();
+        if amount_in == U512::zero() || reserve_in == U512::zero() || reserve_out == U512::zero() {
+            return plan;
         }
-        if self.env().get_block_time() < request.claimable_time {
-            self.env().revert(PoolError::StillUnbonding);
+
+        let swap_fee_bps = self.config.get_or_default().swap_fee_bps.as_u64();
+        plan.swap_amount = math::optimal_swap_amount(amount_in, reserve_in, swap_fee_bps);
+        plan.swap_out = self.get_amount_out(plan.swap_amount, reserve_in, reserve_out);
+        plan.protocol_cut = self.protocol_fee_cut(plan.swap_amount);
+        if plan.swap_out >= reserve_out {
+            return plan;
         }
 
-        request.claimed = true;
-        self.withdrawals.set(&withdrawal_id, request.clone());
+        let reserve_in_after = reserve_in + plan.swap_amount - plan.protocol_cut;
+        let reserve_out_after = reserve_out - plan.swap_out;
+        let remaining_in = amount_in - plan.swap_amount;
 
-        // Transfer CSPR
-        self.env().transfer_tokens(&caller, &request.cspr_amount);
+        // Pair as much as the ratio allows; the rest is dust
+        let (in_used, out_used) =
+            math::optimal_amounts(remaining_in, plan.swap_out, reserve_in_after, reserve_out_after);
+        plan.in_used = in_used;
+        plan.out_used = out_used;
 
-        self.env().emit_event(WithdrawalClaimed {
-            user: caller,
-            withdrawal_id,
-            cspr_amount: request.cspr_amount,
-        });
+        let lp_from_in = (in_used * total_lp) / reserve_in_after;
+        let lp_from_out = (out_used * total_lp) / reserve_out_after;
+        plan.lp_minted = lp_from_in.min(lp_from_out);
 
-        request.cspr_amount
+        plan
     }
 
-    // ============ SWAP FUNCTIONS ============
-
-    /// Swap CSPR for tokens
-    #[odra(payable)]
-    pub fn swap_cspr_for_token(&mut self, min_token_out: U512) -> U512 {
-        let caller = self.env().caller();
-        let cspr_in = self.env().attached_value();
+    /// LP for a deposit; see `math::liquidity_to_mint`
+    /// The first deposit's geometric mean takes the token side in CSPR decimals, so the
+    /// initial LP supply is the same for 6-, 9- or 18-decimal tokens of equal value.
+    fn liquidity_to_mint(
+        &self,
+        cspr_amount: U512,
+        token_amount: U512,
+        reserve_cspr: U512,
+        reserve_token: U512,
+        total_lp: U512,
+    ) -> (U512, U512) {
+        let token_amount = if total_lp == U512::zero() {
+            math::scale_decimals(token_amount, self.token.decimals(), CSPR_DECIMALS)
+        } else {
+            token_amount
+        };
+        let min_liq = self.minimum_liquidity.get_or_default();
+        math::liquidity_to_mint(cspr_amount, token_amount, reserve_cspr, reserve_token, total_lp, min_liq)
+    }
 
+    /// Swap `cspr_in` attached by `caller` for tokens sent to `caller`
+    fn execute_swap_cspr_for_token(&mut self, caller: Address, cspr_in: U512, min_token_out: U512) -> U512 {
         if cspr_in == U512::zero() {
             self.env().revert(PoolError::ZeroCsprAmount);
         }
@@ -321,12 +1710,20 @@ impl GhostPoolPool {
             self.env().revert(PoolError::InsufficientLiquidity);
         }
 
+        // Divert the protocol share of the fee out of the reserves
+        let protocol_cut = self.protocol_fee_cut(cspr_in);
+        if protocol_cut > U512::zero() {
+            let accrued = self.ledger.protocol_fees_cspr.get_or_default();
+            self.ledger.protocol_fees_cspr.set(accrued + protocol_cut);
+        }
+        self.accrue_swap_fee(cspr_in, protocol_cut, true);
+
         // Update reserves
-        self.reserve_cspr.set(reserve_cspr + cspr_in);
+        self.reserve_cspr.set(reserve_cspr + cspr_in - protocol_cut);
         self.reserve_token.set(reserve_token - token_out);
 
         // Add CSPR to buffer, then rebalance
-        let new_buffer = self.buffer_cspr.get_or_default() + cspr_in;
+        let new_buffer = self.buffer_cspr.get_or_default() + cspr_in - protocol_cut;
         self.buffer_cspr.set(new_buffer);
         self.rebalance_stake();
 
@@ -344,18 +1741,8 @@ impl GhostPoolPool {
         token_out
     }
 
-    /// Swap tokens for CSPR
-    pub fn swap_token_for_cspr(
-        &mut self,
-        token_in: U512,
-        min_cspr_out: U512,
-    ) -> U512 {
-        let caller = self.env().caller();
-
-        if token_in == U512::zero() {
-            self.env().revert(PoolError::ZeroTokenAmount);
-        }
-
+    /// Swap `token_in`, already received from `caller`, for CSPR sent to `caller`
+    fn execute_swap_token_for_cspr(&mut self, caller: Address, token_in: U512, min_cspr_out: U512) -> U512 {
         let reserve_cspr = self.reserve_cspr.get_or_default();
         let reserve_token = self.reserve_token.get_or_default();
 
@@ -372,18 +1759,23 @@ impl GhostPoolPool {
             self.env().revert(PoolError::InsufficientBuffer);
         }
 
-        // Transfer tokens from user
-        self.transfer_token_from(&caller, &self.env().self_address(), token_in);
+        // Divert the protocol share of the fee out of the reserves
+        let protocol_cut = self.protocol_fee_cut(token_in);
+        if protocol_cut > U512::zero() {
+            let accrued = self.ledger.protocol_fees_token.get_or_default();
+            self.ledger.protocol_fees_token.set(accrued + protocol_cut);
+        }
+        self.accrue_swap_fee(token_in, protocol_cut, false);
 
         // Update reserves
         self.reserve_cspr.set(reserve_cspr - cspr_out);
-        self.reserve_token.set(reserve_token + token_in);
+        self.reserve_token.set(reserve_token + token_in - protocol_cut);
 
         // Update buffer
         self.buffer_cspr.set(buffer - cspr_out);
 
         // Transfer CSPR to user
-        self.env().transfer_tokens(&caller, &cspr_out);
+        self.pay_cspr(&caller, cspr_out);
 
         self.env().emit_event(Swap {
             sender: caller,
@@ -396,147 +1788,351 @@ impl GhostPoolPool {
         cspr_out
     }
 
-    // ============ COMPOUND ============
+    /// Marginal price of one CSPR in whole tokens at the current reserves, before fees
+    fn spot_price(&self, decimals: u8) -> U512 {
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        self.spot_price_at(reserve_cspr, reserve_token, decimals)
+    }
 
-    /// Harvest and compound staking rewards
-    pub fn compound(&mut self) -> U512 {
-        let rewards = self.get_pending_rewards();
+    /// Marginal price of one CSPR in whole tokens at the given reserves, before fees
+    fn spot_price_at(&self, reserve_cspr: U512, reserve_token: U512, decimals: u8) -> U512 {
+        let (cspr_amount, token_amount) = self.spot_ratio(reserve_cspr, reserve_token);
+        math::price(cspr_amount, CSPR_DECIMALS, token_amount, self.token.decimals(), decimals)
+    }
 
-        if rewards == U512::zero() {
-            return U512::zero();
+    /// CSPR and token amounts exchanging at the marginal price (zeros for empty reserves)
+    /// Constant product prices at the reserve ratio; StableSwap at a fee-free probe swap
+    /// of 1 / SPOT_PRICE_PROBE_DIVISOR of the CSPR reserve.
+    fn spot_ratio(&self, reserve_cspr: U512, reserve_token: U512) -> (U512, U512) {
+        if reserve_cspr == U512::zero() || reserve_token == U512::zero() {
+            return (U512::zero(), U512::zero());
+        }
+
+        if self.curve.is_stable() {
+            let probe = (reserve_cspr / U512::from(SPOT_PRICE_PROBE_DIVISOR)).max(U512::one());
+            (probe, self.curve.get_amount_out(probe, reserve_cspr, reserve_token, 0))
+        } else {
+            (reserve_cspr, reserve_token)
         }
+    }
 
-        // Withdraw rewards from auction
-        self.withdraw_staking_rewards();
+    /// Execution details of swapping `amount_in` CSPR (or tokens) at the current reserves
+    fn detailed_quote(&self, amount_in: U512, cspr_in: bool, slippage_bps: U256) -> SwapQuote {
+        if slippage_bps > U256::from(BPS_DENOMINATOR) {
+            self.env().revert(PoolError::InvalidSlippage);
+        }
 
-        // Calculate protocol fee
+        let (reserve_cspr, reserve_token) = self.get_reserves();
+        let (reserve_in, reserve_out) = if cspr_in {
+            (reserve_cspr, reserve_token)
+        } else {
+            (reserve_token, reserve_cspr)
+        };
+        let amount_out = self.get_amount_out(amount_in, reserve_in, reserve_out);
         let config = self.config.get_or_default();
-        let protocol_fee = (rewards * U512::from(config.protocol_fee_bps.as_u64())) / U512::from(10000u64);
-        let rewards_to_pool = rewards - protocol_fee;
+        let fee_paid = math::swap_fee(amount_in, config.swap_fee_bps.as_u64());
 
-        // Send fee to treasury
-        if protocol_fee > U512::zero() {
-            let treasury = self.treasury.get().expect("Treasury not set");
-            self.env().transfer_tokens(&treasury, &protocol_fee);
+        // Reserves as the swap would leave them (the protocol cut leaves the pool)
+        let amount_in_kept = amount_in - self.protocol_fee_cut(amount_in);
+        let (cspr_after, token_after) = if cspr_in {
+            (reserve_cspr + amount_in_kept, reserve_token.saturating_sub(amount_out))
+        } else {
+            (reserve_cspr.saturating_sub(amount_out), reserve_token + amount_in_kept)
+        };
+
+        // Shortfall against the output at the pre-trade marginal price; the fee is
+        // reported separately, so only the input left after it is priced
+        let (spot_cspr, spot_token) = self.spot_ratio(reserve_cspr, reserve_token);
+        let amount_in_after_fee = amount_in - fee_paid;
+        let ideal_out = if spot_cspr.is_zero() || spot_token.is_zero() {
+            U512::zero()
+        } else if cspr_in {
+            (amount_in_after_fee * spot_token) / spot_cspr
+        } else {
+            (amount_in_after_fee * spot_cspr) / spot_token
+        };
+        let price_impact_bps = if ideal_out.is_zero() {
+            0
+        } else {
+            ((ideal_out.saturating_sub(amount_out) * U512::from(BPS_DENOMINATOR)) / ideal_out).as_u64()
+        };
+
+        let slippage_bps = slippage_bps.as_u64();
+        SwapQuote {
+            amount_in,
+            amount_out,
+            fee_paid,
+            spot_price_before: self.spot_price_at(reserve_cspr, reserve_token, PRICE_DECIMALS),
+            spot_price_after: self.spot_price_at(cspr_after, token_after, PRICE_DECIMALS),
+            price_impact_bps,
+            buffer_sufficient: cspr_in || amount_out <= self.buffer_cspr.get_or_default(),
+            min_amount_out: (amount_out * U512::from(BPS_DENOMINATOR - slippage_bps)) / U512::from(BPS_DENOMINATOR),
         }
+    }
 
-        // Add rewards to CSPR reserve (increases LP value)
-        let new_reserve = self.reserve_cspr.get_or_default() + rewards_to_pool;
-        self.reserve_cspr.set(new_reserve);
+    /// Protocol share of the swap fee charged on `amount_in`
+    fn protocol_fee_cut(&self, amount_in: U512) -> U512 {
+        let config = self.config.get_or_default();
+        math::protocol_fee_cut(
+            amount_in,
+            config.swap_fee_bps.as_u64(),
+            config.protocol_swap_fee_bps.as_u64(),
+        )
+    }
 
-        // Add to buffer, then rebalance
-        let new_buffer = self.buffer_cspr.get_or_default() + rewards_to_pool;
-        self.buffer_cspr.set(new_buffer);
-        self.rebalance_stake();
+    /// Credit the LP share of the swap fee on `amount_in` to the earnings indexes and
+    /// record the swap in the rolling stats
+    fn accrue_swap_fee(&mut self, amount_in: U512, protocol_cut: U512, cspr_in: bool) {
+        let swap_fee_bps = self.config.get_or_default().swap_fee_bps.as_u64();
+        let lp_fee = math::swap_fee(amount_in, swap_fee_bps).saturating_sub(protocol_cut);
+        let (fees_cspr, fees_token) = if cspr_in { (lp_fee, U512::zero()) } else { (U512::zero(), lp_fee) };
 
-        self.env().emit_event(Compounded {
-            rewards_harvested: rewards,
-            protocol_fee,
-            rewards_to_pool,
-        });
+        let total_lp = self.lp_token.total_supply();
+        self.lp_token.earnings.accrue_fees(fees_cspr, fees_token, total_lp);
+        self.ledger.record_swap(cspr_in, amount_in, lp_fee);
+    }
 
-        rewards_to_pool
+    /// Actual CSPR held by the pool as (purse balance, delegated amount)
+    ///
+    /// Native builds without a simulator skip the auction, so delegated and unbonding
+    /// CSPR never leaves the purse there; it is subtracted to keep the report comparable.
+    fn actual_cspr_balances(&self) -> (U512, U512) {
+        let purse = self.env().self_balance();
+        let delegated = self.current_delegated();
+
+        if self.staking.holds_stake_in_purse() {
+            let held_elsewhere = delegated + self.in_flight_unbonding();
+            return (purse.saturating_sub(held_elsewhere), delegated);
+        }
+
+        (purse, delegated)
     }
 
-    // ============ VIEW FUNCTIONS ============
+    /// CSPR in the purse that belongs to withdrawals, the treasury and uncredited
+    /// rewards, not the buffer
+    fn cspr_liabilities_in_purse(&self) -> U512 {
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+        let fees_cspr = self.ledger.protocol_fees_cspr.get_or_default();
+        let harvested = self.ledger.harvested_rewards.get_or_default();
+        (pending + fees_cspr + harvested).saturating_sub(self.in_flight_unbonding())
+    }
 
-    /// Get current reserves
-    pub fn get_reserves(&self) -> (U512, U512) {
-        (
-            self.reserve_cspr.get_or_default(),
-            self.reserve_token.get_or_default(),
-        )
+    /// Undelegated CSPR still on its way back from the auction
+    fn in_flight_unbonding(&self) -> U512 {
+        if self.env().get_block_time() >= self.queue.unbonding_release_time.get_or_default() {
+            U512::zero()
+        } else {
+            self.queue.unbonding_cspr.get_or_default()
+        }
     }
 
-    /// Get staking info (staked, buffer)
-    pub fn get_staking_info(&self) -> (U512, U512) {
-        (
-            self.staked_cspr.get_or_default(),
-            self.buffer_cspr.get_or_default(),
-        )
+    /// Revert while undelegated CSPR has not returned, then clear the bucket
+    fn require_settled_unbonding(&mut self) {
+        self.staking.settle();
+        if self.in_flight_unbonding() > U512::zero() {
+            self.env().revert(PoolError::UnbondingInProgress);
+        }
+        self.queue.unbonding_cspr.set(U512::zero());
     }
 
-    /// Quote CSPR to token swap
-    pub fn quote_cspr_for_token(&self, cspr_in: U512) -> U512 {
-        let (reserve_cspr, reserve_token) = self.get_reserves();
-        self.get_amount_out(cspr_in, reserve_cspr, reserve_token)
+    /// Store a new withdrawal request and index it under its user
+    fn queue_withdrawal(
+        &mut self,
+        user: Address,
+        lp_burned: U512,
+        cspr_amount: U512,
+        token_amount: U512,
+    ) -> u64 {
+        let withdrawal_id = self.queue.withdrawal_counter.get_or_default();
+        self.queue.withdrawal_counter.set(withdrawal_id + 1);
+
+        let now = self.env().get_block_time();
+        let claimable = now + UNBONDING_PERIOD_MS;
+
+        let request = WithdrawalRequest {
+            id: withdrawal_id,
+            user,
+            lp_burned,
+            cspr_amount,
+            token_amount,
+            request_time: now,
+            claimable_time: claimable,
+            claimed: false,
+            cancelled: false,
+            payout_index: self.payout_index(),
+        };
+
+        self.queue.withdrawals.set(&withdrawal_id, request);
+
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+        self.queue.pending_withdrawal_cspr.set(pending + cspr_amount);
+        let pending_count = self.queue.pending_withdrawal_count.get_or_default();
+        self.queue.pending_withdrawal_count.set(pending_count + 1);
+        let user_pending = self.queue.user_pending_count.get(&user).unwrap_or_default();
+        self.queue.user_pending_count.set(&user, user_pending + 1);
+
+        self.push_user_withdrawal(user, withdrawal_id);
+
+        withdrawal_id
     }
 
-    /// Quote token to CSPR swap
-    pub fn quote_token_for_cspr(&self, token_in: U512) -> U512 {
-        let (reserve_cspr, reserve_token) = self.get_reserves();
-        self.get_amount_out(token_in, reserve_token, reserve_cspr)
+    /// Mark a matured request claimed and return its payout (transfer left to the caller)
+    fn settle_withdrawal(&mut self, mut request: WithdrawalRequest) -> U512 {
+        request.claimed = true;
+        self.queue.withdrawals.set(&request.id, request.clone());
+
+        let payout = self.withdrawal_payout(&request);
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+        self.queue.pending_withdrawal_cspr.set(pending.saturating_sub(payout));
+        self.close_withdrawal(&request);
+
+        self.env().emit_event(WithdrawalClaimed {
+            user: request.user,
+            withdrawal_id: request.id,
+            cspr_amount: payout,
+        });
+
+        payout
     }
 
-    /// Get LP token value in underlying assets
-    pub fn get_lp_value(&self, lp_amount: U512) -> (U512, U512) {
-        let total_lp = self.lp_token.total_supply();
-        if total_lp == U512::zero() {
-            return (U512::zero(), U512::zero());
+    /// Update queue counters once a request stops being pending
+    fn close_withdrawal(&mut self, request: &WithdrawalRequest) {
+        self.queue.withdrawal_approvals.set(&request.id, None);
+        let pending_count = self.queue.pending_withdrawal_count.get_or_default();
+        self.queue.pending_withdrawal_count.set(pending_count.saturating_sub(1));
+        let user_pending = self.queue.user_pending_count.get(&request.user).unwrap_or_default();
+        self.queue.user_pending_count.set(&request.user, user_pending.saturating_sub(1));
+
+        // Move the queue head past requests that are no longer open
+        let counter = self.queue.withdrawal_counter.get_or_default();
+        let mut head = self.queue.queue_head.get_or_default();
+        let mut scanned = 0;
+        while head < counter && scanned < MAX_QUEUE_SCAN {
+            if self.queue.withdrawals.get(&head).is_some_and(|next| next.is_open()) {
+                break;
+            }
+            head += 1;
+            scanned += 1;
         }
+        self.queue.queue_head.set(head);
+    }
 
-        let reserve_cspr = self.reserve_cspr.get_or_default();
-        let reserve_token = self.reserve_token.get_or_default();
+    /// Append a withdrawal ID to a user's list
+    fn push_user_withdrawal(&mut self, user: Address, withdrawal_id: u64) {
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        self.queue.user_withdrawal_ids.set(&(user, count), withdrawal_id);
+        self.queue.withdrawal_position.set(&withdrawal_id, count);
+        self.queue.user_withdrawal_count.set(&user, count + 1);
+    }
 
-        let cspr_value = (lp_amount * reserve_cspr) / total_lp;
-        let token_value = (lp_amount * reserve_token) / total_lp;
+    /// Remove a withdrawal ID from a user's list (the last entry takes its place)
+    fn remove_user_withdrawal(&mut self, user: Address, withdrawal_id: u64) {
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        let position = self.queue.withdrawal_position.get(&withdrawal_id).unwrap_or_default();
+        let last = count - 1;
 
-        (cspr_value, token_value)
+        if position != last {
+            let last_id = self.user_withdrawal_id(user, last);
+            self.queue.user_withdrawal_ids.set(&(user, position), last_id);
+            self.queue.withdrawal_position.set(&last_id, position);
+        }
+        self.queue.user_withdrawal_count.set(&user, last);
     }
 
-    /// Get user's withdrawal requests
-    pub fn get_user_withdrawals(&self, user: Address) -> Vec<WithdrawalRequest> {
-        let ids = self.user_withdrawals.get(&user).unwrap_or_default();
-        ids.iter()
-            .filter_map(|id| self.withdrawals.get(id))
-            .collect()
+    /// Withdrawal ID at a position in a user's list
+    fn user_withdrawal_id(&self, user: Address, position: u32) -> u64 {
+        self.queue.user_withdrawal_ids.get(&(user, position)).unwrap_or_default()
     }
 
-    /// Get LP token address (returns pool address as LP token is a submodule)
-    pub fn lp_token_address(&self) -> Address {
-        self.env().self_address()
+    /// Withdrawal request at a position in a user's list
+    fn user_withdrawal_at(&self, user: Address, position: u32) -> Option<WithdrawalRequest> {
+        self.queue.withdrawals.get(&self.user_withdrawal_id(user, position))
     }
 
-    /// Get paired token address
-    pub fn token_address(&self) -> Address {
-        self.token_address.get().expect("Token not set")
+    /// End of a page of a user's list, capped by list length and page size
+    fn page_end(&self, user: Address, offset: u32, limit: u32) -> u32 {
+        let count = self.queue.user_withdrawal_count.get(&user).unwrap_or_default();
+        let end = offset.saturating_add(limit.min(MAX_WITHDRAWAL_PAGE_SIZE));
+        end.min(count)
     }
 
-    /// Get LP token balance for an address
-    pub fn lp_balance_of(&self, owner: &Address) -> U512 {
-        self.lp_token.balance_of(owner)
+    /// Status of a withdrawal request at the current block time
+    fn withdrawal_status(&self, request: &WithdrawalRequest) -> WithdrawalStatus {
+        if request.claimed {
+            WithdrawalStatus::Claimed
+        } else if request.cancelled {
+            WithdrawalStatus::Cancelled
+        } else if self.env().get_block_time() < request.claimable_time {
+            WithdrawalStatus::Unbonding
+        } else {
+            WithdrawalStatus::Claimable
+        }
     }
 
-    /// Get LP token total supply
-    pub fn lp_total_supply(&self) -> U512 {
-        self.lp_token.total_supply()
+    /// Current withdrawal payout index
+    fn payout_index(&self) -> U512 {
+        let index = self.queue.withdrawal_payout_index.get_or_default();
+        if index == U512::zero() {
+            U512::from(PAYOUT_INDEX_SCALE)
+        } else {
+            index
+        }
     }
 
-    /// Get a specific withdrawal request by ID
-    pub fn get_withdrawal(&self, withdrawal_id: u64) -> WithdrawalRequest {
-        self.withdrawals.get(&withdrawal_id)
-            .unwrap_or_else(|| self.env().revert(PoolError::WithdrawalNotFound))
+    /// CSPR a withdrawal request pays out after losses realized since it was queued
+    fn withdrawal_payout(&self, request: &WithdrawalRequest) -> U512 {
+        if request.payout_index == U512::zero() {
+            return request.cspr_amount;
+        }
+        (request.cspr_amount * self.payout_index()) / request.payout_index
     }
 
-    // ============ INTERNAL FUNCTIONS ============
-
-    /// Constant product formula with fee
-    fn get_amount_out(&self, amount_in: U512, reserve_in: U512, reserve_out: U512) -> U512 {
-        if amount_in == U512::zero() || reserve_in == U512::zero() || reserve_out == U512::zero() {
+    /// Write down a delegation shortfall, sharing it between LPs and the withdrawal queue
+    fn realize_staking_loss(&mut self) -> U512 {
+        let staked = self.staked_cspr.get_or_default();
+        let delegated = self.current_delegated();
+        if delegated >= staked {
             return U512::zero();
         }
 
-        let config = self.config.get_or_default();
+        let shortfall = staked - delegated;
+        let reserve_cspr = self.reserve_cspr.get_or_default();
+        let pending = self.queue.pending_withdrawal_cspr.get_or_default();
+
+        // Split pro rata between the reserve and CSPR still owed to the queue
+        let exposed = reserve_cspr + pending;
+        let queue_loss = (shortfall * pending) / exposed;
+        let lp_loss = shortfall - queue_loss;
+
+        // reserve = staked + buffer: the queue's share stays in the pool as buffer
+        self.staked_cspr.set(delegated);
+        self.reserve_cspr.set(reserve_cspr - lp_loss);
+        let buffer = self.buffer_cspr.get_or_default();
+        self.buffer_cspr.set(buffer + queue_loss);
+
+        let mut index = self.payout_index();
+        if queue_loss > U512::zero() {
+            index = (index * (pending - queue_loss)) / pending;
+            self.queue.withdrawal_payout_index.set(index);
+            self.queue.pending_withdrawal_cspr.set(pending - queue_loss);
+        }
 
-        // amount_in_with_fee = amount_in * (10000 - fee) / 10000
-        let fee_multiplier = U512::from(10000u64) - U512::from(config.swap_fee_bps.as_u64());
-        let amount_in_with_fee = (amount_in * fee_multiplier) / U512::from(10000u64);
+        self.env().emit_event(LossRealized {
+            shortfall,
+            lp_loss,
+            queue_loss,
+            payout_index: index,
+        });
 
-        // output = (amount_in_with_fee * reserve_out) / (reserve_in + amount_in_with_fee)
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = reserve_in + amount_in_with_fee;
+        shortfall
+    }
 
-        numerator / denominator
+    /// Revert unless the caller is the admin
+    fn require_admin(&self) {
+        let admin = self.admin.get().expect("Admin not set");
+        if self.env().caller() != admin {
+            self.env().revert(PoolError::NotAdmin);
+        }
     }
 
     /// Rebalance between staked and buffer
@@ -554,6 +2150,12 @@ impl GhostPoolPool {
         if current_buffer > target_buffer {
             // Buffer too high, stake excess
             let excess = current_buffer - target_buffer;
+
+            // The auction rejects delegations below its minimum; keep it buffered until then
+            if current_staked + excess < self.staking.minimum_delegation() {
+                return;
+            }
+
             self.delegate_to_validator(excess);
             self.buffer_cspr.set(target_buffer);
             self.staked_cspr.set(current_staked + excess);
@@ -582,59 +2184,46 @@ impl GhostPoolPool {
     }
 
     // ============ SYSTEM AUCTION CALLS ============
-    // Casper 2.0 System Auction integration using Odra ContractEnv
+    // Casper 2.0 System Auction integration through the staking backend
 
     /// Delegate CSPR to the configured validator via System Auction
-    fn delegate_to_validator(&self, amount: U512) {
+    fn delegate_to_validator(&mut self, amount: U512) {
         if amount == U512::zero() {
             return;
         }
 
-        let validator = self.validator.get().expect("Validator not set");
-
-        // Use Odra's built-in delegate method which calls the System Auction
-        // Note: Only call delegate in WASM (deployment). Native builds (tests) skip this
-        // because OdraVM doesn't support delegation.
-        #[cfg(target_arch = "wasm32")]
-        self.env().delegate(validator, amount);
-        #[cfg(not(target_arch = "wasm32"))]
-        let _ = validator; // silence unused warning in native/test mode
+        self.staking.delegate(amount);
 
         self.env().emit_event(Delegated { amount });
     }
 
     /// Undelegate CSPR from the validator (initiates 14h unbonding period)
-    fn undelegate_from_validator(&self, amount: U512) {
+    fn undelegate_from_validator(&mut self, amount: U512) {
         if amount == U512::zero() {
             return;
         }
 
-        let validator = self.validator.get().expect("Validator not set");
+        // Track the CSPR in flight until the unbonding period ends
+        let unbonding = self.in_flight_unbonding();
+        let release_time = self.env().get_block_time() + UNBONDING_PERIOD_MS;
+        self.queue.unbonding_cspr.set(unbonding + amount);
+        self.queue.unbonding_release_time.set(release_time);
 
-        // Use Odra's built-in undelegate method which calls the System Auction
-        // Note: Only call undelegate in WASM (deployment). Native builds (tests) skip this.
-        #[cfg(target_arch = "wasm32")]
-        self.env().undelegate(validator, amount);
-        #[cfg(not(target_arch = "wasm32"))]
-        let _ = validator; // silence unused warning in native/test mode
+        self.staking.undelegate(amount);
 
         self.env().emit_event(Undelegated { amount });
     }
 
+    /// Get current total delegated amount from the System Auction
+    fn current_delegated(&self) -> U512 {
+        self.staking.delegated_amount()
+    }
+
     /// Get pending staking rewards (difference between current delegated amount and tracked staked amount)
-    /// In Casper 2.0, rewards are auto-compounded into the delegated amount
+    /// In Casper 2.0, rewards are auto-compounded into the delegated amount.
+    /// Shortfalls are not rewards; they are handled by `realize_staking_loss`.
     fn get_pending_rewards(&self) -> U512 {
-        let validator = self.validator.get().expect("Validator not set");
-
-        // Query current total delegated amount from the System Auction
-        // Note: Only query in WASM (deployment). Native builds (tests) return tracked amount.
-        #[cfg(target_arch = "wasm32")]
-        let current_delegated = self.env().delegated_amount(validator);
-        #[cfg(not(target_arch = "wasm32"))]
-        let current_delegated = {
-            let _ = validator; // silence unused warning in native/test mode
-            self.staked_cspr.get_or_default() // In tests, return tracked amount (no rewards)
-        };
+        let current_delegated = self.current_delegated();
         let tracked_staked = self.staked_cspr.get_or_default();
 
         // Rewards = current delegated amount - what we originally staked
@@ -645,9 +2234,50 @@ impl GhostPoolPool {
         }
     }
 
+    /// Credit rewards harvested by `compound` once their unbonding has settled
+    /// Returns the share added to the CSPR reserve (zero while still unbonding).
+    fn credit_harvested_rewards(&mut self) -> U512 {
+        let rewards = self.ledger.harvested_rewards.get_or_default();
+        if rewards == U512::zero() || self.in_flight_unbonding() > U512::zero() {
+            return U512::zero();
+        }
+        self.ledger.harvested_rewards.set(U512::zero());
+
+        // Calculate protocol fee
+        let config = self.config.get_or_default();
+        let protocol_fee = (rewards * U512::from(config.protocol_fee_bps.as_u64())) / U512::from(10000u64);
+        let rewards_to_pool = rewards - protocol_fee;
+
+        // Send fee to treasury
+        if protocol_fee > U512::zero() {
+            let treasury = self.treasury.get().expect("Treasury not set");
+            self.env().transfer_tokens(&treasury, &protocol_fee);
+        }
+
+        // Add rewards to CSPR reserve (increases LP value)
+        let new_reserve = self.reserve_cspr.get_or_default() + rewards_to_pool;
+        self.reserve_cspr.set(new_reserve);
+        let total_lp = self.lp_token.total_supply();
+        self.lp_token.earnings.accrue_staking_rewards(rewards_to_pool, total_lp);
+        self.ledger.record_staking_rewards(rewards_to_pool);
+
+        // Add to buffer, then rebalance
+        let new_buffer = self.buffer_cspr.get_or_default() + rewards_to_pool;
+        self.buffer_cspr.set(new_buffer);
+        self.rebalance_stake();
+
+        self.env().emit_event(Compounded {
+            rewards_harvested: rewards,
+            protocol_fee,
+            rewards_to_pool,
+        });
+
+        rewards_to_pool
+    }
+
     /// Withdraw staking rewards by undelegating the reward portion
     /// Note: This initiates unbonding - rewards become available after 14h
-    fn withdraw_staking_rewards(&self) {
+    fn withdraw_staking_rewards(&mut self) {
         let rewards = self.get_pending_rewards();
 
         if rewards == U512::zero() {
@@ -655,48 +2285,65 @@ impl GhostPoolPool {
         }
 
         // Undelegate the rewards portion from the validator
-        // Note: Only call undelegate in WASM (deployment). Native builds (tests) skip this.
-        #[cfg(target_arch = "wasm32")]
-        {
-            let validator = self.validator.get().expect("Validator not set");
-            self.env().undelegate(validator, rewards);
+        self.undelegate_from_validator(rewards);
+    }
+
+    /// Send CSPR to an account, or to a contract through its payable `receive_cspr`
+    fn pay_cspr(&self, to: &Address, amount: U512) {
+        if to.is_contract() {
+            CsprReceiverContractRef::new(self.env(), *to)
+                .with_tokens(amount)
+                .receive_cspr();
+        } else {
+            self.env().transfer_tokens(to, &amount);
         }
     }
 
     // ============ TOKEN HELPERS ============
 
-    fn transfer_token(&self, to: &Address, amount: U512) {
-        let token_address = self.token_address.get().expect("Token not set");
-        let amount_u256 = U256::from(amount.as_u128());
-        // Call CEP-18 transfer via external contract reference
-        Cep18TokenContractRef::new(self.env(), token_address).transfer(to, &amount_u256);
+    fn token_balance(&self) -> U512 {
+        self.token_balance_of(&self.env().self_address())
     }
 
-    fn transfer_token_from(&self, from: &Address, to: &Address, amount: U512) {
-        let token_address = self.token_address.get().expect("Token not set");
-        let amount_u256 = U256::from(amount.as_u128());
-        // Call CEP-18 transfer_from via external contract reference
-        Cep18TokenContractRef::new(self.env(), token_address).transfer_from(from, to, &amount_u256);
+    fn token_balance_of(&self, owner: &Address) -> U512 {
+        self.token.balance_of(owner)
     }
 
-    /// Integer square root (Babylonian method)
-    fn sqrt(&self, n: U512) -> U512 {
-        if n == U512::zero() {
-            return U512::zero();
-        }
+    fn token_allowance(&self, owner: &Address, spender: &Address) -> U512 {
+        self.token.allowance(owner, spender)
+    }
 
-        let mut x = n;
-        let mut y = (x + U512::one()) / 2;
+    fn transfer_token(&self, to: &Address, amount: U512) {
+        self.token.transfer(to, amount);
+    }
 
-        while y < x {
-            x = y;
-            y = (x + n / x) / 2;
+    /// Pull tokens from `from`, returning the amount to credit under the accounting mode
+    fn pull_token(&self, from: &Address, amount: U512) -> U512 {
+        let received = self.token.pull(from, amount);
+        if received == U512::zero() {
+            self.env().revert(PoolError::ZeroTokenAmount);
         }
-
-        x
+        received
     }
 }
 
+/// Amounts for a single-sided deposit, in the deposited asset ("in") and its pair ("out")
+#[derive(Default)]
+struct ZapPlan {
+    /// Deposited amount swapped for the pair asset
+    swap_amount: U512,
+    /// Pair asset received from the swap
+    swap_out: U512,
+    /// Protocol share of the swap fee (leaves the reserves)
+    protocol_cut: U512,
+    /// Deposited asset added as liquidity
+    in_used: U512,
+    /// Pair asset added as liquidity
+    out_used: U512,
+    /// LP tokens minted
+    lp_minted: U512,
+}
+
 /// Pool errors
 #[odra::odra_error]
 pub enum PoolError {
@@ -728,4 +2375,32 @@ pub enum PoolError {
     InsufficientLiquidity = 13,
     /// Insufficient buffer for swap
     InsufficientBuffer = 14,
+    /// Caller is not the admin
+    NotAdmin = 15,
+    /// Fee parameter out of range
+    InvalidFee = 16,
+    /// Undelegated CSPR has not returned from the auction yet
+    UnbondingInProgress = 17,
+    /// Operation requires no active stake
+    StakeActive = 18,
+    /// Caller is neither owner nor approved for the withdrawal
+    NotApproved = 19,
+    /// Withdrawal was cancelled
+    WithdrawalCancelled = 20,
+    /// Owner cannot supply any of the token side to cancel
+    InsufficientTokenForCancel = 21,
+    /// Operation requires an empty pool
+    LiquidityPresent = 22,
+    /// Liquid staking token uses a different validator (or the route is locked in)
+    ValidatorMismatch = 23,
+    /// Pool does not stake through a liquid staking token
+    LiquidStakingDisabled = 24,
+    /// Requested price precision exceeds MAX_PRICE_DECIMALS
+    InvalidDecimals = 25,
+    /// Slippage tolerance above 100%
+    InvalidSlippage = 26,
+    /// Withdrawal has no token side to re-enter the pool with
+    CsprOnlyWithdrawal = 27,
+    /// Paired token decimals differ from CSPR's
+    DecimalsMismatch = 28,
 }
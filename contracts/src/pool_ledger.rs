@@ -0,0 +1,96 @@
+//! Pool ledger - protocol fee accruals and rolling activity stats
+
+use odra::prelude::*;
+use odra::casper_types::U512;
+
+use crate::types::{DailyStats, DAY_MS, STATS_WINDOW_DAYS};
+
+/// Fee and reward bookkeeping of the pool
+///
+/// Holds the protocol fee accruals (read and written by the pool directly) and a ring of
+/// STATS_WINDOW_DAYS daily buckets of swap volume, LP fees and compounded rewards. A
+/// bucket is reset when its slot is reused for a new day.
+#[odra::module]
+pub struct PoolLedger {
+    /// CSPR swap fees accrued to the treasury (held outside reserves)
+    pub(crate) protocol_fees_cspr: Var<U512>,
+    /// Token swap fees accrued to the treasury (held outside reserves)
+    pub(crate) protocol_fees_token: Var<U512>,
+    /// Staking rewards undelegated by `compound` and not yet credited (still unbonding)
+    pub(crate) harvested_rewards: Var<U512>,
+    /// Daily buckets by day % STATS_WINDOW_DAYS
+    days: Mapping<u64, DailyStats>,
+    /// Time of the first deposit or recorded activity
+    stats_start: Var<u64>,
+}
+
+#[odra::module]
+impl PoolLedger {
+    /// Start the stats window at the pool's first deposit
+    pub fn start(&mut self) {
+        if self.stats_start.get().is_none() {
+            self.stats_start.set(self.env().get_block_time());
+        }
+    }
+
+    /// Record a swap of `amount_in` leaving `lp_fee` to LPs
+    pub fn record_swap(&mut self, cspr_in: bool, amount_in: U512, lp_fee: U512) {
+        let mut bucket = self.current_bucket();
+        if cspr_in {
+            bucket.volume_cspr += amount_in;
+            bucket.fees_cspr += lp_fee;
+        } else {
+            bucket.volume_token += amount_in;
+            bucket.fees_token += lp_fee;
+        }
+        self.days.set(&(bucket.day % STATS_WINDOW_DAYS), bucket);
+    }
+
+    /// Record staking rewards compounded into the reserve
+    pub fn record_staking_rewards(&mut self, rewards: U512) {
+        let mut bucket = self.current_bucket();
+        bucket.staking_rewards += rewards;
+        self.days.set(&(bucket.day % STATS_WINDOW_DAYS), bucket);
+    }
+
+    /// Activity over the window ending now, and the window's start time
+    /// The window starts STATS_WINDOW_DAYS - 1 days before today, or at the first deposit.
+    pub fn window(&self) -> (DailyStats, u64) {
+        let now = self.env().get_block_time();
+        let today = now / DAY_MS;
+        let first_day = today.saturating_sub(STATS_WINDOW_DAYS - 1);
+
+        let mut totals = DailyStats {
+            day: first_day,
+            ..Default::default()
+        };
+        for slot in 0..STATS_WINDOW_DAYS {
+            let bucket = self.days.get(&slot).unwrap_or_default();
+            if bucket.day < first_day || bucket.day > today {
+                continue;
+            }
+            totals.volume_cspr += bucket.volume_cspr;
+            totals.volume_token += bucket.volume_token;
+            totals.fees_cspr += bucket.fees_cspr;
+            totals.fees_token += bucket.fees_token;
+            totals.staking_rewards += bucket.staking_rewards;
+        }
+
+        let window_start = (first_day * DAY_MS).max(self.stats_start.get().unwrap_or(now));
+        (totals, window_start)
+    }
+
+    /// Today's bucket, reset if its slot still holds an older day
+    fn current_bucket(&mut self) -> DailyStats {
+        self.start();
+
+        let day = self.env().get_block_time() / DAY_MS;
+        match self.days.get(&(day % STATS_WINDOW_DAYS)) {
+            Some(bucket) if bucket.day == day => bucket,
+            _ => DailyStats {
+                day,
+                ..Default::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Position NFT - non-fungible ownership of concentrated-liquidity positions
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+
+/// Ownership registry for position IDs
+///
+/// IDs start at 1 and are never reused. Each owner's IDs are enumerable; removing
+/// one moves the owner's last ID into its slot.
+#[odra::module]
+pub struct PositionNft {
+    /// Last ID minted
+    last_id: Var<u64>,
+    /// Owner by position ID (None once burned)
+    owners: Mapping<u64, Option<Address>>,
+    /// Approved spender by position ID
+    approvals: Mapping<u64, Option<Address>>,
+    /// Owner's position IDs by (owner, index)
+    owned_ids: Mapping<(Address, u32), u64>,
+    /// Number of positions per owner
+    owned_count: Mapping<Address, u32>,
+    /// Index of each position ID in its owner's list
+    owned_index: Mapping<u64, u32>,
+}
+
+#[odra::module]
+impl PositionNft {
+    /// Mint the next ID to `owner`
+    pub fn mint(&mut self, owner: Address) -> u64 {
+        let id = self.last_id.get_or_default() + 1;
+        self.last_id.set(id);
+        self.owners.set(&id, Some(owner));
+        self.push(owner, id);
+        id
+    }
+
+    /// Burn a position ID
+    pub fn burn(&mut self, id: u64) {
+        if let Some(owner) = self.owner_of(id) {
+            self.remove(owner, id);
+            self.owners.set(&id, None);
+            self.approvals.set(&id, None);
+        }
+    }
+
+    /// Move a position ID to a new owner, clearing its approval
+    pub fn transfer(&mut self, id: u64, to: Address) {
+        if let Some(from) = self.owner_of(id) {
+            self.remove(from, id);
+            self.push(to, id);
+            self.owners.set(&id, Some(to));
+            self.approvals.set(&id, None);
+        }
+    }
+
+    /// Approve (or with None, revoke) a spender for a position ID
+    pub fn approve(&mut self, id: u64, spender: Option<Address>) {
+        self.approvals.set(&id, spender);
+    }
+
+    /// Owner of a live position ID
+    pub fn owner_of(&self, id: u64) -> Option<Address> {
+        self.owners.get(&id).flatten()
+    }
+
+    /// Approved spender of a position ID
+    pub fn approved(&self, id: u64) -> Option<Address> {
+        self.approvals.get(&id).flatten()
+    }
+
+    /// Number of positions held by `owner`
+    pub fn balance_of(&self, owner: Address) -> u32 {
+        self.owned_count.get(&owner).unwrap_or_default()
+    }
+
+    /// Page of position IDs held by `owner`
+    pub fn tokens_of(&self, owner: Address, offset: u32, limit: u32) -> Vec<u64> {
+        let end = offset.saturating_add(limit).min(self.balance_of(owner));
+        (offset..end)
+            .map(|index| self.owned_ids.get(&(owner, index)).unwrap_or_default())
+            .collect()
+    }
+
+    /// Number of position IDs minted so far
+    pub fn total_minted(&self) -> u64 {
+        self.last_id.get_or_default()
+    }
+
+    fn push(&mut self, owner: Address, id: u64) {
+        let count = self.balance_of(owner);
+        self.owned_ids.set(&(owner, count), id);
+        self.owned_index.set(&id, count);
+        self.owned_count.set(&owner, count + 1);
+    }
+
+    fn remove(&mut self, owner: Address, id: u64) {
+        let last = self.balance_of(owner) - 1;
+        let index = self.owned_index.get(&id).unwrap_or_default();
+
+        if index != last {
+            let last_id = self.owned_ids.get(&(owner, last)).unwrap_or_default();
+            self.owned_ids.set(&(owner, index), last_id);
+            self.owned_index.set(&last_id, index);
+        }
+        self.owned_count.set(&owner, last);
+    }
+}
@@ -0,0 +1,127 @@
+//! Reentrant Token - Malicious CEP-18 token for testing the pools' reentrancy guards
+//!
+//! Behaves like `TestToken` until armed with a target pool, after which every
+//! `transfer` / `transfer_from` calls back into that pool before moving funds.
+//!
+//! Only compiled natively or with the `test-mode` feature; never part of a production build.
+
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{U256, U512};
+use odra_modules::cep18_token::Cep18;
+
+use crate::pool::GhostPoolPoolContractRef;
+
+/// Pool entry point the token re-enters during a transfer
+#[odra::odra_type]
+pub enum ReentryCall {
+    /// `swap_token_for_cspr(1, 0)`
+    SwapTokenForCspr,
+    /// `remove_liquidity(1, 0, 0)`
+    RemoveLiquidity,
+    /// `compound()`
+    Compound,
+}
+
+/// CEP-18 token that re-enters a pool from inside its own transfers
+#[odra::module]
+pub struct ReentrantToken {
+    /// Underlying CEP-18 token
+    cep18: SubModule<Cep18>,
+    /// Pool to attack, if armed
+    target: Var<Option<Address>>,
+    /// Entry point called on the target
+    call: Var<ReentryCall>,
+}
+
+#[odra::module]
+impl ReentrantToken {
+    /// Initialize the token with initial supply to deployer (minted by `Cep18::init`); starts disarmed
+    pub fn init(&mut self, name: String, symbol: String, decimals: u8, initial_supply: U256) {
+        self.cep18.init(symbol, name, decimals, initial_supply);
+        self.target.set(None);
+    }
+
+    /// Re-enter `target` with `call` on every subsequent transfer
+    pub fn arm(&mut self, target: Address, call: ReentryCall) {
+        self.target.set(Some(target));
+        self.call.set(call);
+    }
+
+    /// Stop re-entering
+    pub fn disarm(&mut self) {
+        self.target.set(None);
+    }
+
+    /// Mint tokens (for testing)
+    pub fn mint(&mut self, to: &Address, amount: &U256) {
+        self.cep18.raw_mint(to, amount);
+    }
+
+    /// Get total supply
+    pub fn total_supply(&self) -> U256 {
+        self.cep18.total_supply()
+    }
+
+    /// Get balance of address
+    pub fn balance_of(&self, address: &Address) -> U256 {
+        self.cep18.balance_of(address)
+    }
+
+    /// Transfer tokens, re-entering the target first if armed
+    pub fn transfer(&mut self, recipient: &Address, amount: &U256) {
+        self.reenter();
+        self.cep18.transfer(recipient, amount);
+    }
+
+    /// Transfer from, re-entering the target first if armed
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.reenter();
+        self.cep18.transfer_from(owner, recipient, amount);
+    }
+
+    /// Approve spender
+    pub fn approve(&mut self, spender: &Address, amount: &U256) {
+        self.cep18.approve(spender, amount);
+    }
+
+    /// Get allowance
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        self.cep18.allowance(owner, spender)
+    }
+
+    /// Get token name
+    pub fn name(&self) -> String {
+        self.cep18.name()
+    }
+
+    /// Get token symbol
+    pub fn symbol(&self) -> String {
+        self.cep18.symbol()
+    }
+
+    /// Get token decimals
+    pub fn decimals(&self) -> u8 {
+        self.cep18.decimals()
+    }
+}
+
+impl ReentrantToken {
+    fn reenter(&mut self) {
+        let (Some(target), Some(call)) = (self.target.get().flatten(), self.call.get()) else {
+            return;
+        };
+        let mut pool = GhostPoolPoolContractRef::new(self.env(), target);
+        match call {
+            ReentryCall::SwapTokenForCspr => {
+                pool.swap_token_for_cspr(U512::one(), U512::zero());
+            }
+            ReentryCall::RemoveLiquidity => {
+                pool.remove_liquidity(U512::one(), U512::zero(), U512::zero());
+            }
+            ReentryCall::Compound => {
+                pool.compound();
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+//! Ghost Pool Router - atomic multi-hop swaps across factory pools and pairs
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{U256, U512};
+
+use crate::factory::GhostPoolFactoryContractRef;
+use crate::pair::GhostPoolPairContractRef;
+use crate::pool::{Cep18TokenContractRef, CsprReceiverContractRef, GhostPoolPoolContractRef};
+
+/// Router chaining swaps through the pools and pairs registered in a factory
+///
+/// A path lists every asset a swap passes through, with `None` standing for CSPR.
+/// Each hop is one pool or pair: a hop touching CSPR uses the token's staked-CSPR pool,
+/// a hop between two tokens uses their pair. Token A to token B is `[Some(A), Some(B)]`
+/// through a pair, or `[Some(A), None, Some(B)]` through CSPR.
+/// `amounts` returned by swaps and quotes line up with the path.
+///
+/// CSPR only moves between contracts as attached value: the router pays pools through
+/// their payable swaps, and pools pay the router through `receive_cspr`.
+#[odra::module]
+pub struct GhostPoolRouter {
+    /// Factory the pools are looked up in
+    factory: Var<Address>,
+}
+
+#[odra::module]
+impl GhostPoolRouter {
+    /// Initialize the router
+    #[odra(init)]
+    pub fn init(&mut self, factory: Address) {
+        self.factory.set(factory);
+    }
+
+    // ============ EXACT INPUT ============
+
+    /// Swap an exact amount of tokens along `path` for as many tokens as possible
+    pub fn swap_exact_tokens_for_tokens(
+        &mut self,
+        amount_in: U512,
+        amount_out_min: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, false, false);
+
+        let amounts = self.get_amounts_out(amount_in, path.clone());
+        self.require_min_out(&amounts, amount_out_min);
+
+        self.pull_input(&path, amounts[0]);
+        self.swap_along(&amounts, &path, to);
+        amounts
+    }
+
+    /// Swap the attached CSPR along `path` for as many tokens as possible
+    #[odra(payable)]
+    pub fn swap_exact_cspr_for_tokens(
+        &mut self,
+        amount_out_min: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, true, false);
+
+        let amounts = self.get_amounts_out(self.env().attached_value(), path.clone());
+        self.require_min_out(&amounts, amount_out_min);
+
+        self.swap_along(&amounts, &path, to);
+        amounts
+    }
+
+    /// Swap an exact amount of tokens along `path` for as much CSPR as possible
+    pub fn swap_exact_tokens_for_cspr(
+        &mut self,
+        amount_in: U512,
+        amount_out_min: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, false, true);
+
+        let amounts = self.get_amounts_out(amount_in, path.clone());
+        self.require_min_out(&amounts, amount_out_min);
+
+        self.pull_input(&path, amounts[0]);
+        self.swap_along(&amounts, &path, to);
+        amounts
+    }
+
+    // ============ EXACT OUTPUT ============
+
+    /// Swap as few tokens as possible along `path` for exactly `amount_out` tokens
+    pub fn swap_tokens_for_exact_tokens(
+        &mut self,
+        amount_out: U512,
+        amount_in_max: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, false, false);
+
+        let amounts = self.get_amounts_in(amount_out, path.clone());
+        if amounts[0] > amount_in_max {
+            self.env().revert(RouterError::ExcessiveInputAmount);
+        }
+
+        self.pull_input(&path, amounts[0]);
+        self.swap_along(&amounts, &path, to);
+        amounts
+    }
+
+    /// Swap as little of the attached CSPR as possible for exactly `amount_out` tokens
+    /// Unused CSPR is refunded to the caller
+    #[odra(payable)]
+    pub fn swap_cspr_for_exact_tokens(
+        &mut self,
+        amount_out: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, true, false);
+
+        let attached = self.env().attached_value();
+        let amounts = self.get_amounts_in(amount_out, path.clone());
+        if amounts[0] > attached {
+            self.env().revert(RouterError::ExcessiveInputAmount);
+        }
+
+        self.swap_along(&amounts, &path, to);
+
+        let refund = attached - amounts[0];
+        if refund > U512::zero() {
+            self.pay_cspr(&self.env().caller(), refund);
+        }
+        amounts
+    }
+
+    /// Swap as few tokens as possible along `path` for exactly `amount_out` CSPR
+    pub fn swap_tokens_for_exact_cspr(
+        &mut self,
+        amount_out: U512,
+        amount_in_max: U512,
+        path: Vec<Option<Address>>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<U512> {
+        self.require_deadline(deadline);
+        self.require_endpoints(&path, false, true);
+
+        let amounts = self.get_amounts_in(amount_out, path.clone());
+        if amounts[0] > amount_in_max {
+            self.env().revert(RouterError::ExcessiveInputAmount);
+        }
+
+        self.pull_input(&path, amounts[0]);
+        self.swap_along(&amounts, &path, to);
+        amounts
+    }
+
+    /// Accept CSPR paid out by a pool for a token to CSPR hop
+    #[odra(payable)]
+    pub fn receive_cspr(&mut self) {}
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// Quote the amount reached at every step of `path` for an exact input
+    pub fn get_amounts_out(&self, amount_in: U512, path: Vec<Option<Address>>) -> Vec<U512> {
+        self.require_path(&path);
+
+        let mut amounts = Vec::with_capacity(path.len());
+        amounts.push(amount_in);
+        for hop in path.windows(2) {
+            let amount = *amounts.last().unwrap();
+            let out = match self.hop(hop[0], hop[1]) {
+                Hop::CsprToToken(pool) => {
+                    GhostPoolPoolContractRef::new(self.env(), pool).quote_cspr_for_token(amount)
+                }
+                Hop::TokenToCspr(pool) => {
+                    GhostPoolPoolContractRef::new(self.env(), pool).quote_token_for_cspr(amount)
+                }
+                Hop::Pair(pair, token_in, _) => {
+                    GhostPoolPairContractRef::new(self.env(), pair).quote_amount_out(token_in, amount)
+                }
+            };
+            if out == U512::zero() {
+                self.env().revert(RouterError::InsufficientLiquidity);
+            }
+            amounts.push(out);
+        }
+        amounts
+    }
+
+    /// Quote the input needed at every step of `path` for an exact output
+    pub fn get_amounts_in(&self, amount_out: U512, path: Vec<Option<Address>>) -> Vec<U512> {
+        self.require_path(&path);
+
+        let mut amounts = alloc::vec![U512::zero(); path.len()];
+        amounts[path.len() - 1] = amount_out;
+        for i in (1..path.len()).rev() {
+            let amount_in = match self.hop(path[i - 1], path[i]) {
+                Hop::CsprToToken(pool) => {
+                    GhostPoolPoolContractRef::new(self.env(), pool).quote_cspr_for_exact_token(amounts[i])
+                }
+                Hop::TokenToCspr(pool) => {
+                    GhostPoolPoolContractRef::new(self.env(), pool).quote_token_for_exact_cspr(amounts[i])
+                }
+                Hop::Pair(pair, _, token_out) => {
+                    GhostPoolPairContractRef::new(self.env(), pair).quote_amount_in(token_out, amounts[i])
+                }
+            };
+            if amount_in == U512::zero() {
+                self.env().revert(RouterError::InsufficientLiquidity);
+            }
+            amounts[i - 1] = amount_in;
+        }
+        amounts
+    }
+
+    /// Get the factory address
+    pub fn factory(&self) -> Address {
+        self.factory.get().expect("Factory not set")
+    }
+
+    // ============ INTERNAL FUNCTIONS ============
+
+    /// Execute every hop with the quoted amounts as minimum outputs, then pay `to`
+    fn swap_along(&mut self, amounts: &[U512], path: &[Option<Address>], to: Address) {
+        for (i, hop) in path.windows(2).enumerate() {
+            match self.hop(hop[0], hop[1]) {
+                Hop::CsprToToken(pool) => {
+                    GhostPoolPoolContractRef::new(self.env(), pool)
+                        .with_tokens(amounts[i])
+                        .swap_cspr_for_token(amounts[i + 1]);
+                }
+                Hop::TokenToCspr(pool) => {
+                    // The pool pulls the tokens from the router
+                    self.approve(hop[0], pool, amounts[i]);
+                    GhostPoolPoolContractRef::new(self.env(), pool)
+                        .swap_token_for_cspr(amounts[i], amounts[i + 1]);
+                }
+                Hop::Pair(pair, token_in, _) => {
+                    self.approve(hop[0], pair, amounts[i]);
+                    GhostPoolPairContractRef::new(self.env(), pair)
+                        .swap(token_in, amounts[i], amounts[i + 1]);
+                }
+            }
+        }
+
+        let amount_out = amounts[amounts.len() - 1];
+        match path[path.len() - 1] {
+            None => self.pay_cspr(&to, amount_out),
+            Some(token) => {
+                Cep18TokenContractRef::new(self.env(), token).transfer(&to, &Self::to_u256(amount_out));
+            }
+        }
+    }
+
+    /// Send CSPR to an account, or to a contract through its payable `receive_cspr`
+    fn pay_cspr(&self, to: &Address, amount: U512) {
+        if to.is_contract() {
+            CsprReceiverContractRef::new(self.env(), *to)
+                .with_tokens(amount)
+                .receive_cspr();
+        } else {
+            self.env().transfer_tokens(to, &amount);
+        }
+    }
+
+    /// Pull the input tokens from the caller (requires prior approval of the router)
+    fn pull_input(&mut self, path: &[Option<Address>], amount: U512) {
+        if let Some(token) = path[0] {
+            let caller = self.env().caller();
+            let self_address = self.env().self_address();
+            Cep18TokenContractRef::new(self.env(), token)
+                .transfer_from(&caller, &self_address, &Self::to_u256(amount));
+        }
+    }
+
+    /// Approve `spender` to pull `amount` of the router's tokens
+    fn approve(&self, token: Option<Address>, spender: Address, amount: U512) {
+        if let Some(token) = token {
+            Cep18TokenContractRef::new(self.env(), token).approve(&spender, &Self::to_u256(amount));
+        }
+    }
+
+    /// Pool or pair serving a hop
+    fn hop(&self, from: Option<Address>, to: Option<Address>) -> Hop {
+        let factory = GhostPoolFactoryContractRef::new(self.env(), self.factory());
+        let hop = match (from, to) {
+            (None, Some(token)) => factory.get_pool(token).map(Hop::CsprToToken),
+            (Some(token), None) => factory.get_pool(token).map(Hop::TokenToCspr),
+            (Some(token_in), Some(token_out)) => factory
+                .get_pair(token_in, token_out)
+                .map(|pair| Hop::Pair(pair, token_in, token_out)),
+            (None, None) => self.env().revert(RouterError::InvalidPath),
+        };
+        match hop {
+            Some(hop) => hop,
+            None => self.env().revert(RouterError::PoolNotFound),
+        }
+    }
+
+    fn require_path(&self, path: &[Option<Address>]) {
+        if path.len() < 2 {
+            self.env().revert(RouterError::InvalidPath);
+        }
+    }
+
+    /// Check that the path starts and ends in the assets the entry point trades
+    fn require_endpoints(&self, path: &[Option<Address>], cspr_in: bool, cspr_out: bool) {
+        self.require_path(path);
+        if path[0].is_none() != cspr_in || path[path.len() - 1].is_none() != cspr_out {
+            self.env().revert(RouterError::InvalidPath);
+        }
+    }
+
+    fn require_deadline(&self, deadline: u64) {
+        if self.env().get_block_time() > deadline {
+            self.env().revert(RouterError::Expired);
+        }
+    }
+
+    fn require_min_out(&self, amounts: &[U512], amount_out_min: U512) {
+        if amounts[amounts.len() - 1] < amount_out_min {
+            self.env().revert(RouterError::InsufficientOutputAmount);
+        }
+    }
+
+    fn to_u256(amount: U512) -> U256 {
+        U256::from(amount.as_u128())
+    }
+}
+
+/// One step of a path
+enum Hop {
+    /// Buy a token with CSPR in its pool
+    CsprToToken(Address),
+    /// Sell a token for CSPR in its pool
+    TokenToCspr(Address),
+    /// Swap between two tokens in their pair as (pair, token in, token out)
+    Pair(Address, Address, Address),
+}
+
+/// Router errors
+#[odra::odra_error]
+pub enum RouterError {
+    /// Deadline has passed
+    Expired = 1,
+    /// Path is too short, has a CSPR-to-CSPR hop, or has the wrong endpoints
+    InvalidPath = 2,
+    /// No pool or pair registered for a hop in the path
+    PoolNotFound = 3,
+    /// Output below the caller's minimum
+    InsufficientOutputAmount = 4,
+    /// Input above the caller's maximum
+    ExcessiveInputAmount = 5,
+    /// A pool cannot cover the requested amount
+    InsufficientLiquidity = 6,
+}
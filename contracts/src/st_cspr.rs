@@ -0,0 +1,446 @@
+//! stCSPR - Liquid staking token for CSPR delegated to the pool's validator
+
+use odra::prelude::*;
+use odra::casper_types::{PublicKey, U256, U512};
+use odra_modules::cep18_token::Cep18;
+
+use crate::events::*;
+use crate::staking::StakingBackend;
+use crate::types::*;
+
+/// Interface the pool's staking backend uses to stake through stCSPR
+#[odra::external_contract]
+pub trait LiquidStaking {
+    /// Deposit the attached CSPR and mint stCSPR to the caller
+    fn deposit(&mut self) -> U512;
+    /// Burn stCSPR and queue its CSPR for the caller
+    fn request_unstake(&mut self, shares: U512) -> u64;
+    /// Pay out a matured unstake request to its owner
+    fn claim_unstake(&mut self, request_id: u64) -> U512;
+    /// Get an unstake request
+    fn get_unstake_request(&self, request_id: u64) -> UnstakeRequest;
+    /// CSPR value of `shares` stCSPR
+    fn shares_to_cspr(&self, shares: U512) -> U512;
+    /// stCSPR needed to redeem at least `cspr_amount`
+    fn cspr_to_shares_up(&self, cspr_amount: U512) -> U512;
+    /// Get stCSPR balance of an address
+    fn balance_of(&self, owner: &Address) -> U512;
+    /// Transfer stCSPR from owner to recipient (requires prior approval)
+    fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+    /// Get the validator deposits are delegated to
+    fn validator(&self) -> PublicKey;
+}
+
+/// Liquid staking token backed by CSPR delegated to one validator
+///
+/// Deposits mint stCSPR at the current exchange rate and are delegated once the
+/// auction minimum is met. Rewards stay delegated; `compound` recognizes them in the
+/// exchange rate (minting the protocol fee to the treasury as stCSPR), so holders earn
+/// without any unbonding. Redemptions burn stCSPR and pay out after the unbonding period
+/// unless idle CSPR can cover them.
+///
+/// A `GhostPoolPool` pointed at this contract stakes its buffer excess here instead of
+/// delegating itself, and accepts stCSPR as already-staked CSPR liquidity.
+#[odra::module]
+pub struct StakedCspr {
+    /// Underlying CEP-18 token
+    cep18: SubModule<Cep18>,
+    /// Delegation backend (System Auction, or a simulator in tests)
+    staking: SubModule<StakingBackend>,
+
+    // ============ BACKING ============
+    /// CSPR delegated via auction, rewards recognized so far included
+    staked_cspr: Var<U512>,
+    /// Deposited CSPR waiting for the auction minimum
+    idle_cspr: Var<U512>,
+
+    // ============ UNSTAKING ============
+    /// Counter for unstake request IDs
+    unstake_counter: Var<u64>,
+    /// Unstake requests by ID
+    unstake_requests: Mapping<u64, UnstakeRequest>,
+    /// CSPR owed to unclaimed unstake requests
+    pending_unstake_cspr: Var<U512>,
+
+    // ============ CONFIG ============
+    /// Share of recognized rewards minted to the treasury (1000 = 10%)
+    protocol_fee_bps: Var<U256>,
+    /// Treasury address for protocol fees
+    treasury: Var<Address>,
+    /// Admin address
+    admin: Var<Address>,
+}
+
+#[odra::module]
+impl StakedCspr {
+    /// Initialize the token
+    #[odra(init)]
+    pub fn init(&mut self, validator: PublicKey, treasury: Address, admin: Address) {
+        self.cep18.init(
+            String::from("stCSPR"),
+            String::from("Ghost Pool Staked CSPR"),
+            9,
+            U256::zero(),
+        );
+        self.staking.init(validator);
+        self.treasury.set(treasury);
+        self.admin.set(admin);
+
+        self.staked_cspr.set(U512::zero());
+        self.idle_cspr.set(U512::zero());
+        self.protocol_fee_bps.set(U256::from(DEFAULT_PROTOCOL_FEE_BPS));
+    }
+
+    // ============ STAKING ============
+
+    /// Deposit the attached CSPR and mint stCSPR at the current exchange rate
+    #[odra(payable)]
+    pub fn deposit(&mut self) -> U512 {
+        let caller = self.env().caller();
+        let cspr_amount = self.env().attached_value();
+
+        let shares = self.cspr_to_shares(cspr_amount);
+        if shares == U512::zero() {
+            self.env().revert(StakedCsprError::ZeroAmount);
+        }
+
+        self.idle_cspr.set(self.idle_cspr.get_or_default() + cspr_amount);
+        self.mint(&caller, shares);
+        self.delegate_idle();
+
+        self.env().emit_event(StakedCsprMinted {
+            owner: caller,
+            cspr_amount,
+            shares,
+        });
+
+        shares
+    }
+
+    /// Burn stCSPR and queue its CSPR value for the caller
+    /// Idle CSPR is claimable immediately; the rest after the unbonding period.
+    pub fn request_unstake(&mut self, shares: U512) -> u64 {
+        let caller = self.env().caller();
+
+        if shares == U512::zero() {
+            self.env().revert(StakedCsprError::ZeroAmount);
+        }
+        if shares > self.balance_of(&caller) {
+            self.env().revert(StakedCsprError::InsufficientBalance);
+        }
+
+        let cspr_amount = self.shares_to_cspr(shares);
+        self.cep18.raw_burn(&caller, &U256::from(shares.as_u128()));
+
+        // Release idle CSPR first, then undelegate
+        let idle = self.idle_cspr.get_or_default();
+        let now = self.env().get_block_time();
+        let claimable_time = if cspr_amount <= idle {
+            self.idle_cspr.set(idle - cspr_amount);
+            now
+        } else {
+            let from_staked = cspr_amount - idle;
+            self.idle_cspr.set(U512::zero());
+            self.staked_cspr.set(self.staked_cspr.get_or_default() - from_staked);
+            self.staking.undelegate(from_staked);
+            self.env().emit_event(Undelegated { amount: from_staked });
+            now + UNBONDING_PERIOD_MS
+        };
+
+        let request_id = self.unstake_counter.get_or_default() + 1;
+        self.unstake_counter.set(request_id);
+        self.unstake_requests.set(
+            &request_id,
+            UnstakeRequest {
+                id: request_id,
+                owner: caller,
+                cspr_amount,
+                claimable_time,
+                claimed: false,
+            },
+        );
+        self.pending_unstake_cspr.set(self.pending_unstake_cspr.get_or_default() + cspr_amount);
+
+        self.env().emit_event(UnstakeRequested {
+            owner: caller,
+            request_id,
+            shares,
+            cspr_amount,
+            claimable_time,
+        });
+
+        request_id
+    }
+
+    /// Pay out a matured unstake request to its owner (callable by anyone)
+    pub fn claim_unstake(&mut self, request_id: u64) -> U512 {
+        let mut request = self.get_unstake_request(request_id);
+
+        if request.claimed {
+            self.env().revert(StakedCsprError::AlreadyClaimed);
+        }
+        if self.env().get_block_time() < request.claimable_time {
+            self.env().revert(StakedCsprError::StillUnbonding);
+        }
+
+        request.claimed = true;
+        self.unstake_requests.set(&request_id, request.clone());
+        let pending = self.pending_unstake_cspr.get_or_default();
+        self.pending_unstake_cspr.set(pending.saturating_sub(request.cspr_amount));
+
+        self.env().transfer_tokens(&request.owner, &request.cspr_amount);
+
+        self.env().emit_event(UnstakeClaimed {
+            owner: request.owner,
+            request_id,
+            cspr_amount: request.cspr_amount,
+        });
+
+        request.cspr_amount
+    }
+
+    /// Recognize staking rewards (or a shortfall) in the exchange rate
+    ///
+    /// Rewards stay delegated. The protocol share is minted to the treasury as stCSPR
+    /// worth exactly that share, so holders keep the rest. Returns the rewards recognized.
+    pub fn compound(&mut self) -> U512 {
+        let delegated = self.staking.delegated_amount();
+        let staked = self.staked_cspr.get_or_default();
+        self.staked_cspr.set(delegated);
+
+        let (rewards, loss) = if delegated >= staked {
+            (delegated - staked, U512::zero())
+        } else {
+            (U512::zero(), staked - delegated)
+        };
+
+        let mut fee_shares = U512::zero();
+        let supply = self.total_supply();
+        if rewards > U512::zero() && supply > U512::zero() {
+            let fee_bps = self.protocol_fee_bps.get_or_default().as_u64();
+            let fee = (rewards * U512::from(fee_bps)) / U512::from(BPS_DENOMINATOR);
+
+            // Shares worth `fee` after minting: fee * supply / (backing - fee)
+            let backing = self.total_backing();
+            if fee > U512::zero() && backing > fee {
+                fee_shares = (fee * supply) / (backing - fee);
+                let treasury = self.treasury.get().expect("Treasury not set");
+                self.mint(&treasury, fee_shares);
+            }
+        }
+
+        self.env().emit_event(StakedCsprCompounded {
+            rewards,
+            loss,
+            fee_shares,
+            exchange_rate: self.exchange_rate(),
+        });
+
+        rewards
+    }
+
+    // ============ ADMIN FUNCTIONS ============
+
+    /// Route staking to an `AuctionSimulator` contract (admin only, before any stake)
+    /// Reverts in production WASM builds without the `test-mode` feature.
+    pub fn set_auction_simulator(&mut self, simulator: Address) {
+        self.require_admin();
+        if self.staked_cspr.get_or_default() > U512::zero() {
+            self.env().revert(StakedCsprError::StakeActive);
+        }
+        self.staking.set_simulator(simulator);
+    }
+
+    /// Set the share of recognized rewards minted to the treasury (admin only)
+    pub fn set_protocol_fee(&mut self, protocol_fee_bps: U256) {
+        self.require_admin();
+        if protocol_fee_bps > U256::from(BPS_DENOMINATOR) {
+            self.env().revert(StakedCsprError::InvalidFee);
+        }
+        self.protocol_fee_bps.set(protocol_fee_bps);
+    }
+
+    /// Set the treasury receiving protocol fees (admin only)
+    pub fn set_treasury(&mut self, treasury: Address) {
+        self.require_admin();
+        self.treasury.set(treasury);
+
+        self.env().emit_event(TreasuryUpdated { treasury });
+    }
+
+    // ============ VIEW FUNCTIONS ============
+
+    /// CSPR backing stCSPR (delegated + idle, unclaimed redemptions excluded)
+    pub fn total_backing(&self) -> U512 {
+        self.staked_cspr.get_or_default() + self.idle_cspr.get_or_default()
+    }
+
+    /// CSPR value of one stCSPR
+    pub fn exchange_rate(&self) -> U512 {
+        self.shares_to_cspr_at(U512::from(ST_CSPR_UNIT), U512::from(ST_CSPR_UNIT))
+    }
+
+    /// CSPR value of `shares` stCSPR (rounded down)
+    pub fn shares_to_cspr(&self, shares: U512) -> U512 {
+        self.shares_to_cspr_at(shares, U512::zero())
+    }
+
+    /// stCSPR minted for `cspr_amount` (rounded down)
+    pub fn cspr_to_shares(&self, cspr_amount: U512) -> U512 {
+        let supply = self.total_supply();
+        let backing = self.total_backing();
+        if supply == U512::zero() || backing == U512::zero() {
+            cspr_amount
+        } else {
+            (cspr_amount * supply) / backing
+        }
+    }
+
+    /// stCSPR needed to redeem at least `cspr_amount`
+    pub fn cspr_to_shares_up(&self, cspr_amount: U512) -> U512 {
+        let supply = self.total_supply();
+        let backing = self.total_backing();
+        if supply == U512::zero() || backing == U512::zero() {
+            cspr_amount
+        } else {
+            (cspr_amount * supply + backing - U512::one()) / backing
+        }
+    }
+
+    /// Get an unstake request
+    pub fn get_unstake_request(&self, request_id: u64) -> UnstakeRequest {
+        self.unstake_requests
+            .get(&request_id)
+            .unwrap_or_revert_with(&self.env(), StakedCsprError::RequestNotFound)
+    }
+
+    /// Get CSPR owed to unclaimed unstake requests
+    pub fn pending_unstake(&self) -> U512 {
+        self.pending_unstake_cspr.get_or_default()
+    }
+
+    /// Get the validator deposits are delegated to
+    pub fn validator(&self) -> PublicKey {
+        self.staking.validator()
+    }
+
+    /// Get the share of recognized rewards minted to the treasury
+    pub fn protocol_fee_bps(&self) -> U256 {
+        self.protocol_fee_bps.get_or_default()
+    }
+
+    /// Get admin address
+    pub fn admin(&self) -> Address {
+        self.admin.get().expect("Admin not set")
+    }
+
+    /// Get treasury address
+    pub fn treasury(&self) -> Address {
+        self.treasury.get().expect("Treasury not set")
+    }
+
+    // ============ CEP-18 ============
+
+    /// Get total supply
+    pub fn total_supply(&self) -> U512 {
+        U512::from(self.cep18.total_supply().as_u128())
+    }
+
+    /// Get balance of address
+    pub fn balance_of(&self, owner: &Address) -> U512 {
+        U512::from(self.cep18.balance_of(owner).as_u128())
+    }
+
+    /// Transfer tokens (standard CEP-18)
+    pub fn transfer(&mut self, to: &Address, amount: &U256) {
+        self.cep18.transfer(to, amount);
+    }
+
+    /// Transfer from (standard CEP-18)
+    pub fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256) {
+        self.cep18.transfer_from(owner, recipient, amount);
+    }
+
+    /// Approve spender (standard CEP-18)
+    pub fn approve(&mut self, spender: &Address, amount: &U256) {
+        self.cep18.approve(spender, amount);
+    }
+
+    /// Get allowance (standard CEP-18)
+    pub fn allowance(&self, owner: &Address, spender: &Address) -> U256 {
+        self.cep18.allowance(owner, spender)
+    }
+
+    /// Get token name
+    pub fn name(&self) -> String {
+        self.cep18.name()
+    }
+
+    /// Get token symbol
+    pub fn symbol(&self) -> String {
+        self.cep18.symbol()
+    }
+
+    /// Get token decimals
+    pub fn decimals(&self) -> u8 {
+        self.cep18.decimals()
+    }
+
+    // ============ INTERNAL ============
+
+    /// CSPR value of `shares`, or `default` while nothing is minted
+    fn shares_to_cspr_at(&self, shares: U512, default: U512) -> U512 {
+        let supply = self.total_supply();
+        if supply == U512::zero() {
+            return default;
+        }
+        (shares * self.total_backing()) / supply
+    }
+
+    fn mint(&mut self, to: &Address, shares: U512) {
+        self.cep18.raw_mint(to, &U256::from(shares.as_u128()));
+    }
+
+    /// Delegate idle CSPR once the delegation meets the auction minimum
+    fn delegate_idle(&mut self) {
+        let idle = self.idle_cspr.get_or_default();
+        let staked = self.staked_cspr.get_or_default();
+        if idle == U512::zero() || staked + idle < self.staking.minimum_delegation() {
+            return;
+        }
+
+        self.staking.delegate(idle);
+        self.idle_cspr.set(U512::zero());
+        self.staked_cspr.set(staked + idle);
+
+        self.env().emit_event(Delegated { amount: idle });
+    }
+
+    fn require_admin(&self) {
+        let admin = self.admin.get().expect("Admin not set");
+        if self.env().caller() != admin {
+            self.env().revert(StakedCsprError::NotAdmin);
+        }
+    }
+}
+
+/// stCSPR errors
+#[odra::odra_error]
+pub enum StakedCsprError {
+    /// Zero amount provided (or too small to mint a unit)
+    ZeroAmount = 1,
+    /// stCSPR balance too low
+    InsufficientBalance = 2,
+    /// Unstake request not found
+    RequestNotFound = 3,
+    /// Unstake request already claimed
+    AlreadyClaimed = 4,
+    /// CSPR still unbonding
+    StillUnbonding = 5,
+    /// Caller is not the admin
+    NotAdmin = 6,
+    /// Fee parameter out of range
+    InvalidFee = 7,
+    /// Operation requires no active stake
+    StakeActive = 8,
+}
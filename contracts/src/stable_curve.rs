@@ -0,0 +1,193 @@
+//! Stable curve - per-pool curve selection and amplification ramping
+
+use odra::prelude::*;
+use odra::casper_types::U512;
+
+use crate::events::*;
+use crate::math;
+use crate::stable_math;
+use crate::types::*;
+
+/// Admin entry points shared by every pool that embeds a `StableCurve`
+#[odra::external_contract]
+pub trait StableCurvePool {
+    /// Switch an empty pool to the StableSwap curve
+    fn set_stable_curve(&mut self, amp: u64);
+    /// Ramp the amplification coefficient linearly until `future_time`
+    fn ramp_amp(&mut self, future_amp: u64, future_time: u64);
+    /// Freeze the amplification coefficient at its current value
+    fn stop_amp_ramp(&mut self);
+}
+
+/// Curve a pool prices swaps along
+///
+/// Pools start on the constant product curve. Switching to StableSwap is a one-off
+/// choice made before any liquidity exists; afterwards the admin can only move the
+/// amplification coefficient, linearly over at least `MIN_RAMP_TIME_MS` and by at most
+/// `MAX_AMP_CHANGE`x per ramp, so LPs cannot be repriced abruptly.
+#[odra::module]
+pub struct StableCurve {
+    /// Selected curve (unset means constant product)
+    curve: Var<CurveType>,
+    /// Coefficient at the start of the current ramp
+    initial_amp: Var<u64>,
+    /// Coefficient at the end of the current ramp
+    future_amp: Var<u64>,
+    /// Start time of the current ramp
+    initial_amp_time: Var<u64>,
+    /// End time of the current ramp
+    future_amp_time: Var<u64>,
+}
+
+#[odra::module]
+impl StableCurve {
+    /// Switch to the StableSwap curve with a fixed amplification coefficient
+    /// The caller must ensure the pool holds no liquidity yet
+    pub fn enable(&mut self, amp: u64) {
+        if self.is_stable() {
+            self.env().revert(StableCurveError::AlreadyStable);
+        }
+        self.require_valid_amp(amp);
+
+        let now = self.env().get_block_time();
+        self.curve.set(CurveType::Stable);
+        self.initial_amp.set(amp);
+        self.future_amp.set(amp);
+        self.initial_amp_time.set(now);
+        self.future_amp_time.set(now);
+
+        self.env().emit_event(StableCurveEnabled { amp });
+    }
+
+    /// Ramp the amplification coefficient from its current value to `future_amp`
+    pub fn ramp(&mut self, future_amp: u64, future_time: u64) {
+        if !self.is_stable() {
+            self.env().revert(StableCurveError::NotStable);
+        }
+
+        let now = self.env().get_block_time();
+        if now < self.initial_amp_time.get_or_default() + MIN_RAMP_TIME_MS {
+            self.env().revert(StableCurveError::RampTooSoon);
+        }
+        if future_time < now + MIN_RAMP_TIME_MS {
+            self.env().revert(StableCurveError::RampTooShort);
+        }
+        self.require_valid_amp(future_amp);
+
+        let initial_amp = self.amp();
+        if future_amp > initial_amp * MAX_AMP_CHANGE || initial_amp > future_amp * MAX_AMP_CHANGE {
+            self.env().revert(StableCurveError::AmpChangeTooLarge);
+        }
+
+        self.initial_amp.set(initial_amp);
+        self.future_amp.set(future_amp);
+        self.initial_amp_time.set(now);
+        self.future_amp_time.set(future_time);
+
+        self.env().emit_event(AmpRampStarted {
+            initial_amp,
+            future_amp,
+            initial_time: now,
+            future_time,
+        });
+    }
+
+    /// Freeze the amplification coefficient at its current value
+    pub fn stop_ramp(&mut self) {
+        if !self.is_stable() {
+            self.env().revert(StableCurveError::NotStable);
+        }
+
+        let amp = self.amp();
+        let now = self.env().get_block_time();
+        self.initial_amp.set(amp);
+        self.future_amp.set(amp);
+        self.initial_amp_time.set(now);
+        self.future_amp_time.set(now);
+
+        self.env().emit_event(AmpRampStopped { amp });
+    }
+
+    /// Get the selected curve
+    pub fn curve(&self) -> CurveType {
+        self.curve.get().unwrap_or(CurveType::ConstantProduct)
+    }
+
+    /// Whether swaps use the StableSwap curve
+    pub fn is_stable(&self) -> bool {
+        self.curve() == CurveType::Stable
+    }
+
+    /// Current amplification coefficient, interpolated along an active ramp (0 if not stable)
+    pub fn amp(&self) -> u64 {
+        let initial_amp = self.initial_amp.get_or_default();
+        let future_amp = self.future_amp.get_or_default();
+        let initial_time = self.initial_amp_time.get_or_default();
+        let future_time = self.future_amp_time.get_or_default();
+
+        let now = self.env().get_block_time();
+        if now >= future_time {
+            return future_amp;
+        }
+
+        // u128 so long ramps cannot overflow
+        let elapsed = (now - initial_time) as u128;
+        let duration = (future_time - initial_time) as u128;
+        if future_amp > initial_amp {
+            initial_amp + ((future_amp - initial_amp) as u128 * elapsed / duration) as u64
+        } else {
+            initial_amp - ((initial_amp - future_amp) as u128 * elapsed / duration) as u64
+        }
+    }
+
+    /// Get the current (or last) amplification ramp
+    pub fn ramp_info(&self) -> AmpRamp {
+        AmpRamp {
+            initial_amp: self.initial_amp.get_or_default(),
+            future_amp: self.future_amp.get_or_default(),
+            initial_time: self.initial_amp_time.get_or_default(),
+            future_time: self.future_amp_time.get_or_default(),
+        }
+    }
+
+    /// Output for `amount_in` along the selected curve
+    pub fn get_amount_out(&self, amount_in: U512, reserve_in: U512, reserve_out: U512, swap_fee_bps: u64) -> U512 {
+        if self.is_stable() {
+            stable_math::get_amount_out(amount_in, reserve_in, reserve_out, self.amp(), swap_fee_bps)
+        } else {
+            math::get_amount_out(amount_in, reserve_in, reserve_out, swap_fee_bps)
+        }
+    }
+
+    /// Smallest input for which `get_amount_out` yields at least `amount_out`
+    pub fn get_amount_in(&self, amount_out: U512, reserve_in: U512, reserve_out: U512, swap_fee_bps: u64) -> U512 {
+        if self.is_stable() {
+            stable_math::get_amount_in(amount_out, reserve_in, reserve_out, self.amp(), swap_fee_bps)
+        } else {
+            math::get_amount_in(amount_out, reserve_in, reserve_out, swap_fee_bps)
+        }
+    }
+
+    fn require_valid_amp(&self, amp: u64) {
+        if amp == 0 || amp > MAX_AMP {
+            self.env().revert(StableCurveError::InvalidAmp);
+        }
+    }
+}
+
+/// Stable curve errors
+#[odra::odra_error]
+pub enum StableCurveError {
+    /// Amplification coefficient out of range
+    InvalidAmp = 1,
+    /// Pool already uses the StableSwap curve
+    AlreadyStable = 2,
+    /// Pool does not use the StableSwap curve
+    NotStable = 3,
+    /// Previous ramp started less than `MIN_RAMP_TIME_MS` ago
+    RampTooSoon = 4,
+    /// Ramp would end less than `MIN_RAMP_TIME_MS` from now
+    RampTooShort = 5,
+    /// Ramp changes the coefficient by more than `MAX_AMP_CHANGE`x
+    AmpChangeTooLarge = 6,
+}
@@ -0,0 +1,126 @@
+//! StableSwap (Curve) invariant math for two-asset pools
+//!
+//! Balances must share the same precision. `amp` is the amplification coefficient A
+//! in Curve's convention (A * n^(n-1)); with two assets the invariant is
+//! `2A(x + y) + D = 2AD + D^3 / (4xy)`.
+
+use odra::casper_types::U512;
+
+use crate::types::BPS_DENOMINATOR;
+
+/// Number of assets in the pool
+const N_COINS: u64 = 2;
+
+/// Newton iterations before giving up on convergence
+const MAX_ITERATIONS: usize = 255;
+
+/// Invariant D for balances `x` and `y`
+/// If Newton's method has not settled within `MAX_ITERATIONS` (very low `amp` on very
+/// skewed balances, where rounding makes it oscillate by a unit or two) the last iterate is used.
+pub fn get_d(x: U512, y: U512, amp: u64) -> U512 {
+    let sum = x + y;
+    if sum == U512::zero() {
+        return U512::zero();
+    }
+    if x == U512::zero() || y == U512::zero() {
+        // The invariant degenerates with an empty side; nothing can be priced
+        return U512::zero();
+    }
+
+    let n = U512::from(N_COINS);
+    let ann = U512::from(amp) * n;
+    let mut d = sum;
+
+    for _ in 0..MAX_ITERATIONS {
+        // d_p = D^3 / (4xy)
+        let mut d_p = d;
+        d_p = d_p * d / (x * n);
+        d_p = d_p * d / (y * n);
+
+        let d_prev = d;
+        let numerator = (ann * sum + d_p * n) * d;
+        let denominator = (ann - U512::one()) * d + (n + U512::one()) * d_p;
+        d = numerator / denominator;
+
+        if abs_diff(d, d_prev) <= U512::one() {
+            return d;
+        }
+    }
+
+    d
+}
+
+/// Balance of the other asset that keeps invariant `d` when one side holds `x`
+pub fn get_y(x: U512, d: U512, amp: u64) -> U512 {
+    if x == U512::zero() || d == U512::zero() {
+        return U512::zero();
+    }
+
+    let n = U512::from(N_COINS);
+    let ann = U512::from(amp) * n;
+
+    // c = D^3 / (4 * x * Ann), b = x + D / Ann
+    let mut c = d * d / (x * n);
+    c = c * d / (ann * n);
+    let b = x + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        // y = (y^2 + c) / (2y + b - D); 2y + b >= D holds for every iterate from y = D
+        y = (y * y + c) / (y * U512::from(2u64) + b - d);
+
+        if abs_diff(y, y_prev) <= U512::one() {
+            return y;
+        }
+    }
+
+    y
+}
+
+/// Output for `amount_in` along the StableSwap curve, fee charged on the input
+pub fn get_amount_out(amount_in: U512, reserve_in: U512, reserve_out: U512, amp: u64, swap_fee_bps: u64) -> U512 {
+    if amount_in == U512::zero() || reserve_in == U512::zero() || reserve_out == U512::zero() {
+        return U512::zero();
+    }
+
+    let scale = U512::from(BPS_DENOMINATOR);
+    let amount_in_with_fee = (amount_in * (scale - U512::from(swap_fee_bps))) / scale;
+
+    let d = get_d(reserve_in, reserve_out, amp);
+    let new_reserve_out = get_y(reserve_in + amount_in_with_fee, d, amp);
+
+    // Round against the trader
+    reserve_out.saturating_sub(new_reserve_out + U512::one())
+}
+
+/// Smallest input for which `get_amount_out` yields at least `amount_out`
+/// Returns zero when the reserves cannot cover `amount_out`
+pub fn get_amount_in(amount_out: U512, reserve_in: U512, reserve_out: U512, amp: u64, swap_fee_bps: u64) -> U512 {
+    if amount_out == U512::zero() || reserve_in == U512::zero() || amount_out >= reserve_out {
+        return U512::zero();
+    }
+
+    let d = get_d(reserve_in, reserve_out, amp);
+    // One extra unit out covers the rounding in `get_amount_out`
+    let new_reserve_out = reserve_out - amount_out - U512::one();
+    if new_reserve_out == U512::zero() {
+        return U512::zero();
+    }
+    let new_reserve_in = get_y(new_reserve_out, d, amp);
+
+    // Two units of slack cover the Newton tolerance on both sides
+    let amount_in_with_fee = new_reserve_in.saturating_sub(reserve_in) + U512::from(2u64);
+
+    let scale = U512::from(BPS_DENOMINATOR);
+    let fee_multiplier = scale - U512::from(swap_fee_bps);
+    (amount_in_with_fee * scale + fee_multiplier - U512::one()) / fee_multiplier
+}
+
+fn abs_diff(a: U512, b: U512) -> U512 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
@@ -0,0 +1,232 @@
+//! Staking backend - routes delegation to the System Auction or an auction simulator
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::casper_types::{PublicKey, U512};
+
+use crate::st_cspr::LiquidStakingContractRef;
+use crate::types::MINIMUM_DELEGATION;
+
+/// Interface the backend uses to delegate through an `AuctionSimulator`
+///
+/// Declared here rather than imported so production builds, which leave the simulator
+/// out, still compile the backend.
+#[odra::external_contract]
+pub trait SimulatedAuction {
+    /// Delegate the attached CSPR to a validator
+    fn delegate(&mut self, validator: PublicKey);
+    /// Undelegate CSPR from a validator; it is paid back after the unbonding delay
+    fn undelegate(&mut self, validator: PublicKey, amount: U512);
+    /// Get CSPR delegated by a delegator to a validator (rewards included)
+    fn delegated_amount(&self, delegator: Address, validator: PublicKey) -> U512;
+    /// Get the minimum total delegation
+    fn minimum_delegation(&self) -> U512;
+}
+
+/// Delegation backend used by the pool
+///
+/// WASM builds delegate through the System Auction. Native builds have no auction,
+/// so they either track delegation in storage or, once a simulator is set, delegate
+/// to an `AuctionSimulator` contract. Once a liquid staking token is set, stake is
+/// held as stCSPR instead and unstaking goes through its redemption queue.
+#[odra::module]
+pub struct StakingBackend {
+    /// Validator public key for delegation
+    validator: Var<PublicKey>,
+    /// Auction simulator replacing the System Auction (tests only)
+    simulator: Var<Address>,
+    /// Delegation tracked in storage when no auction is reachable
+    native_delegated: Var<U512>,
+    /// Liquid staking token staked through instead of delegating directly
+    liquid_staking: Var<Address>,
+    /// Unclaimed stCSPR unstake request IDs
+    unstake_tickets: Var<Vec<u64>>,
+}
+
+#[odra::module]
+impl StakingBackend {
+    /// Initialize the backend
+    pub fn init(&mut self, validator: PublicKey) {
+        self.validator.set(validator);
+    }
+
+    /// Route staking to an auction simulator
+    /// Only available in native builds or WASM builds with the `test-mode` feature
+    pub fn set_simulator(&mut self, simulator: Address) {
+        if !cfg!(any(not(target_arch = "wasm32"), feature = "test-mode")) {
+            self.env().revert(StakingError::SimulatorDisabled);
+        }
+        self.simulator.set(simulator);
+    }
+
+    /// Get the auction simulator, if staking is routed to one
+    pub fn simulator(&self) -> Option<Address> {
+        self.simulator.get()
+    }
+
+    /// Stake through a liquid staking token instead of delegating directly
+    /// The caller must ensure nothing is delegated or unbonding and the validators match
+    pub fn set_liquid_staking(&mut self, st_cspr: Address) {
+        self.liquid_staking.set(st_cspr);
+    }
+
+    /// Get the liquid staking token, if staking is routed through one
+    pub fn liquid_staking(&self) -> Option<Address> {
+        self.liquid_staking.get()
+    }
+
+    /// Switch the validator new delegations go to
+    /// The caller must ensure nothing is still delegated to the previous validator
+    pub fn set_validator(&mut self, validator: PublicKey) {
+        self.validator.set(validator);
+    }
+
+    /// Get the validator public key
+    pub fn validator(&self) -> PublicKey {
+        self.validator.get().expect("Validator not set")
+    }
+
+    /// Delegate CSPR from the contract purse to the validator
+    pub fn delegate(&mut self, amount: U512) {
+        if let Some(st_cspr) = self.liquid_staking.get() {
+            LiquidStakingContractRef::new(self.env(), st_cspr)
+                .with_tokens(amount)
+                .deposit();
+            return;
+        }
+
+        let validator = self.validator();
+
+        if let Some(simulator) = self.simulator.get() {
+            SimulatedAuctionContractRef::new(self.env(), simulator)
+                .with_tokens(amount)
+                .delegate(validator);
+            return;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.env().delegate(validator, amount);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = validator; // silence unused warning in native/test mode
+            let delegated = self.native_delegated.get_or_default();
+            self.native_delegated.set(delegated + amount);
+        }
+    }
+
+    /// Undelegate CSPR from the validator (returns to the purse after unbonding)
+    /// With a liquid staking token, redeems enough stCSPR to cover `amount`; the CSPR
+    /// arrives once `settle` claims the matured request.
+    pub fn undelegate(&mut self, amount: U512) {
+        if let Some(st_cspr) = self.liquid_staking.get() {
+            let mut token = LiquidStakingContractRef::new(self.env(), st_cspr);
+            let held = token.balance_of(&self.env().self_address());
+            let shares = token.cspr_to_shares_up(amount).min(held);
+            if shares > U512::zero() {
+                let ticket = token.request_unstake(shares);
+                let mut tickets = self.unstake_tickets.get_or_default();
+                tickets.push(ticket);
+                self.unstake_tickets.set(tickets);
+            }
+            return;
+        }
+
+        let validator = self.validator();
+
+        if let Some(simulator) = self.simulator.get() {
+            SimulatedAuctionContractRef::new(self.env(), simulator).undelegate(validator, amount);
+            return;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        self.env().undelegate(validator, amount);
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let _ = validator; // silence unused warning in native/test mode
+            let delegated = self.native_delegated.get_or_default();
+            self.native_delegated.set(delegated.saturating_sub(amount));
+        }
+    }
+
+    /// Get CSPR currently delegated to the validator (rewards and slashing included)
+    pub fn delegated_amount(&self) -> U512 {
+        if let Some(st_cspr) = self.liquid_staking.get() {
+            let token = LiquidStakingContractRef::new(self.env(), st_cspr);
+            return token.shares_to_cspr(token.balance_of(&self.env().self_address()));
+        }
+
+        let validator = self.validator();
+
+        if let Some(simulator) = self.simulator.get() {
+            let delegator = self.env().self_address();
+            return SimulatedAuctionContractRef::new(self.env(), simulator)
+                .delegated_amount(delegator, validator);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        let delegated = self.env().delegated_amount(validator);
+        #[cfg(not(target_arch = "wasm32"))]
+        let delegated = {
+            let _ = validator; // silence unused warning in native/test mode
+            self.native_delegated.get_or_default()
+        };
+
+        delegated
+    }
+
+    /// Get the minimum total delegation accepted by the auction
+    /// stCSPR pools deposits itself, so any amount can be staked through it
+    pub fn minimum_delegation(&self) -> U512 {
+        if self.liquid_staking.get().is_some() {
+            return U512::zero();
+        }
+        match self.simulator.get() {
+            Some(simulator) => SimulatedAuctionContractRef::new(self.env(), simulator).minimum_delegation(),
+            None => U512::from(MINIMUM_DELEGATION),
+        }
+    }
+
+    /// Whether delegated and unbonding CSPR stays in the contract purse
+    /// (native builds without a simulator only track delegation in storage)
+    pub fn holds_stake_in_purse(&self) -> bool {
+        cfg!(not(target_arch = "wasm32"))
+            && self.simulator.get().is_none()
+            && self.liquid_staking.get().is_none()
+    }
+
+    /// Claim matured stCSPR unstake requests into the contract purse
+    /// No-op unless staking through a liquid staking token.
+    pub fn settle(&mut self) {
+        let Some(st_cspr) = self.liquid_staking.get() else {
+            return;
+        };
+        let tickets = self.unstake_tickets.get_or_default();
+        if tickets.is_empty() {
+            return;
+        }
+
+        let now = self.env().get_block_time();
+        let mut token = LiquidStakingContractRef::new(self.env(), st_cspr);
+        let mut pending = Vec::new();
+        for ticket in tickets {
+            let request = token.get_unstake_request(ticket);
+            if request.claimed {
+                continue;
+            }
+            if now >= request.claimable_time {
+                token.claim_unstake(ticket);
+            } else {
+                pending.push(ticket);
+            }
+        }
+        self.unstake_tickets.set(pending);
+    }
+}
+
+/// Staking backend errors
+#[odra::odra_error]
+pub enum StakingError {
+    /// Simulator routing is disabled in production builds
+    SimulatorDisabled = 1,
+}
@@ -39,8 +39,8 @@ impl TestToken {
     }
 
     /// Get balance of address
-    pub fn balance_of(&self, owner: &Address) -> U256 {
-        self.cep18.balance_of(owner)
+    pub fn balance_of(&self, address: &Address) -> U256 {
+        self.cep18.balance_of(address)
     }
 
     /// Transfer tokens
@@ -0,0 +1,176 @@
+//! Tick book - initialized ticks of a concentrated-liquidity pool
+
+use alloc::vec::Vec;
+use odra::prelude::*;
+use odra::casper_types::{U256, U512};
+
+use crate::cl_math::{wrapping_sub, MAX_TICK, MIN_TICK};
+use crate::types::{ClSlot, TickInfo};
+
+/// Initialized ticks with their liquidity and fee growth outside
+///
+/// Initialized ticks are flagged in a bitmap of 256-tick words (in units of the tick
+/// spacing), so a swap step only reads one word to find the next tick or the word's
+/// edge, however many ticks are initialized. Fee growth "outside" a tick is relative to the current price and flips
+/// every time the price crosses it, Uniswap v3 style.
+#[odra::module]
+pub struct TickBook {
+    /// Distance between usable ticks
+    tick_spacing: Var<i32>,
+    /// Tick data by tick index
+    ticks: Mapping<i32, TickInfo>,
+    /// Initialized ticks bitmap by word (compressed tick >> 8)
+    bitmap: Mapping<i32, U256>,
+}
+
+#[odra::module]
+impl TickBook {
+    /// Initialize the book
+    pub fn init(&mut self, tick_spacing: i32) {
+        self.tick_spacing.set(tick_spacing);
+    }
+
+    /// Get the tick spacing
+    pub fn tick_spacing(&self) -> i32 {
+        self.tick_spacing.get_or_default()
+    }
+
+    /// Get a tick (default when uninitialized)
+    pub fn get(&self, tick: i32) -> TickInfo {
+        self.ticks.get(&tick).unwrap_or_default()
+    }
+
+    /// Get up to `limit` initialized ticks at or above `from_tick`, ascending
+    pub fn initialized_ticks(&self, from_tick: i32, limit: u32) -> Vec<i32> {
+        let mut ticks = Vec::new();
+        let mut tick = from_tick.saturating_sub(1);
+        while ticks.len() < limit as usize && tick < MAX_TICK {
+            let (next, initialized) = self.next_above(tick);
+            if initialized {
+                ticks.push(next);
+            }
+            tick = next;
+        }
+        ticks
+    }
+
+    /// Greatest initialized tick at or below `tick` within its bitmap word
+    /// Returns the word's lowest tick and `false` when none is initialized there.
+    pub fn next_at_or_below(&self, tick: i32) -> (i32, bool) {
+        let spacing = self.tick_spacing();
+        let compressed = tick.div_euclid(spacing);
+        let (word, bit) = position(compressed);
+        let at_or_below = (U256::one() << bit) - U256::one() + (U256::one() << bit);
+        let masked = self.bitmap.get(&word).unwrap_or_default() & at_or_below;
+
+        let (next, initialized) = if masked.is_zero() {
+            (compressed - bit as i32, false)
+        } else {
+            let highest = 255 - masked.leading_zeros() as i32;
+            (compressed - (bit as i32 - highest), true)
+        };
+        ((next * spacing).max(MIN_TICK), initialized)
+    }
+
+    /// Smallest initialized tick above `tick` within the next tick's bitmap word
+    /// Returns the word's highest tick and `false` when none is initialized there.
+    pub fn next_above(&self, tick: i32) -> (i32, bool) {
+        let spacing = self.tick_spacing();
+        let compressed = tick.div_euclid(spacing) + 1;
+        let (word, bit) = position(compressed);
+        let at_or_above = !((U256::one() << bit) - U256::one());
+        let masked = self.bitmap.get(&word).unwrap_or_default() & at_or_above;
+
+        let (next, initialized) = if masked.is_zero() {
+            (compressed + (255 - bit as i32), false)
+        } else {
+            let lowest = masked.trailing_zeros() as i32;
+            (compressed + (lowest - bit as i32), true)
+        };
+        ((next * spacing).min(MAX_TICK), initialized)
+    }
+
+    /// Add or remove position liquidity referencing `tick` as its lower or upper bound
+    ///
+    /// A tick initialized at or below the current tick assumes all fees so far were
+    /// earned below it; a tick that drops to zero liquidity is cleared.
+    pub fn update(&mut self, tick: i32, slot: &ClSlot, liquidity: U512, add: bool, upper: bool) {
+        let mut info = self.get(tick);
+        let was_initialized = info.liquidity_gross() > U512::zero();
+
+        let side = if upper { &mut info.liquidity_upper } else { &mut info.liquidity_lower };
+        *side = if add { *side + liquidity } else { *side - liquidity };
+
+        if info.liquidity_gross() == U512::zero() {
+            if was_initialized {
+                self.ticks.set(&tick, TickInfo::default());
+                self.flip(tick);
+            }
+            return;
+        }
+
+        if !was_initialized {
+            if tick <= slot.tick {
+                info.fee_growth_outside_cspr = slot.fee_growth_global_cspr;
+                info.fee_growth_outside_token = slot.fee_growth_global_token;
+                info.reward_growth_outside = slot.reward_growth_global;
+            }
+            self.flip(tick);
+        }
+
+        self.ticks.set(&tick, info);
+    }
+
+    /// Flip a tick's fee and reward growth outside as the price crosses it
+    pub fn cross(
+        &mut self,
+        tick: i32,
+        fee_growth_global_cspr: U512,
+        fee_growth_global_token: U512,
+        reward_growth_global: U512,
+    ) {
+        let mut info = self.get(tick);
+        info.fee_growth_outside_cspr = wrapping_sub(fee_growth_global_cspr, info.fee_growth_outside_cspr);
+        info.fee_growth_outside_token = wrapping_sub(fee_growth_global_token, info.fee_growth_outside_token);
+        info.reward_growth_outside = wrapping_sub(reward_growth_global, info.reward_growth_outside);
+        self.ticks.set(&tick, info);
+    }
+
+    /// (CSPR fee, token fee, staking reward) growth inside [tick_lower, tick_upper) at the current slot
+    pub fn growth_inside(&self, tick_lower: i32, tick_upper: i32, slot: &ClSlot) -> (U512, U512, U512) {
+        let lower = self.get(tick_lower);
+        let upper = self.get(tick_upper);
+
+        let inside = |global: U512, lower_outside: U512, upper_outside: U512| {
+            let below = if slot.tick >= tick_lower {
+                lower_outside
+            } else {
+                wrapping_sub(global, lower_outside)
+            };
+            let above = if slot.tick < tick_upper {
+                upper_outside
+            } else {
+                wrapping_sub(global, upper_outside)
+            };
+            wrapping_sub(wrapping_sub(global, below), above)
+        };
+
+        (
+            inside(slot.fee_growth_global_cspr, lower.fee_growth_outside_cspr, upper.fee_growth_outside_cspr),
+            inside(slot.fee_growth_global_token, lower.fee_growth_outside_token, upper.fee_growth_outside_token),
+            inside(slot.reward_growth_global, lower.reward_growth_outside, upper.reward_growth_outside),
+        )
+    }
+
+    /// Toggle a tick's bit in the bitmap
+    fn flip(&mut self, tick: i32) {
+        let (word, bit) = position(tick / self.tick_spacing());
+        let bits = self.bitmap.get(&word).unwrap_or_default() ^ (U256::one() << bit);
+        self.bitmap.set(&word, bits);
+    }
+}
+
+/// Bitmap word and bit of a compressed tick (tick / tick spacing)
+fn position(compressed: i32) -> (i32, u8) {
+    (compressed >> 8, (compressed & 0xff) as u8)
+}
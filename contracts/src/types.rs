@@ -1,11 +1,17 @@
 //! Data types for Ghost Pool AMM
 
-use odra::casper_types::{U256, U512};
+use odra::casper_types::{PublicKey, U256, U512};
 use odra::prelude::Address;
 
-/// Pool state containing reserves and staking information
+/// Snapshot of a pool's balances, settings and liabilities, read in one call
 #[odra::odra_type]
 pub struct PoolState {
+    /// Paired CEP-18 token address
+    pub token_address: Address,
+    /// How incoming paired tokens are credited
+    pub token_accounting: TokenAccounting,
+    /// Decimals of the paired token
+    pub token_decimals: u8,
     /// Total CSPR reserve (staked + buffer)
     pub reserve_cspr: U512,
     /// Total paired token reserve
@@ -14,12 +20,49 @@ pub struct PoolState {
     pub staked_cspr: U512,
     /// Unstaked CSPR for immediate swaps
     pub buffer_cspr: U512,
-    /// Target buffer percentage (1000 = 10%)
-    pub buffer_target_bps: U256,
-    /// Swap fee (30 = 0.3%)
-    pub swap_fee_bps: U256,
-    /// Protocol fee on staking rewards (1000 = 10%)
-    pub protocol_fee_bps: U256,
+    /// Undelegated CSPR not yet returned by the auction
+    pub unbonding_cspr: U512,
+    /// LP token total supply
+    pub lp_total_supply: U512,
+    /// Pool configuration (fees and buffer target)
+    pub config: PoolConfig,
+    /// Swap curve
+    pub curve: CurveType,
+    /// Validator the pool delegates to
+    pub validator: PublicKey,
+    /// stCSPR contract the pool stakes through, if any
+    pub liquid_staking: Option<Address>,
+    /// Treasury address for protocol fees
+    pub treasury: Address,
+    /// Admin address
+    pub admin: Address,
+    /// CSPR owed to pending withdrawal requests
+    pub pending_withdrawal_cspr: U512,
+    /// CSPR swap fees owed to the treasury
+    pub protocol_fees_cspr: U512,
+    /// Token swap fees owed to the treasury
+    pub protocol_fees_token: U512,
+}
+
+/// Execution details of a quoted swap
+#[odra::odra_type]
+pub struct SwapQuote {
+    /// Input amount (CSPR motes or token base units)
+    pub amount_in: U512,
+    /// Output amount, before any transfer fee of the paired token
+    pub amount_out: U512,
+    /// Swap fee charged on the input, protocol share included
+    pub fee_paid: U512,
+    /// Price of one CSPR in whole tokens before the swap (PRICE_DECIMALS fixed point)
+    pub spot_price_before: U512,
+    /// Price of one CSPR in whole tokens after the swap (PRICE_DECIMALS fixed point)
+    pub spot_price_after: U512,
+    /// Shortfall of the output against the pre-swap spot price in bps, excluding the fee
+    pub price_impact_bps: u64,
+    /// Whether the CSPR buffer can pay the output (always true when buying tokens)
+    pub buffer_sufficient: bool,
+    /// Smallest acceptable output at the requested slippage tolerance
+    pub min_amount_out: U512,
 }
 
 /// Withdrawal request for queued CSPR withdrawals
@@ -27,7 +70,7 @@ pub struct PoolState {
 pub struct WithdrawalRequest {
     /// Unique withdrawal ID
     pub id: u64,
-    /// User address
+    /// Current owner (receives the CSPR; starts as the LP, changes on transfer)
     pub user: Address,
     /// LP tokens burned
     pub lp_burned: U512,
@@ -41,6 +84,50 @@ pub struct WithdrawalRequest {
     pub claimable_time: u64,
     /// Whether withdrawal has been claimed
     pub claimed: bool,
+    /// Whether withdrawal was cancelled and re-entered the pool
+    pub cancelled: bool,
+    /// Withdrawal payout index when requested (payout scales with later losses)
+    pub payout_index: U512,
+}
+
+impl WithdrawalRequest {
+    /// Whether the request still owes CSPR (neither claimed nor cancelled)
+    pub fn is_open(&self) -> bool {
+        !self.claimed && !self.cancelled
+    }
+}
+
+/// Status of a withdrawal request
+#[odra::odra_type]
+pub enum WithdrawalStatus {
+    /// CSPR is still unbonding
+    Unbonding,
+    /// CSPR can be claimed
+    Claimable,
+    /// CSPR has been claimed
+    Claimed,
+    /// Request was cancelled and its CSPR re-entered the pool
+    Cancelled,
+}
+
+/// Asset a zap-out pays in
+#[odra::odra_type]
+pub enum ZapAsset {
+    /// CSPR, paid through the withdrawal queue
+    Cspr,
+    /// Paired token, paid immediately
+    Token,
+}
+
+/// How the pool credits paired tokens it receives
+#[odra::odra_type]
+#[derive(Default)]
+pub enum TokenAccounting {
+    /// Credit the amount requested from the sender (plain CEP-18 tokens)
+    #[default]
+    Exact,
+    /// Credit the change in the pool's balance (fee-on-transfer and rebasing tokens)
+    BalanceDelta,
 }
 
 /// Minimum liquidity locked forever to prevent division by zero
@@ -55,6 +142,79 @@ pub const DEFAULT_SWAP_FEE_BPS: u64 = 30;
 /// Default protocol fee on staking rewards (10%)
 pub const DEFAULT_PROTOCOL_FEE_BPS: u64 = 1000;
 
+/// Default share of swap fees diverted to the treasury (fee switch off)
+pub const DEFAULT_PROTOCOL_SWAP_FEE_BPS: u64 = 0;
+
+/// Maximum share of swap fees the fee switch may divert (50%)
+pub const MAX_PROTOCOL_SWAP_FEE_BPS: u64 = 5000;
+
+/// Maximum swap fee (10%)
+pub const MAX_SWAP_FEE_BPS: u64 = 1000;
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10000;
+
+/// Decimals of CSPR (1 CSPR = 10^9 motes)
+pub const CSPR_DECIMALS: u8 = 9;
+
+/// Decimals of the fixed-point price returned by `get_spot_price`
+pub const PRICE_DECIMALS: u8 = 18;
+
+/// Most decimals a price view may be requested with
+pub const MAX_PRICE_DECIMALS: u8 = 36;
+
+/// Fraction of the CSPR reserve probed to price a StableSwap pool (1 / 10^6)
+pub const SPOT_PRICE_PROBE_DIVISOR: u64 = 1_000_000;
+
+/// Scale of the withdrawal payout index (1.0 = no losses realized)
+pub const PAYOUT_INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// Minimum total delegation accepted by the System Auction (500 CSPR)
+pub const MINIMUM_DELEGATION: u64 = 500_000_000_000;
+
+/// Maximum withdrawal requests returned or scanned by one paginated call
+pub const MAX_WITHDRAWAL_PAGE_SIZE: u32 = 50;
+
+/// Maximum withdrawal requests scanned when advancing the global queue head
+pub const MAX_QUEUE_SCAN: u64 = 50;
+
+/// Maximum withdrawal requests scanned by one batch claim
+pub const MAX_BATCH_CLAIM: u32 = 50;
+
+/// Invariant a pool prices swaps along
+#[odra::odra_type]
+pub enum CurveType {
+    /// Constant product (x * y = k)
+    ConstantProduct,
+    /// StableSwap with an amplification coefficient, for correlated assets
+    Stable,
+}
+
+/// Linear ramp of the StableSwap amplification coefficient
+#[odra::odra_type]
+pub struct AmpRamp {
+    /// Coefficient at the start of the ramp
+    pub initial_amp: u64,
+    /// Coefficient at the end of the ramp
+    pub future_amp: u64,
+    /// Ramp start time
+    pub initial_time: u64,
+    /// Ramp end time
+    pub future_time: u64,
+}
+
+/// Maximum pools returned by one paginated factory call
+pub const MAX_POOLS_PAGE_SIZE: u32 = 50;
+
+/// Maximum StableSwap amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Maximum factor the amplification coefficient may change by in one ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+
+/// Minimum duration of an amplification ramp, and between ramp starts (1 day)
+pub const MIN_RAMP_TIME_MS: u64 = 24 * 60 * 60 * 1000;
+
 /// Unbonding period in milliseconds (14 hours)
 pub const UNBONDING_PERIOD_MS: u64 = 14 * 60 * 60 * 1000;
 
@@ -68,4 +228,299 @@ pub struct PoolConfig {
     pub swap_fee_bps: U256,
     /// Protocol fee on staking rewards (1000 = 10%)
     pub protocol_fee_bps: U256,
+    /// Share of each swap fee sent to the treasury (1666 = 1/6, 0 = off)
+    pub protocol_swap_fee_bps: U256,
+}
+
+impl PoolConfig {
+    /// Pool defaults for a new deployment
+    pub fn with_defaults() -> Self {
+        Self {
+            buffer_target_bps: U256::from(DEFAULT_BUFFER_TARGET_BPS),
+            swap_fee_bps: U256::from(DEFAULT_SWAP_FEE_BPS),
+            protocol_fee_bps: U256::from(DEFAULT_PROTOCOL_FEE_BPS),
+            protocol_swap_fee_bps: U256::from(DEFAULT_PROTOCOL_SWAP_FEE_BPS),
+        }
+    }
+
+    /// Whether every parameter is within its allowed range
+    pub fn is_valid(&self) -> bool {
+        self.buffer_target_bps <= U256::from(BPS_DENOMINATOR)
+            && self.swap_fee_bps <= U256::from(MAX_SWAP_FEE_BPS)
+            && self.protocol_fee_bps <= U256::from(BPS_DENOMINATOR)
+            && self.protocol_swap_fee_bps <= U256::from(MAX_PROTOCOL_SWAP_FEE_BPS)
+    }
+}
+
+/// Tracked bookkeeping compared against the pool's actual balances
+#[odra::odra_type]
+pub struct SolvencyReport {
+    /// Tracked CSPR reserve (staked + buffer)
+    pub tracked_reserve_cspr: U512,
+    /// Tracked CSPR delegated via auction
+    pub tracked_staked_cspr: U512,
+    /// Tracked unstaked CSPR buffer
+    pub tracked_buffer_cspr: U512,
+    /// Tracked paired token reserve
+    pub tracked_reserve_token: U512,
+    /// CSPR actually held in the contract purse
+    pub actual_purse_cspr: U512,
+    /// CSPR actually delegated to the validator
+    pub actual_delegated_cspr: U512,
+    /// Paired tokens actually held by the pool
+    pub actual_token_balance: U512,
+    /// CSPR owed to unclaimed withdrawal requests
+    pub pending_withdrawal_cspr: U512,
+    /// CSPR undelegated but not yet returned by the auction
+    pub unbonding_cspr: U512,
+    /// CSPR swap fees owed to the treasury
+    pub protocol_fees_cspr: U512,
+    /// Token swap fees owed to the treasury
+    pub protocol_fees_token: U512,
+    /// CSPR held beyond all liabilities
+    pub cspr_surplus: U512,
+    /// CSPR missing to cover all liabilities
+    pub cspr_deficit: U512,
+    /// Tokens held beyond reserve and fees
+    pub token_surplus: U512,
+    /// Tokens missing to cover reserve and fees
+    pub token_deficit: U512,
+}
+
+/// Price and in-range liquidity of a concentrated-liquidity pool
+#[odra::odra_type]
+#[derive(Default)]
+pub struct ClSlot {
+    /// Current sqrt price (token per CSPR) as Q96
+    pub sqrt_price_x96: U512,
+    /// Greatest tick at or below the current price
+    pub tick: i32,
+    /// Liquidity of positions whose range contains the current price
+    pub liquidity: U512,
+    /// CSPR fees earned per unit of liquidity since launch (Q128, wrapping)
+    pub fee_growth_global_cspr: U512,
+    /// Token fees earned per unit of liquidity since launch (Q128, wrapping)
+    pub fee_growth_global_token: U512,
+    /// CSPR staking rewards earned per unit of liquidity since launch (Q128, wrapping)
+    pub reward_growth_global: U512,
+}
+
+/// Tracked balances of a concentrated-liquidity pool
+#[odra::odra_type]
+#[derive(Default)]
+pub struct ClBalances {
+    /// CSPR backing positions (staked + buffer)
+    pub reserve_cspr: U512,
+    /// Tokens backing positions
+    pub reserve_token: U512,
+    /// CSPR delegated via auction
+    pub staked_cspr: U512,
+    /// Unstaked CSPR for immediate swaps
+    pub buffer_cspr: U512,
+    /// CSPR owed to positions (fees and removed liquidity, not yet collected)
+    pub owed_cspr: U512,
+    /// Tokens owed to positions (fees and removed liquidity, not yet collected)
+    pub owed_token: U512,
+    /// CSPR swap fees and reward cut accrued to the treasury
+    pub protocol_fees_cspr: U512,
+    /// Token swap fees accrued to the treasury
+    pub protocol_fees_token: U512,
+    /// CSPR undelegated and still unbonding
+    pub unbonding_cspr: U512,
 }
+
+/// Liquidity referencing an initialized tick
+#[odra::odra_type]
+#[derive(Default)]
+pub struct TickInfo {
+    /// Liquidity of positions using this tick as their lower bound
+    pub liquidity_lower: U512,
+    /// Liquidity of positions using this tick as their upper bound
+    pub liquidity_upper: U512,
+    /// CSPR fee growth on the other side of this tick from the current price (Q128)
+    pub fee_growth_outside_cspr: U512,
+    /// Token fee growth on the other side of this tick from the current price (Q128)
+    pub fee_growth_outside_token: U512,
+    /// Staking reward growth on the other side of this tick from the current price (Q128)
+    pub reward_growth_outside: U512,
+}
+
+impl TickInfo {
+    /// Total liquidity referencing the tick (zero means uninitialized)
+    pub fn liquidity_gross(&self) -> U512 {
+        self.liquidity_lower + self.liquidity_upper
+    }
+}
+
+/// Concentrated-liquidity position, identified by its non-fungible ID
+#[odra::odra_type]
+pub struct Position {
+    /// Position ID
+    pub id: u64,
+    /// Lower bound of the price range
+    pub tick_lower: i32,
+    /// Upper bound of the price range (exclusive)
+    pub tick_upper: i32,
+    /// Liquidity provided over the range
+    pub liquidity: U512,
+    /// CSPR fee growth inside the range at the last update (Q128)
+    pub fee_growth_inside_cspr_last: U512,
+    /// Token fee growth inside the range at the last update (Q128)
+    pub fee_growth_inside_token_last: U512,
+    /// Staking reward growth inside the range at the last update (Q128)
+    pub reward_growth_inside_last: U512,
+    /// CSPR fees and removed liquidity awaiting collection
+    pub cspr_owed: U512,
+    /// Token fees and removed liquidity awaiting collection
+    pub token_owed: U512,
+    /// When the owed CSPR can be collected (set when it had to be undelegated)
+    pub cspr_unlock_time: u64,
+    /// Compounded staking rewards awaiting collection
+    pub rewards_owed: U512,
+    /// When the owed rewards can be collected (they unbond after each harvest)
+    pub rewards_unlock_time: u64,
+}
+
+/// Maximum tick spacing of concentrated-liquidity pools
+pub const MAX_TICK_SPACING: i32 = 16384;
+
+/// Maximum positions returned by one paginated call
+pub const MAX_POSITIONS_PAGE_SIZE: u32 = 50;
+
+/// Pending stCSPR redemption, claimable once its CSPR is back from the auction
+#[odra::odra_type]
+pub struct UnstakeRequest {
+    /// Request ID
+    pub id: u64,
+    /// Holder who burned the stCSPR (receives the CSPR)
+    pub owner: Address,
+    /// CSPR owed
+    pub cspr_amount: U512,
+    /// When the CSPR can be claimed
+    pub claimable_time: u64,
+    /// Whether the request has been claimed
+    pub claimed: bool,
+}
+
+/// stCSPR units in one stCSPR (9 decimals, like CSPR)
+pub const ST_CSPR_UNIT: u64 = 1_000_000_000;
+
+/// Incentive campaign emitting gauge rewards at a fixed rate over a time window
+#[odra::odra_type]
+pub struct Campaign {
+    /// Campaign ID
+    pub id: u32,
+    /// Reward tokens emitted per second
+    pub reward_per_second: U512,
+    /// Emission start time
+    pub start_time: u64,
+    /// Emission end time
+    pub end_time: u64,
+}
+
+impl Campaign {
+    /// Rewards emitted between `from` and `to` (clamped to the campaign window)
+    pub fn emitted_between(&self, from: u64, to: u64) -> U512 {
+        let start = from.max(self.start_time);
+        let end = to.min(self.end_time);
+        if end <= start {
+            return U512::zero();
+        }
+        (self.reward_per_second * U512::from(end - start)) / U512::from(1000u64)
+    }
+
+    /// Total rewards the campaign emits
+    pub fn total_rewards(&self) -> U512 {
+        self.emitted_between(self.start_time, self.end_time)
+    }
+}
+
+/// LP shares staked in a gauge and the rewards already accounted for them
+#[odra::odra_type]
+#[derive(Default)]
+pub struct GaugeStake {
+    /// LP shares staked
+    pub amount: U512,
+    /// amount * acc_reward_per_share at the last update (scaled)
+    pub reward_debt: U512,
+    /// Rewards accrued but not yet harvested
+    pub pending: U512,
+}
+
+/// Scale of the gauge's accumulated reward per share
+pub const REWARD_PER_SHARE_SCALE: u128 = 1_000_000_000_000_000_000;
+
+/// Maximum campaigns a gauge accrues through in one update
+pub const MAX_CAMPAIGN_SCAN: u32 = 50;
+
+/// Returns attributed to an LP's shares since their first deposit
+#[odra::odra_type]
+#[derive(Default)]
+pub struct LpEarnings {
+    /// CSPR swap fees earned
+    pub fees_cspr: U512,
+    /// Token swap fees earned
+    pub fees_token: U512,
+    /// CSPR staking rewards earned (after the protocol fee)
+    pub staking_rewards: U512,
+}
+
+/// An LP's earnings as of their last balance change, with the indexes they were taken at
+#[odra::odra_type]
+#[derive(Default)]
+pub struct LpCheckpoint {
+    /// Earnings accrued up to the checkpoint
+    pub earned: LpEarnings,
+    /// CSPR fees per share at the checkpoint
+    pub fees_cspr_index: U512,
+    /// Token fees per share at the checkpoint
+    pub fees_token_index: U512,
+    /// Staking rewards per share at the checkpoint
+    pub staking_rewards_index: U512,
+}
+
+/// Pool activity over one day of the rolling stats window
+#[odra::odra_type]
+#[derive(Default)]
+pub struct DailyStats {
+    /// Day number (block time / DAY_MS)
+    pub day: u64,
+    /// CSPR swapped into the pool
+    pub volume_cspr: U512,
+    /// Tokens swapped into the pool
+    pub volume_token: U512,
+    /// CSPR swap fees left to LPs
+    pub fees_cspr: U512,
+    /// Token swap fees left to LPs
+    pub fees_token: U512,
+    /// Staking rewards compounded into the reserve
+    pub staking_rewards: U512,
+}
+
+/// Pool snapshot with rolling activity and the yields it implies
+#[odra::odra_type]
+pub struct PoolStats {
+    /// Pool state at the end of the window
+    pub state: PoolState,
+    /// Start of the stats window
+    pub window_start: u64,
+    /// End of the stats window (current block time)
+    pub window_end: u64,
+    /// Activity summed over the window (`day` is the window's first day)
+    pub totals: DailyStats,
+    /// Annualized LP swap fees over pool value (bps)
+    pub fee_apr_bps: u64,
+    /// Annualized staking rewards over pool value (bps)
+    pub staking_apr_bps: u64,
+    /// Combined APR compounded daily (bps)
+    pub total_apy_bps: u64,
+}
+
+/// Milliseconds in a day
+pub const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Milliseconds in a 365-day year
+pub const YEAR_MS: u64 = 365 * DAY_MS;
+
+/// Days of activity kept by the pool's rolling stats
+pub const STATS_WINDOW_DAYS: u64 = 7;
@@ -0,0 +1,43 @@
+//! Withdrawal queue storage for the pool
+
+use odra::prelude::*;
+use odra::casper_types::U512;
+
+use crate::types::WithdrawalRequest;
+
+/// Storage of the pool's CSPR withdrawal queue
+///
+/// Kept in its own module because Odra caps the number of fields per module;
+/// the queue logic lives in the pool, which reads and writes these fields directly.
+#[odra::module]
+pub struct WithdrawalQueue {
+    /// Counter for withdrawal IDs
+    pub(crate) withdrawal_counter: Var<u64>,
+    /// Withdrawal requests by ID
+    pub(crate) withdrawals: Mapping<u64, WithdrawalRequest>,
+    /// User's withdrawal IDs by (user, position)
+    pub(crate) user_withdrawal_ids: Mapping<(Address, u32), u64>,
+    /// Number of withdrawal IDs listed per user
+    pub(crate) user_withdrawal_count: Mapping<Address, u32>,
+    /// Position of each withdrawal ID in its user's list
+    pub(crate) withdrawal_position: Mapping<u64, u32>,
+    /// Number of unclaimed withdrawals per user
+    pub(crate) user_pending_count: Mapping<Address, u32>,
+    /// Number of unclaimed withdrawals across all users
+    pub(crate) pending_withdrawal_count: Var<u64>,
+    /// Lowest withdrawal ID that may still be unclaimed
+    pub(crate) queue_head: Var<u64>,
+    /// Address approved to transfer a withdrawal request
+    pub(crate) withdrawal_approvals: Mapping<u64, Option<Address>>,
+    /// CSPR owed to unclaimed withdrawal requests
+    pub(crate) pending_withdrawal_cspr: Var<U512>,
+    /// CSPR undelegated and not yet returned by the auction
+    pub(crate) unbonding_cspr: Var<U512>,
+    /// When the most recent undelegation finishes unbonding
+    pub(crate) unbonding_release_time: Var<u64>,
+    /// Scales payouts of pending withdrawals after realized losses
+    pub(crate) withdrawal_payout_index: Var<U512>,
+}
+
+#[odra::module]
+impl WithdrawalQueue {}
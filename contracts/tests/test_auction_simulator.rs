@@ -0,0 +1,90 @@
+//! Tests for the Auction Simulator contract
+
+use odra::casper_types::{AsymmetricType, PublicKey, U512};
+use odra::host::{Deployer, HostRef};
+
+use ghost_pool::auction_simulator::{AuctionSimulator, AuctionSimulatorInitArgs};
+
+#[cfg(test)]
+mod auction_simulator_tests {
+    use super::*;
+
+    const MINIMUM_DELEGATION: u128 = 500_000_000_000; // 500 CSPR
+
+    fn validator() -> PublicKey {
+        let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+        PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+    }
+
+    fn setup() -> (odra::host::HostEnv, ghost_pool::auction_simulator::AuctionSimulatorHostRef) {
+        let env = odra_test::env();
+
+        let auction = AuctionSimulator::deploy(
+            &env,
+            AuctionSimulatorInitArgs {
+                unbonding_delay: 7,
+                minimum_delegation: U512::from(MINIMUM_DELEGATION),
+            },
+        );
+
+        (env, auction)
+    }
+
+    #[test]
+    fn test_delegate_enforces_minimum() {
+        let (env, auction) = setup();
+        let delegator = env.get_account(0);
+
+        let result = auction
+            .with_tokens(U512::from(MINIMUM_DELEGATION - 1))
+            .try_delegate(validator());
+        assert!(result.is_err(), "Should revert below minimum delegation");
+
+        auction.with_tokens(U512::from(MINIMUM_DELEGATION)).delegate(validator());
+        assert_eq!(auction.delegated_amount(delegator, validator()), U512::from(MINIMUM_DELEGATION));
+    }
+
+    #[test]
+    fn test_undelegate_released_after_delay() {
+        let (env, mut auction) = setup();
+        let delegator = env.get_account(0);
+        let amount = U512::from(MINIMUM_DELEGATION);
+
+        auction.with_tokens(amount).delegate(validator());
+        auction.undelegate(validator(), amount);
+        assert_eq!(auction.unbonding_amount(delegator), amount);
+
+        // Nothing is released before the unbonding delay
+        assert_eq!(auction.advance_eras(6), U512::zero());
+
+        let balance_before = env.balance_of(&delegator);
+        assert_eq!(auction.advance_era(), amount);
+        assert_eq!(env.balance_of(&delegator), balance_before + amount);
+        assert_eq!(auction.unbonding_amount(delegator), U512::zero());
+    }
+
+    #[test]
+    fn test_rewards_and_slashing() {
+        let (env, mut auction) = setup();
+        let delegator = env.get_account(0);
+
+        auction.with_tokens(U512::from(1_000_000_000_000u128)).delegate(validator());
+        auction
+            .with_tokens(U512::from(10_000_000_000u128))
+            .distribute_rewards(delegator, validator());
+        assert_eq!(auction.delegated_amount(delegator, validator()), U512::from(1_010_000_000_000u128));
+
+        let slashed = auction.slash(delegator, validator(), 1000);
+        assert_eq!(slashed, U512::from(101_000_000_000u128));
+        assert_eq!(auction.delegated_amount(delegator, validator()), U512::from(909_000_000_000u128));
+    }
+
+    #[test]
+    fn test_undelegate_more_than_delegated_fails() {
+        let (_env, mut auction) = setup();
+
+        auction.with_tokens(U512::from(MINIMUM_DELEGATION)).delegate(validator());
+        let result = auction.try_undelegate(validator(), U512::from(MINIMUM_DELEGATION + 1));
+        assert!(result.is_err(), "Should revert: insufficient delegation");
+    }
+}
@@ -0,0 +1,139 @@
+//! Tests for the concentrated-liquidity tick and liquidity math
+
+use odra::casper_types::U512;
+
+use ghost_pool::cl_math::{self, MAX_TICK, MIN_TICK};
+
+#[cfg(test)]
+mod cl_math_tests {
+    use super::*;
+
+    fn to_f64(value: U512) -> f64 {
+        let mut result = 0.0;
+        for (i, word) in value.0.iter().enumerate() {
+            result += *word as f64 * 2f64.powi(64 * i as i32);
+        }
+        result
+    }
+
+    fn price_at(tick: i32) -> f64 {
+        1.0001f64.powi(tick)
+    }
+
+    #[test]
+    fn test_sqrt_price_at_tick_bounds() {
+        assert_eq!(cl_math::sqrt_price_at_tick(0), cl_math::q96());
+        assert_eq!(cl_math::min_sqrt_price(), U512::from(4_295_128_739u64));
+        assert_eq!(
+            cl_math::max_sqrt_price(),
+            U512::from_dec_str("1461446703485210103287273052203988822378723970342").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sqrt_price_matches_reference() {
+        for tick in [-500_000, -100_000, -887, -60, -1, 1, 60, 887, 100_000, 500_000] {
+            let actual = to_f64(cl_math::sqrt_price_at_tick(tick)) / 2f64.powi(96);
+            let expected = price_at(tick).sqrt();
+            assert!((actual - expected).abs() / expected < 1e-9, "tick {}: {} vs {}", tick, actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_price_round_trip() {
+        for tick in [MIN_TICK, -887_000, -12_345, -1, 0, 1, 6_931, 443_636, MAX_TICK - 1] {
+            let sqrt_price = cl_math::sqrt_price_at_tick(tick);
+            assert_eq!(cl_math::tick_at_sqrt_price(sqrt_price), tick);
+            // Just below a tick's price is the tick underneath
+            assert_eq!(cl_math::tick_at_sqrt_price(sqrt_price - 1), (tick - 1).max(MIN_TICK));
+        }
+    }
+
+    #[test]
+    fn test_liquidity_round_trip_never_exceeds_amounts() {
+        let cspr = U512::from(1_000_000_000_000u64);
+        let token = U512::from(2_500_000_000_000u64);
+        let sqrt_lower = cl_math::sqrt_price_at_tick(-600);
+        let sqrt_upper = cl_math::sqrt_price_at_tick(600);
+
+        // Below, inside and above the range
+        for tick in [-1200, -600, 0, 300, 600, 1200] {
+            let sqrt_price = cl_math::sqrt_price_at_tick(tick);
+            let liquidity = cl_math::liquidity_for_amounts(sqrt_price, sqrt_lower, sqrt_upper, cspr, token);
+            assert!(liquidity > U512::zero());
+
+            let (cspr_used, token_used) =
+                cl_math::amounts_for_liquidity(sqrt_price, sqrt_lower, sqrt_upper, liquidity, true);
+            assert!(cspr_used <= cspr && token_used <= token, "tick {}", tick);
+            if tick <= -600 {
+                assert_eq!(token_used, U512::zero());
+            }
+            if tick >= 600 {
+                assert_eq!(cspr_used, U512::zero());
+            }
+
+            // Withdrawing rounds down, depositing rounds up
+            let (cspr_out, token_out) =
+                cl_math::amounts_for_liquidity(sqrt_price, sqrt_lower, sqrt_upper, liquidity, false);
+            assert!(cspr_out <= cspr_used && token_out <= token_used);
+        }
+    }
+
+    #[test]
+    fn test_swap_step_reaches_target() {
+        let sqrt_price = cl_math::q96();
+        let sqrt_target = cl_math::sqrt_price_at_tick(-60);
+        let liquidity = U512::from(1_000_000_000_000_000u64);
+
+        let step = cl_math::compute_swap_step(sqrt_price, sqrt_target, liquidity, U512::MAX >> 200, 30, true);
+        assert_eq!(step.sqrt_price_next, sqrt_target);
+        assert_eq!(step.amount_in, cl_math::cspr_delta(sqrt_target, sqrt_price, liquidity, true));
+        assert_eq!(step.amount_out, cl_math::token_delta(sqrt_target, sqrt_price, liquidity, false));
+        // Fee is 0.3% of the gross input
+        let gross = to_f64(step.amount_in + step.fee);
+        assert!((to_f64(step.fee) / gross - 0.003).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_swap_step_partial_matches_reference() {
+        let liquidity = U512::from(1_000_000_000_000_000u64);
+        let sqrt_price = cl_math::q96();
+        let amount = U512::from(1_000_000_000u64);
+
+        // CSPR in: price falls, y = L * (sqrt_p - sqrt_p') with 1/sqrt_p' = 1/sqrt_p + dx/L
+        let step = cl_math::compute_swap_step(sqrt_price, cl_math::min_sqrt_price(), liquidity, amount, 30, true);
+        assert_eq!(step.amount_in + step.fee, amount);
+        let l = to_f64(liquidity);
+        let dx = to_f64(amount) * 0.997;
+        let expected = l * (1.0 - 1.0 / (1.0 + dx / l));
+        assert!((to_f64(step.amount_out) - expected).abs() <= 2.0);
+        assert!(step.amount_out <= amount);
+
+        // Token in: price rises, dx = L * (1/sqrt_p - 1/sqrt_p')
+        let step = cl_math::compute_swap_step(sqrt_price, cl_math::max_sqrt_price(), liquidity, amount, 30, false);
+        assert_eq!(step.amount_in + step.fee, amount);
+        let expected = l * (1.0 - 1.0 / (1.0 + dx / l));
+        assert!((to_f64(step.amount_out) - expected).abs() <= 2.0);
+    }
+
+    #[test]
+    fn test_swap_step_without_liquidity_moves_to_target() {
+        let sqrt_target = cl_math::sqrt_price_at_tick(120);
+        let step =
+            cl_math::compute_swap_step(cl_math::q96(), sqrt_target, U512::zero(), U512::from(1_000u64), 30, false);
+        assert_eq!(step.sqrt_price_next, sqrt_target);
+        assert_eq!(step.amount_in, U512::zero());
+        assert_eq!(step.amount_out, U512::zero());
+        assert_eq!(step.fee, U512::zero());
+    }
+
+    #[test]
+    fn test_wrapping_sub() {
+        assert_eq!(cl_math::wrapping_sub(U512::from(5u64), U512::from(3u64)), U512::from(2u64));
+        assert_eq!(cl_math::wrapping_sub(U512::zero(), U512::one()), U512::MAX);
+        // Differences survive wrap-around of the accumulator
+        let before = U512::MAX - U512::from(10u64);
+        let after = before.overflowing_add(U512::from(25u64)).0;
+        assert_eq!(cl_math::wrapping_sub(after, before), U512::from(25u64));
+    }
+}
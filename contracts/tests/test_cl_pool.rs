@@ -0,0 +1,388 @@
+//! Tests for the concentrated-liquidity pool
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::auction_simulator::{AuctionSimulator, AuctionSimulatorInitArgs};
+use ghost_pool::cl_math;
+use ghost_pool::cl_pool::{GhostPoolClPool, GhostPoolClPoolInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+use ghost_pool::types::{PoolConfig, UNBONDING_PERIOD_MS};
+
+#[cfg(test)]
+mod cl_pool_tests {
+    use super::*;
+
+    fn test_validator() -> PublicKey {
+        let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+        PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+    }
+
+    /// Setup a pool with tick spacing 60, starting at a price of 1 (tick 0)
+    fn setup() -> (
+        odra::host::HostEnv,
+        ghost_pool::cl_pool::GhostPoolClPoolHostRef,
+        ghost_pool::test_token::TestTokenHostRef,
+    ) {
+        let env = odra_test::env();
+        env.set_caller(env.get_account(0));
+
+        let token = TestToken::deploy(
+            &env,
+            TestTokenInitArgs {
+                name: "Test USDC".to_string(),
+                symbol: "tUSDC".to_string(),
+                decimals: 6,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let pool = GhostPoolClPool::deploy(
+            &env,
+            GhostPoolClPoolInitArgs {
+                token_address: token.address(),
+                validator: test_validator(),
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+                tick_spacing: 60,
+                sqrt_price_x96: cl_math::q96(),
+            },
+        );
+
+        (env, pool, token)
+    }
+
+    /// Setup with staking routed to an auction simulator
+    fn setup_with_simulator() -> (
+        odra::host::HostEnv,
+        ghost_pool::cl_pool::GhostPoolClPoolHostRef,
+        ghost_pool::test_token::TestTokenHostRef,
+        ghost_pool::auction_simulator::AuctionSimulatorHostRef,
+    ) {
+        let (env, mut pool, token) = setup();
+        let auction = AuctionSimulator::deploy(
+            &env,
+            AuctionSimulatorInitArgs {
+                unbonding_delay: 7,
+                minimum_delegation: U512::from(500_000_000_000u128),
+            },
+        );
+        pool.set_auction_simulator(auction.address());
+        (env, pool, token, auction)
+    }
+
+    /// Helper: open a position with up to `cspr` and `token_amount`
+    fn mint_position(
+        pool: &mut ghost_pool::cl_pool::GhostPoolClPoolHostRef,
+        token: &mut ghost_pool::test_token::TestTokenHostRef,
+        tick_lower: i32,
+        tick_upper: i32,
+        cspr: u128,
+        token_amount: u128,
+    ) -> u64 {
+        token.approve(&pool.address(), &U256::from(token_amount));
+        let token_amount = U512::from(token_amount);
+        if cspr == 0 {
+            pool.mint(tick_lower, tick_upper, token_amount, U512::zero(), U512::zero())
+        } else {
+            pool.with_tokens(U512::from(cspr))
+                .mint(tick_lower, tick_upper, token_amount, U512::zero(), U512::zero())
+        }
+    }
+
+    fn within(actual: U512, expected: u128, tolerance: u128) -> bool {
+        let actual = actual.as_u128();
+        actual + tolerance >= expected && actual <= expected + tolerance
+    }
+
+    #[test]
+    fn test_mint_in_range_uses_both_assets() {
+        let (env, mut pool, mut token) = setup();
+        let user = env.get_account(0);
+        let cspr_before = env.balance_of(&user);
+        let token_before = token.balance_of(&user);
+
+        // Twice the CSPR needed at a price of 1; the surplus is refunded
+        let id = mint_position(&mut pool, &mut token, -600, 600, 2_000_000_000, 1_000_000_000);
+
+        assert_eq!(pool.position_owner(id), Some(user));
+        assert_eq!(pool.positions_of(user, 0, 10), vec![id]);
+        let position = pool.get_position(id);
+        assert!(position.liquidity > U512::zero());
+        assert_eq!(pool.get_slot().liquidity, position.liquidity);
+        assert_eq!(pool.initialized_ticks(cl_math::MIN_TICK, 10), vec![-600, 600]);
+
+        let balances = pool.get_balances();
+        assert_eq!(U512::from((token_before - token.balance_of(&user)).as_u128()), balances.reserve_token);
+        assert_eq!(cspr_before - env.balance_of(&user), balances.reserve_cspr);
+        assert!(within(balances.reserve_cspr, 1_000_000_000, 10));
+        assert!(within(balances.reserve_token, 1_000_000_000, 10));
+        // Below the minimum delegation everything stays buffered
+        assert_eq!(balances.buffer_cspr, balances.reserve_cspr);
+    }
+
+    #[test]
+    fn test_out_of_range_mint_is_single_sided() {
+        let (_env, mut pool, mut token) = setup();
+
+        // Above the price: CSPR only
+        let above = mint_position(&mut pool, &mut token, 600, 1200, 1_000_000_000, 0);
+        let (cspr, tokens) = pool.get_position_amounts(above);
+        assert!(within(cspr, 1_000_000_000, 10));
+        assert_eq!(tokens, U512::zero());
+
+        // Below the price: token only
+        let below = mint_position(&mut pool, &mut token, -1200, -600, 0, 1_000_000_000);
+        let (cspr, tokens) = pool.get_position_amounts(below);
+        assert_eq!(cspr, U512::zero());
+        assert!(within(tokens, 1_000_000_000, 10));
+
+        // Neither is in range
+        assert_eq!(pool.get_slot().liquidity, U512::zero());
+    }
+
+    #[test]
+    fn test_invalid_ranges_rejected() {
+        let (_env, mut pool, mut token) = setup();
+        token.approve(&pool.address(), &U256::from(1_000_000_000u128));
+        let amount = U512::from(1_000_000_000u128);
+
+        for (lower, upper) in [(600, -600), (0, 0), (-601, 600), (-600, 610)] {
+            let result = pool
+                .with_tokens(amount)
+                .try_mint(lower, upper, amount, U512::zero(), U512::zero());
+            assert!(result.is_err(), "Should revert: range ({}, {})", lower, upper);
+        }
+
+        // A range above the price needs CSPR
+        let result = pool.try_mint(600, 1200, amount, U512::zero(), U512::zero());
+        assert!(result.is_err(), "Should revert: no liquidity");
+    }
+
+    #[test]
+    fn test_swap_fees_go_to_in_range_positions() {
+        let (env, mut pool, mut token) = setup();
+        let in_range = mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let out_of_range = mint_position(&mut pool, &mut token, 600, 1200, 1_000_000_000, 0);
+
+        let token_in = U512::from(100_000_000u128);
+        let quoted = pool.quote_token_for_cspr(token_in);
+        token.approve(&pool.address(), &U256::from(token_in.as_u128()));
+        let cspr_out = pool.swap_token_for_cspr(token_in, quoted);
+        assert_eq!(cspr_out, quoted);
+        assert!(pool.get_slot().tick > 0 && pool.get_slot().tick < 600);
+
+        // 0.3% of the input, all to the only position in range
+        let (_, fees) = pool.get_position_owed(in_range);
+        assert!(within(fees, 300_000, 2));
+        assert_eq!(pool.get_position_owed(out_of_range), (U512::zero(), U512::zero()));
+
+        let user = env.get_account(0);
+        let token_before = token.balance_of(&user);
+        let (cspr_paid, token_paid) = pool.collect(in_range);
+        assert_eq!(cspr_paid, U512::zero());
+        assert_eq!(token_paid, fees);
+        assert_eq!(token.balance_of(&user) - token_before, U256::from(fees.as_u128()));
+    }
+
+    #[test]
+    fn test_swap_crosses_ticks() {
+        let (_env, mut pool, mut token) = setup();
+        mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let upper = mint_position(&mut pool, &mut token, 600, 1200, 1_000_000_000, 0);
+
+        // Enough tokens to drain the first range's CSPR and enter the second
+        let token_in = U512::from(2_000_000_000u128);
+        let quoted = pool.quote_token_for_cspr(token_in);
+        token.approve(&pool.address(), &U256::from(token_in.as_u128()));
+        let cspr_out = pool.swap_token_for_cspr(token_in, quoted);
+        assert_eq!(cspr_out, quoted);
+        assert!(cspr_out > U512::from(1_000_000_000u128));
+
+        let slot = pool.get_slot();
+        assert!(slot.tick >= 600 && slot.tick < 1200);
+        assert_eq!(slot.liquidity, pool.get_position(upper).liquidity);
+
+        // Selling CSPR back crosses down into the first range again
+        let cspr_in = U512::from(1_500_000_000u128);
+        let quoted = pool.quote_cspr_for_token(cspr_in);
+        let token_out = pool.with_tokens(cspr_in).swap_cspr_for_token(quoted);
+        assert_eq!(token_out, quoted);
+        assert!(pool.get_slot().tick < 600);
+    }
+
+    #[test]
+    fn test_swap_crosses_many_initialized_ticks() {
+        let (_env, mut pool, mut token) = setup();
+
+        // 50 adjacent ranges spanning several bitmap words
+        for lower in (-30_000..30_000).step_by(1200) {
+            mint_position(&mut pool, &mut token, lower, lower + 1200, 1_000_000_000, 1_000_000_000);
+        }
+        let ticks = pool.initialized_ticks(cl_math::MIN_TICK, 100);
+        assert_eq!(ticks, (-30_000..=30_000).step_by(1200).collect::<Vec<i32>>());
+        assert_eq!(pool.initialized_ticks(1, 3), vec![1200, 2400, 3600]);
+
+        // Buying all the CSPR crosses every tick above the price
+        let token_in = U512::from(500_000_000_000u128);
+        let quoted = pool.quote_token_for_cspr(token_in);
+        token.approve(&pool.address(), &U256::from(token_in.as_u128()));
+        assert_eq!(pool.swap_token_for_cspr(token_in, quoted), quoted);
+        let slot = pool.get_slot();
+        assert!(slot.tick >= 30_000);
+        assert_eq!(slot.liquidity, U512::zero());
+
+        // Selling it back crosses all of them downwards
+        let cspr_in = U512::from(500_000_000_000u128);
+        let quoted = pool.quote_cspr_for_token(cspr_in);
+        assert_eq!(pool.with_tokens(cspr_in).swap_cspr_for_token(quoted), quoted);
+        let slot = pool.get_slot();
+        assert!(slot.tick < -30_000);
+        assert_eq!(slot.liquidity, U512::zero());
+    }
+
+    #[test]
+    fn test_swap_cspr_beyond_liquidity_refunds_surplus() {
+        let (env, mut pool, mut token) = setup();
+        mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let user = env.get_account(0);
+
+        // Far more CSPR than the range can absorb
+        let cspr_in = U512::from(100_000_000_000u128);
+        let balance_before = env.balance_of(&user);
+        let token_out = pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+
+        assert!(within(token_out, 1_000_000_000, 10));
+        let spent = balance_before - env.balance_of(&user);
+        assert!(spent < U512::from(3_000_000_000u128));
+        assert_eq!(pool.get_slot().sqrt_price_x96, cl_math::min_sqrt_price());
+    }
+
+    #[test]
+    fn test_decrease_collect_and_burn() {
+        let (env, mut pool, mut token) = setup();
+        let id = mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let user = env.get_account(0);
+
+        let liquidity = pool.get_position(id).liquidity;
+        let (cspr, tokens) = pool.decrease_liquidity(id, liquidity, U512::zero(), U512::zero());
+        assert!(within(cspr, 1_000_000_000, 10));
+        assert!(within(tokens, 1_000_000_000, 10));
+        assert_eq!(pool.get_slot().liquidity, U512::zero());
+        assert!(pool.initialized_ticks(cl_math::MIN_TICK, 10).is_empty());
+
+        // Not empty until collected
+        assert!(pool.try_burn(id).is_err(), "Should revert: owed balances");
+
+        let cspr_before = env.balance_of(&user);
+        assert_eq!(pool.collect(id), (cspr, tokens));
+        assert_eq!(env.balance_of(&user) - cspr_before, cspr);
+
+        pool.burn(id);
+        assert_eq!(pool.position_owner(id), None);
+        assert_eq!(pool.position_count(user), 0);
+        assert!(pool.try_get_position(id).is_err(), "Should revert: burned");
+    }
+
+    #[test]
+    fn test_staked_cspr_unlocks_after_unbonding() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        let user = env.get_account(0);
+
+        // 1000 CSPR above the price: 90% is staked
+        let id = mint_position(&mut pool, &mut token, 600, 1200, 1_000_000_000_000, 0);
+        let balances = pool.get_balances();
+        assert!(within(balances.staked_cspr, 900_000_000_000, 10));
+        assert!(within(balances.buffer_cspr, 100_000_000_000, 10));
+
+        let liquidity = pool.get_position(id).liquidity;
+        let (cspr, _) = pool.decrease_liquidity(id, liquidity, U512::zero(), U512::zero());
+        assert!(pool.get_position(id).cspr_unlock_time > env.block_time());
+
+        // Nothing to pay until the undelegated CSPR is back
+        assert_eq!(pool.collect(id), (U512::zero(), U512::zero()));
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        auction.advance_eras(7);
+
+        let cspr_before = env.balance_of(&user);
+        assert_eq!(pool.collect(id), (cspr, U512::zero()));
+        assert_eq!(env.balance_of(&user) - cspr_before, cspr);
+    }
+
+    #[test]
+    fn test_compound_rewards_in_range_liquidity() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        let in_range = mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let out_of_range = mint_position(&mut pool, &mut token, 600, 1200, 1_000_000_000_000, 0);
+
+        let pool_addr = pool.address();
+        auction
+            .with_tokens(U512::from(10_000_000_000u128))
+            .distribute_rewards(pool_addr, test_validator());
+
+        // 10% to the treasury, the rest to the liquidity in range
+        let rewards = pool.compound();
+        assert_eq!(rewards, U512::from(9_000_000_000u128));
+        assert_eq!(pool.get_balances().protocol_fees_cspr, U512::from(1_000_000_000u128));
+        let (owed, _) = pool.get_position_owed(in_range);
+        assert!(within(owed, 9_000_000_000, 10));
+        assert_eq!(pool.get_position_owed(out_of_range), (U512::zero(), U512::zero()));
+
+        // Swap fees earned meanwhile are not held back with the rewards
+        pool.with_tokens(U512::from(100_000_000u128)).swap_cspr_for_token(U512::zero());
+        let (owed_with_fees, _) = pool.get_position_owed(in_range);
+        let swap_fees = owed_with_fees - owed;
+        assert!(swap_fees > U512::zero());
+        assert_eq!(pool.collect(in_range).0, swap_fees);
+
+        // Rewards unlock once they are back from the auction
+        assert_eq!(pool.collect(in_range), (U512::zero(), U512::zero()));
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        auction.advance_eras(7);
+        let (cspr_paid, _) = pool.collect(in_range);
+        assert_eq!(cspr_paid, owed);
+
+        let treasury = env.get_account(1);
+        let treasury_before = env.balance_of(&treasury);
+        pool.claim_protocol_fees();
+        assert_eq!(env.balance_of(&treasury) - treasury_before, U512::from(1_000_000_000u128));
+    }
+
+    #[test]
+    fn test_transfer_and_approval() {
+        let (env, mut pool, mut token) = setup();
+        let id = mint_position(&mut pool, &mut token, -600, 600, 1_000_000_000, 1_000_000_000);
+        let (owner, spender, recipient) = (env.get_account(0), env.get_account(2), env.get_account(3));
+
+        env.set_caller(spender);
+        assert!(pool.try_transfer_position(id, recipient).is_err(), "Should revert: not approved");
+
+        env.set_caller(owner);
+        pool.approve_position(id, Some(spender));
+        assert_eq!(pool.get_position_approved(id), Some(spender));
+
+        env.set_caller(spender);
+        pool.transfer_position(id, recipient);
+        assert_eq!(pool.position_owner(id), Some(recipient));
+        assert_eq!(pool.positions_of(recipient, 0, 10), vec![id]);
+        assert_eq!(pool.position_count(owner), 0);
+
+        // The approval does not survive the transfer
+        assert_eq!(pool.get_position_approved(id), None);
+        assert!(pool.try_collect(id).is_err(), "Should revert: approval cleared");
+    }
+
+    #[test]
+    fn test_admin_only_config() {
+        let (env, mut pool, _token) = setup();
+
+        env.set_caller(env.get_account(2));
+        assert!(pool.try_set_config(PoolConfig::with_defaults()).is_err(), "Should revert: not admin");
+
+        env.set_caller(env.get_account(0));
+        let mut config = PoolConfig::with_defaults();
+        config.swap_fee_bps = U256::from(2000u64);
+        assert!(pool.try_set_config(config).is_err(), "Should revert: fee too high");
+    }
+}
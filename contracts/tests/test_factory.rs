@@ -0,0 +1,271 @@
+//! Tests for the Ghost Pool Factory
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256};
+use odra::host::Deployer;
+use odra::prelude::*;
+
+use ghost_pool::factory::{GhostPoolFactory, GhostPoolFactoryInitArgs};
+use ghost_pool::pair::{GhostPoolPair, GhostPoolPairInitArgs};
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+use ghost_pool::types::{CurveType, PoolConfig};
+
+#[cfg(test)]
+mod factory_tests {
+    use super::*;
+
+    fn validators() -> Vec<PublicKey> {
+        [
+            "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b",
+            "0106ca7c39cd272dbf21a86eeb3b36b7c26e2e9b94af64292419f7862936bca2ca",
+        ]
+        .iter()
+        .map(|hex| PublicKey::from_hex(hex).expect("Invalid validator key"))
+        .collect()
+    }
+
+    fn setup() -> (odra::host::HostEnv, ghost_pool::factory::GhostPoolFactoryHostRef) {
+        let env = odra_test::env();
+        env.set_caller(env.get_account(0));
+
+        let factory = GhostPoolFactory::deploy(
+            &env,
+            GhostPoolFactoryInitArgs {
+                admin: env.get_account(0),
+                treasury: env.get_account(1),
+                validators: validators(),
+            },
+        );
+
+        (env, factory)
+    }
+
+    /// Helper: deploy a token and a pool administered by `admin`
+    fn deploy_pool(
+        env: &odra::host::HostEnv,
+        symbol: &str,
+        admin: Address,
+    ) -> (ghost_pool::test_token::TestTokenHostRef, ghost_pool::pool::GhostPoolPoolHostRef) {
+        let token = TestToken::deploy(
+            env,
+            TestTokenInitArgs {
+                name: symbol.to_string(),
+                symbol: symbol.to_string(),
+                decimals: 9,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let pool = GhostPoolPool::deploy(
+            env,
+            GhostPoolPoolInitArgs {
+                token_address: token.address(),
+                validator: validators()[0].clone(),
+                treasury: env.get_account(5),
+                admin,
+            },
+        );
+        (token, pool)
+    }
+
+    #[test]
+    fn test_register_pool() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (token, pool) = deploy_pool(&env, "tUSDC", factory_addr);
+
+        let index = factory.register_pool(pool.address());
+
+        assert_eq!(index, 0);
+        assert_eq!(factory.get_pool(token.address()), Some(pool.address()));
+        assert_eq!(factory.all_pools_length(), 1);
+        assert_eq!(factory.all_pools(0, 10), vec![pool.address()]);
+        assert_eq!(factory.get_pool(env.get_account(3)), None);
+    }
+
+    #[test]
+    fn test_pool_inherits_defaults() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+
+        let mut config = PoolConfig::with_defaults();
+        config.swap_fee_bps = U256::from(25u64);
+        config.protocol_swap_fee_bps = U256::from(1666u64);
+        factory.set_default_config(config.clone());
+        factory.set_treasury(env.get_account(2));
+
+        let (_token, pool) = deploy_pool(&env, "tUSDC", factory_addr);
+        factory.register_pool(pool.address());
+
+        assert_eq!(pool.get_config(), config);
+        assert_eq!(pool.treasury(), env.get_account(2));
+        assert_eq!(pool.admin(), factory_addr);
+    }
+
+    #[test]
+    fn test_validators_assigned_round_robin() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let validators = validators();
+
+        let mut assigned = Vec::new();
+        for symbol in ["tA", "tB", "tC"] {
+            let (_token, pool) = deploy_pool(&env, symbol, factory_addr);
+            assert_eq!(factory.next_validator(), validators[assigned.len() % 2]);
+            factory.register_pool(pool.address());
+            assigned.push(pool.validator());
+        }
+
+        assert_eq!(assigned, vec![validators[0].clone(), validators[1].clone(), validators[0].clone()]);
+        assert_eq!(factory.all_pools(1, 10).len(), 2);
+    }
+
+    #[test]
+    fn test_duplicate_token_rejected() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (token, pool) = deploy_pool(&env, "tUSDC", factory_addr);
+        factory.register_pool(pool.address());
+
+        let duplicate = GhostPoolPool::deploy(
+            &env,
+            GhostPoolPoolInitArgs {
+                token_address: token.address(),
+                validator: validators()[0].clone(),
+                treasury: env.get_account(1),
+                admin: factory_addr,
+            },
+        );
+        let result = factory.try_register_pool(duplicate.address());
+        assert!(result.is_err(), "Should revert: pool exists for token");
+    }
+
+    #[test]
+    fn test_register_requires_factory_admin_and_ownership() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+
+        // Pool administered by someone else
+        let (_token, foreign_pool) = deploy_pool(&env, "tUSDC", env.get_account(0));
+        let result = factory.try_register_pool(foreign_pool.address());
+        assert!(result.is_err(), "Should revert: pool not owned by factory");
+
+        let (_token, pool) = deploy_pool(&env, "tDAI", factory_addr);
+        env.set_caller(env.get_account(2));
+        let result = factory.try_register_pool(pool.address());
+        assert!(result.is_err(), "Should revert: not admin");
+    }
+
+    #[test]
+    fn test_invalid_default_config_rejected() {
+        let (_env, mut factory) = setup();
+
+        let mut config = PoolConfig::with_defaults();
+        config.swap_fee_bps = U256::from(5000u64);
+        let result = factory.try_set_default_config(config);
+        assert!(result.is_err(), "Should revert: swap fee too high");
+    }
+
+    #[test]
+    fn test_register_pair() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (token_a, _) = deploy_pool(&env, "wUSDC", env.get_account(0));
+        let (token_b, _) = deploy_pool(&env, "wETH", env.get_account(0));
+        let (token_a, token_b) = (token_a.address(), token_b.address());
+
+        factory.set_treasury(env.get_account(2));
+        let pair = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a,
+                token_b,
+                treasury: env.get_account(5),
+                admin: factory_addr,
+            },
+        );
+        assert_eq!(factory.register_pair(pair.address()), 0);
+
+        // Lookup works in either order
+        assert_eq!(factory.get_pair(token_a, token_b), Some(pair.address()));
+        assert_eq!(factory.get_pair(token_b, token_a), Some(pair.address()));
+        assert_eq!(factory.all_pairs(0, 10), vec![pair.address()]);
+        assert_eq!(pair.treasury(), env.get_account(2));
+
+        // A second pair for the same tokens is rejected
+        let duplicate = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token_b,
+                token_b: token_a,
+                treasury: env.get_account(5),
+                admin: factory_addr,
+            },
+        );
+        let result = factory.try_register_pair(duplicate.address());
+        assert!(result.is_err(), "Should revert: pair exists");
+    }
+
+    #[test]
+    fn test_stable_curve_forwarded_to_pool() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (_, pool) = deploy_pool(&env, "sCSPR", factory_addr);
+        factory.register_pool(pool.address());
+
+        env.set_caller(env.get_account(1));
+        let result = factory.try_set_stable_curve(pool.address(), 50);
+        assert!(result.is_err(), "Should revert: not admin");
+
+        env.set_caller(env.get_account(0));
+        factory.set_stable_curve(pool.address(), 50);
+        assert_eq!(pool.curve_type(), CurveType::Stable);
+        assert_eq!(pool.get_amp(), 50);
+    }
+
+    #[test]
+    fn test_transfer_admin_hands_pool_over() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (_, mut pool) = deploy_pool(&env, "tUSDC", factory_addr);
+        factory.register_pool(pool.address());
+        let new_admin = env.get_account(3);
+
+        env.set_caller(env.get_account(1));
+        let result = factory.try_transfer_admin(pool.address(), new_admin);
+        assert!(result.is_err(), "Should revert: not admin");
+
+        env.set_caller(env.get_account(0));
+        factory.transfer_admin(pool.address(), new_admin);
+        assert_eq!(pool.admin(), new_admin);
+        assert_eq!(factory.get_pool(pool.token_address()), Some(pool.address()));
+
+        // The new admin tunes the pool directly; the factory no longer can
+        env.set_caller(new_admin);
+        pool.set_treasury(env.get_account(4));
+        assert_eq!(pool.treasury(), env.get_account(4));
+        env.set_caller(env.get_account(0));
+        let result = factory.try_set_stable_curve(pool.address(), 50);
+        assert!(result.is_err(), "Should revert: factory is no longer admin");
+    }
+
+    #[test]
+    fn test_transfer_admin_hands_pair_over() {
+        let (env, mut factory) = setup();
+        let factory_addr = factory.address();
+        let (token_a, _) = deploy_pool(&env, "wUSDC", env.get_account(0));
+        let (token_b, _) = deploy_pool(&env, "wETH", env.get_account(0));
+        let pair = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token_a.address(),
+                token_b: token_b.address(),
+                treasury: env.get_account(5),
+                admin: factory_addr,
+            },
+        );
+        factory.register_pair(pair.address());
+
+        factory.transfer_admin(pair.address(), env.get_account(3));
+        assert_eq!(pair.admin(), env.get_account(3));
+    }
+}
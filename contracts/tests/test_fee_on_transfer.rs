@@ -0,0 +1,226 @@
+//! Tests for balance-delta accounting with fee-on-transfer and rebasing paired tokens
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::fee_token::{FeeToken, FeeTokenInitArgs};
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::types::TokenAccounting;
+
+/// Transfer fee charged by the test token (1%)
+const FEE_BPS: u64 = 100;
+
+fn test_validator() -> PublicKey {
+    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+    PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+}
+
+fn after_fee(amount: u64) -> U512 {
+    U512::from(amount - amount * FEE_BPS / 10000)
+}
+
+/// Deploy a pool paired with a 1% fee token, in balance-delta mode unless `exact`
+fn setup(exact: bool) -> (
+    odra::host::HostEnv,
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::fee_token::FeeTokenHostRef,
+) {
+    let env = odra_test::env();
+    let admin = env.get_account(0);
+    env.set_caller(admin);
+
+    let mut token = FeeToken::deploy(
+        &env,
+        FeeTokenInitArgs {
+            name: "Fee Token".to_string(),
+            symbol: "FEE".to_string(),
+            decimals: 6,
+            initial_supply: U256::from(1_000_000_000_000u128),
+            fee_bps: FEE_BPS,
+        },
+    );
+    let mut pool = GhostPoolPool::deploy(
+        &env,
+        GhostPoolPoolInitArgs {
+            token_address: token.address(),
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin,
+        },
+    );
+    if !exact {
+        pool.set_token_accounting(TokenAccounting::BalanceDelta);
+    }
+
+    // Trading account holds tokens of its own
+    token.transfer(&env.get_account(2), &U256::from(10_000_000_000u128));
+    token.approve(&pool.address(), &U256::from(10_000_000_000u128));
+
+    (env, pool, token)
+}
+
+/// Seed the pool with 1000 CSPR and 1000 tokens (before the transfer fee)
+fn seed(pool: &ghost_pool::pool::GhostPoolPoolHostRef) {
+    pool.with_tokens(U512::from(1_000_000_000_000u128))
+        .add_liquidity(U512::from(1_000_000_000u128), U512::zero(), U512::zero(), U512::zero());
+}
+
+fn pool_token_balance(
+    pool: &ghost_pool::pool::GhostPoolPoolHostRef,
+    token: &ghost_pool::fee_token::FeeTokenHostRef,
+) -> U512 {
+    U512::from(token.balance_of(&pool.address()).as_u128())
+}
+
+#[cfg(test)]
+mod fee_on_transfer_tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_mode_overcredits_fee_token() {
+        let (_env, pool, token) = setup(true);
+        seed(&pool);
+
+        // The reserve assumes the full amount arrived
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, U512::from(1_000_000_000u64));
+        assert_eq!(pool_token_balance(&pool, &token), after_fee(1_000_000_000));
+        assert_eq!(pool.token_accounting(), TokenAccounting::Exact);
+    }
+
+    #[test]
+    fn test_fee_token_mints_once_and_keeps_fees() {
+        let (env, _pool, token) = setup(true);
+
+        assert_eq!(token.total_supply(), U256::from(1_000_000_000_000u128));
+        // Setup's transfer to account 2 paid its fee to the token contract
+        assert_eq!(token.balance_of(&env.get_account(2)), U256::from(after_fee(10_000_000_000).as_u128()));
+        assert_eq!(token.balance_of(&token.address()), U256::from(100_000_000u64));
+    }
+
+    #[test]
+    fn test_balance_delta_credits_received_on_add_liquidity() {
+        let (_env, pool, token) = setup(false);
+        let (_, tokens_used, lp) = pool
+            .with_tokens(U512::from(1_000_000_000_000u128))
+            .add_liquidity(U512::from(1_000_000_000u128), U512::zero(), U512::zero(), U512::zero());
+
+        assert_eq!(tokens_used, after_fee(1_000_000_000));
+        assert!(lp > U512::zero());
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, after_fee(1_000_000_000));
+        assert_eq!(reserve_token, pool_token_balance(&pool, &token));
+        assert_eq!(pool.get_pool_state().token_accounting, TokenAccounting::BalanceDelta);
+    }
+
+    #[test]
+    fn test_balance_delta_refunds_cspr_for_fee_shortfall() {
+        let (env, pool, _token) = setup(false);
+        seed(&pool);
+        let admin = env.get_account(0);
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+
+        // 100 CSPR and the tokens matching it; the fee shaves 1% off the tokens on the way in
+        let cspr_attached = U512::from(100_000_000_000u64);
+        let tokens_desired = cspr_attached * reserve_token / reserve_cspr;
+        let balance_before = env.balance_of(&admin);
+        let (cspr_used, tokens_used, _) = pool
+            .with_tokens(cspr_attached)
+            .add_liquidity(tokens_desired, U512::zero(), U512::zero(), U512::zero());
+
+        assert_eq!(tokens_used, after_fee(tokens_desired.as_u64()));
+        assert_eq!(cspr_used, tokens_used * reserve_cspr / reserve_token);
+        assert!(cspr_used < cspr_attached);
+        assert_eq!(env.balance_of(&admin), balance_before - cspr_used);
+        assert_eq!(pool.get_reserves(), (reserve_cspr + cspr_used, reserve_token + tokens_used));
+    }
+
+    #[test]
+    fn test_balance_delta_swap_prices_received_amount() {
+        let (env, mut pool, mut token) = setup(false);
+        seed(&pool);
+        let trader = env.get_account(2);
+
+        env.set_caller(trader);
+        token.approve(&pool.address(), &U256::from(10_000_000u64));
+        let expected = pool.quote_token_for_cspr(after_fee(10_000_000));
+        let cspr_out = pool.swap_token_for_cspr(U512::from(10_000_000u64), U512::zero());
+
+        assert_eq!(cspr_out, expected);
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, pool_token_balance(&pool, &token));
+    }
+
+    #[test]
+    fn test_supporting_fee_swap_in_exact_mode() {
+        let (env, mut pool, mut token) = setup(true);
+
+        // Fee switched on only after an exact seed, so the reserve starts in line
+        token.set_fee_bps(0);
+        seed(&pool);
+        token.set_fee_bps(FEE_BPS);
+
+        let trader = env.get_account(2);
+        env.set_caller(trader);
+        token.approve(&pool.address(), &U256::from(10_000_000u64));
+        let expected = pool.quote_token_for_cspr(after_fee(10_000_000));
+        let cspr_out = pool.swap_token_for_cspr_supporting_fee(U512::from(10_000_000u64), U512::zero());
+
+        assert_eq!(cspr_out, expected);
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, pool_token_balance(&pool, &token));
+    }
+
+    #[test]
+    fn test_supporting_fee_swap_out_checks_amount_received() {
+        let (env, pool, token) = setup(false);
+        seed(&pool);
+        let trader = env.get_account(2);
+        let cspr_in = U512::from(10_000_000_000u64);
+
+        // The pool sends the quoted amount; the trader gets it minus the fee
+        let quoted = pool.quote_cspr_for_token(cspr_in);
+        let received = quoted - quoted * U512::from(FEE_BPS) / U512::from(10000u64);
+
+        env.set_caller(trader);
+        let result = pool
+            .with_tokens(cspr_in)
+            .try_swap_cspr_for_token_supporting_fee(received + U512::one());
+        assert!(result.is_err(), "Should revert: received less than the minimum");
+
+        let balance_before = token.balance_of(&trader);
+        let token_out = pool.with_tokens(cspr_in).swap_cspr_for_token_supporting_fee(received);
+        assert_eq!(token_out, received);
+        assert_eq!(token.balance_of(&trader), balance_before + U256::from(received.as_u128()));
+    }
+
+    #[test]
+    fn test_sync_follows_rebases() {
+        let (env, mut pool, mut token) = setup(false);
+        seed(&pool);
+        let pool_address = pool.address();
+
+        // Positive rebase
+        token.mint(&pool_address, &U256::from(50_000_000u64));
+        env.set_caller(env.get_account(0));
+        pool.sync();
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, after_fee(1_000_000_000) + U512::from(50_000_000u64));
+
+        // Negative rebase
+        token.burn(&pool_address, &U256::from(100_000_000u64));
+        pool.sync();
+        let (_, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_token, pool_token_balance(&pool, &token));
+    }
+
+    #[test]
+    fn test_set_token_accounting_requires_admin() {
+        let (env, mut pool, _token) = setup(true);
+
+        env.set_caller(env.get_account(2));
+        let result = pool.try_set_token_accounting(TokenAccounting::BalanceDelta);
+        assert!(result.is_err(), "Should revert: not admin");
+    }
+}
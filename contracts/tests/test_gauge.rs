@@ -0,0 +1,328 @@
+//! Tests for the LP staking gauge
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::gauge::{GhostPoolGauge, GhostPoolGaugeInitArgs};
+use ghost_pool::pair::{GhostPoolPair, GhostPoolPairInitArgs};
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+
+/// Reward tokens (6 decimals) emitted per second by test campaigns
+const RATE: u64 = 1_000_000;
+/// LP shares each staker receives
+const STAKE: u64 = 100_000_000;
+
+fn test_validator() -> PublicKey {
+    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+    PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+}
+
+fn seconds(count: u64) -> u64 {
+    count * 1000
+}
+
+fn deploy_token(env: &odra::host::HostEnv, symbol: &str) -> ghost_pool::test_token::TestTokenHostRef {
+    TestToken::deploy(
+        env,
+        TestTokenInitArgs {
+            name: symbol.to_string(),
+            symbol: symbol.to_string(),
+            decimals: 6,
+            initial_supply: U256::from(1_000_000_000_000u128),
+        },
+    )
+}
+
+/// Deploy a funded pool and a gauge for its LP token; accounts 2 and 3 hold LP shares
+fn setup() -> (
+    odra::host::HostEnv,
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::gauge::GhostPoolGaugeHostRef,
+    ghost_pool::test_token::TestTokenHostRef,
+) {
+    let env = odra_test::env();
+    let admin = env.get_account(0);
+    env.set_caller(admin);
+
+    let mut token = deploy_token(&env, "tUSDC");
+    let mut pool = GhostPoolPool::deploy(
+        &env,
+        GhostPoolPoolInitArgs {
+            token_address: token.address(),
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin,
+        },
+    );
+    token.approve(&pool.address(), &U256::from(1_000_000_000u128));
+    pool.with_tokens(U512::from(1_000_000_000_000u128))
+        .add_liquidity(U512::from(1_000_000_000u128), U512::zero(), U512::zero(), U512::zero());
+    pool.transfer(&env.get_account(2), &U256::from(STAKE));
+    pool.transfer(&env.get_account(3), &U256::from(STAKE));
+
+    let reward_token = deploy_token(&env, "GHOST");
+    let gauge = GhostPoolGauge::deploy(
+        &env,
+        GhostPoolGaugeInitArgs {
+            staking_token: pool.address(),
+            reward_token: reward_token.address(),
+            admin,
+        },
+    );
+
+    (env, pool, gauge, reward_token)
+}
+
+/// Schedule a campaign emitting RATE per second from `start` for `duration` seconds
+fn schedule(
+    env: &odra::host::HostEnv,
+    gauge: &mut ghost_pool::gauge::GhostPoolGaugeHostRef,
+    reward_token: &mut ghost_pool::test_token::TestTokenHostRef,
+    start: u64,
+    duration: u64,
+) -> u32 {
+    env.set_caller(env.get_account(0));
+    reward_token.approve(&gauge.address(), &U256::from(RATE * duration));
+    gauge.schedule_campaign(U512::from(RATE), start, start + seconds(duration))
+}
+
+/// Stake `amount` LP shares as `user`
+fn stake(
+    env: &odra::host::HostEnv,
+    pool: &mut ghost_pool::pool::GhostPoolPoolHostRef,
+    gauge: &mut ghost_pool::gauge::GhostPoolGaugeHostRef,
+    user: Address,
+    amount: u64,
+) {
+    env.set_caller(user);
+    pool.approve(&gauge.address(), &U256::from(amount));
+    gauge.deposit(U512::from(amount));
+}
+
+#[cfg(test)]
+mod gauge_tests {
+    use super::*;
+
+    #[test]
+    fn test_single_staker_earns_emission() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+        assert_eq!(pool.lp_balance_of(&alice), U512::zero());
+        assert_eq!(gauge.total_staked(), U512::from(STAKE));
+
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 1000);
+        env.advance_block_time(seconds(250));
+        assert_eq!(gauge.pending_reward(alice), U512::from(250 * RATE));
+
+        env.set_caller(alice);
+        let harvested = gauge.harvest();
+        assert_eq!(harvested, U512::from(250 * RATE));
+        assert_eq!(reward_token.balance_of(&alice), U256::from(250 * RATE));
+        assert_eq!(gauge.pending_reward(alice), U512::zero());
+    }
+
+    #[test]
+    fn test_multiple_stakers_share_pro_rata() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 1000);
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+
+        // Alice alone for 100s, then Bob joins with half her stake for 300s
+        env.advance_block_time(seconds(100));
+        stake(&env, &mut pool, &mut gauge, bob, STAKE / 2);
+        env.advance_block_time(seconds(300));
+
+        assert_eq!(gauge.pending_reward(alice), U512::from(300 * RATE));
+        assert_eq!(gauge.pending_reward(bob), U512::from(100 * RATE));
+
+        // Alice leaves; Bob earns everything afterwards
+        env.set_caller(alice);
+        gauge.withdraw(U512::from(STAKE));
+        assert_eq!(pool.lp_balance_of(&alice), U512::from(STAKE));
+        env.advance_block_time(seconds(100));
+
+        assert_eq!(gauge.pending_reward(alice), U512::from(300 * RATE));
+        assert_eq!(gauge.pending_reward(bob), U512::from(200 * RATE));
+
+        env.set_caller(alice);
+        assert_eq!(gauge.harvest(), U512::from(300 * RATE));
+        env.set_caller(bob);
+        assert_eq!(gauge.harvest(), U512::from(200 * RATE));
+    }
+
+    #[test]
+    fn test_campaign_window_bounds_emission() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+
+        // Nothing accrues before the start
+        let start = env.block_time() + seconds(100);
+        schedule(&env, &mut gauge, &mut reward_token, start, 200);
+        assert!(gauge.active_campaign().is_none());
+        env.advance_block_time(seconds(100));
+        assert_eq!(gauge.pending_reward(alice), U512::zero());
+
+        env.advance_block_time(seconds(50));
+        assert_eq!(gauge.active_campaign().map(|campaign| campaign.id), Some(0));
+
+        // Nor after the end
+        env.advance_block_time(seconds(500));
+        assert_eq!(gauge.pending_reward(alice), U512::from(200 * RATE));
+        assert!(gauge.active_campaign().is_none());
+
+        // A follow-up campaign picks up from the current time
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 100);
+        env.advance_block_time(seconds(40));
+        assert_eq!(gauge.pending_reward(alice), U512::from(240 * RATE));
+        assert_eq!(gauge.campaign_count(), 2);
+    }
+
+    #[test]
+    fn test_back_to_back_campaigns_accrue_in_one_update() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+
+        let start = env.block_time();
+        schedule(&env, &mut gauge, &mut reward_token, start, 100);
+        schedule(&env, &mut gauge, &mut reward_token, start + seconds(100), 100);
+        env.advance_block_time(seconds(150));
+
+        env.set_caller(alice);
+        assert_eq!(gauge.harvest(), U512::from(150 * RATE));
+    }
+
+    #[test]
+    fn test_overlapping_campaign_rejected() {
+        let (env, _pool, mut gauge, mut reward_token) = setup();
+        let start = env.block_time();
+        schedule(&env, &mut gauge, &mut reward_token, start, 100);
+
+        env.set_caller(env.get_account(0));
+        reward_token.approve(&gauge.address(), &U256::from(RATE * 100));
+        let result = gauge.try_schedule_campaign(U512::from(RATE), start + seconds(50), start + seconds(150));
+        assert!(result.is_err(), "Should revert: overlaps the previous campaign");
+
+        let result = gauge.try_schedule_campaign(U512::from(RATE), start + seconds(200), start + seconds(200));
+        assert!(result.is_err(), "Should revert: empty window");
+    }
+
+    #[test]
+    fn test_schedule_requires_admin() {
+        let (env, _pool, mut gauge, _reward_token) = setup();
+
+        env.set_caller(env.get_account(2));
+        let start = env.block_time();
+        let result = gauge.try_schedule_campaign(U512::from(RATE), start, start + seconds(100));
+        assert!(result.is_err(), "Should revert: not admin");
+    }
+
+    #[test]
+    fn test_emergency_withdraw_forfeits_rewards() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+        let bob = env.get_account(3);
+        let treasury = env.get_account(1);
+
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 1000);
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+        stake(&env, &mut pool, &mut gauge, bob, STAKE);
+        env.advance_block_time(seconds(100));
+
+        // Alice's harvest brings the accounting up to date; Bob then bails out
+        env.set_caller(alice);
+        assert_eq!(gauge.harvest(), U512::from(50 * RATE));
+        env.set_caller(bob);
+        assert_eq!(gauge.emergency_withdraw(), U512::from(STAKE));
+        assert_eq!(pool.lp_balance_of(&bob), U512::from(STAKE));
+        assert_eq!(gauge.get_stake(bob).amount, U512::zero());
+        assert_eq!(gauge.pending_reward(bob), U512::zero());
+        assert!(gauge.try_emergency_withdraw().is_err(), "Should revert: nothing staked");
+
+        // Bob's forfeited share is recoverable by the admin
+        assert_eq!(gauge.undistributed_rewards(), U512::from(50 * RATE));
+        env.set_caller(env.get_account(0));
+        assert_eq!(gauge.recover_undistributed(treasury), U512::from(50 * RATE));
+        assert_eq!(reward_token.balance_of(&treasury), U256::from(50 * RATE));
+    }
+
+    #[test]
+    fn test_pair_lp_can_be_staked() {
+        let env = odra_test::env();
+        let admin = env.get_account(0);
+        let alice = env.get_account(2);
+        env.set_caller(admin);
+
+        let mut token_a = deploy_token(&env, "wUSDC");
+        let mut token_b = deploy_token(&env, "wETH");
+        let mut pair = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token_a.address(),
+                token_b: token_b.address(),
+                treasury: env.get_account(1),
+                admin,
+            },
+        );
+        token_a.approve(&pair.address(), &U256::from(1_000_000_000u128));
+        token_b.approve(&pair.address(), &U256::from(1_000_000_000u128));
+        pair.add_liquidity(
+            U512::from(1_000_000_000u128),
+            U512::from(1_000_000_000u128),
+            U512::zero(),
+            U512::zero(),
+            U512::zero(),
+        );
+        pair.transfer(&alice, &U256::from(STAKE));
+
+        let mut reward_token = deploy_token(&env, "GHOST");
+        let mut gauge = GhostPoolGauge::deploy(
+            &env,
+            GhostPoolGaugeInitArgs {
+                staking_token: pair.address(),
+                reward_token: reward_token.address(),
+                admin,
+            },
+        );
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 1000);
+
+        env.set_caller(alice);
+        pair.approve(&gauge.address(), &U256::from(STAKE));
+        gauge.deposit(U512::from(STAKE));
+        assert_eq!(pair.lp_balance_of(&alice), U512::zero());
+        assert_eq!(pair.lp_balance_of(&gauge.address()), U512::from(STAKE));
+
+        env.advance_block_time(seconds(100));
+        gauge.withdraw(U512::from(STAKE));
+        assert_eq!(pair.lp_balance_of(&alice), U512::from(STAKE));
+        assert_eq!(gauge.harvest(), U512::from(100 * RATE));
+    }
+
+    #[test]
+    fn test_emission_without_stakers_is_recoverable() {
+        let (env, mut pool, mut gauge, mut reward_token) = setup();
+        let alice = env.get_account(2);
+        let treasury = env.get_account(1);
+
+        schedule(&env, &mut gauge, &mut reward_token, env.block_time(), 1000);
+        env.advance_block_time(seconds(100));
+        stake(&env, &mut pool, &mut gauge, alice, STAKE);
+        env.advance_block_time(seconds(100));
+
+        assert_eq!(gauge.pending_reward(alice), U512::from(100 * RATE));
+        assert_eq!(gauge.undistributed_rewards(), U512::from(100 * RATE));
+
+        env.set_caller(env.get_account(0));
+        assert_eq!(gauge.recover_undistributed(treasury), U512::from(100 * RATE));
+        assert_eq!(reward_token.balance_of(&treasury), U256::from(100 * RATE));
+        assert_eq!(gauge.undistributed_rewards(), U512::zero());
+    }
+}
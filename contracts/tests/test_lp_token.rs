@@ -1,7 +1,7 @@
 //! Tests for LP Token contract
 
 use odra::casper_types::U256;
-use odra::host::{Deployer, HostRef};
+use odra::host::Deployer;
 use odra::prelude::*;
 
 use ghost_pool::lp_token::{LpToken, LpTokenInitArgs};
@@ -0,0 +1,305 @@
+//! Tests for token/token pairs
+
+use odra::casper_types::{U256, U512};
+use odra::host::Deployer;
+use odra::prelude::*;
+
+use ghost_pool::pair::{GhostPoolPair, GhostPoolPairInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+use ghost_pool::math;
+use ghost_pool::types::{CurveType, PoolConfig, MIN_RAMP_TIME_MS};
+
+#[cfg(test)]
+mod pair_tests {
+    use super::*;
+
+    fn deploy_token(env: &odra::host::HostEnv, symbol: &str) -> ghost_pool::test_token::TestTokenHostRef {
+        TestToken::deploy(
+            env,
+            TestTokenInitArgs {
+                name: symbol.to_string(),
+                symbol: symbol.to_string(),
+                decimals: 6,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        )
+    }
+
+    /// Setup a pair with tokens sorted as (token0, token1)
+    fn setup() -> (
+        odra::host::HostEnv,
+        ghost_pool::pair::GhostPoolPairHostRef,
+        ghost_pool::test_token::TestTokenHostRef,
+        ghost_pool::test_token::TestTokenHostRef,
+    ) {
+        let env = odra_test::env();
+        env.set_caller(env.get_account(0));
+
+        let token_a = deploy_token(&env, "wUSDC");
+        let token_b = deploy_token(&env, "wETH");
+        let pair = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token_a.address(),
+                token_b: token_b.address(),
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+            },
+        );
+
+        let (token0, token1) = if pair.token0() == token_a.address() {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        (env, pair, token0, token1)
+    }
+
+    /// Helper: deposit 1000 token0 / 2000 token1
+    fn add_initial_liquidity(
+        pair: &mut ghost_pool::pair::GhostPoolPairHostRef,
+        token0: &mut ghost_pool::test_token::TestTokenHostRef,
+        token1: &mut ghost_pool::test_token::TestTokenHostRef,
+    ) -> U512 {
+        let amount0 = U512::from(1_000_000_000u128);
+        let amount1 = U512::from(2_000_000_000u128);
+        token0.approve(&pair.address(), &U256::from(amount0.as_u128()));
+        token1.approve(&pair.address(), &U256::from(amount1.as_u128()));
+        let (_, _, lp) = pair.add_liquidity(amount0, amount1, U512::zero(), U512::zero(), U512::zero());
+        lp
+    }
+
+    #[test]
+    fn test_tokens_sorted_and_identical_rejected() {
+        let (env, pair, token0, token1) = setup();
+        assert!(token0.address() < token1.address());
+        assert_eq!(pair.token1(), token1.address());
+
+        let result = GhostPoolPair::try_deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token0.address(),
+                token_b: token0.address(),
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+            },
+        );
+        assert!(result.is_err(), "Should revert: identical tokens");
+    }
+
+    #[test]
+    fn test_add_liquidity_at_ratio() {
+        let (_env, mut pair, mut token0, mut token1) = setup();
+        let lp = add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        assert!(lp > U512::zero());
+
+        // Second deposit offers too much token1; only the matching share is pulled
+        let amount0 = U512::from(100_000_000u128);
+        let amount1 = U512::from(500_000_000u128);
+        token0.approve(&pair.address(), &U256::from(amount0.as_u128()));
+        token1.approve(&pair.address(), &U256::from(amount1.as_u128()));
+        let (used0, used1, _) = pair.add_liquidity(amount0, amount1, U512::zero(), U512::zero(), U512::zero());
+
+        assert_eq!(used0, amount0);
+        assert_eq!(used1, U512::from(200_000_000u128));
+        assert_eq!(pair.get_reserves(), (U512::from(1_100_000_000u128), U512::from(2_200_000_000u128)));
+    }
+
+    #[test]
+    fn test_swap_matches_quotes() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        let user = env.get_account(0);
+
+        let amount_in = U512::from(10_000_000u128);
+        let quoted = pair.quote_amount_out(token0.address(), amount_in);
+        let balance1 = token1.balance_of(&user);
+
+        token0.approve(&pair.address(), &U256::from(amount_in.as_u128()));
+        let amount_out = pair.swap(token0.address(), amount_in, quoted);
+
+        assert_eq!(amount_out, quoted);
+        assert_eq!(token1.balance_of(&user), balance1 + U256::from(amount_out.as_u128()));
+        assert_eq!(
+            pair.get_reserves(),
+            (U512::from(1_010_000_000u128), U512::from(2_000_000_000u128) - amount_out)
+        );
+
+        // Exact-out quote buys at least the requested amount
+        let wanted = U512::from(5_000_000u128);
+        let needed = pair.quote_amount_in(token0.address(), wanted);
+        assert!(pair.quote_amount_out(token1.address(), needed) >= wanted);
+        assert!(pair.quote_amount_out(token1.address(), needed - U512::one()) < wanted);
+    }
+
+    #[test]
+    fn test_swap_rejects_slippage_and_unknown_token() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        let amount_in = U512::from(10_000_000u128);
+        let quoted = pair.quote_amount_out(token1.address(), amount_in);
+
+        token1.approve(&pair.address(), &U256::from(amount_in.as_u128()));
+        let result = pair.try_swap(token1.address(), amount_in, quoted + 1);
+        assert!(result.is_err(), "Should revert: slippage");
+
+        let result = pair.try_swap(env.get_account(4), amount_in, U512::zero());
+        assert!(result.is_err(), "Should revert: token not in pair");
+    }
+
+    #[test]
+    fn test_remove_liquidity_is_immediate() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        let lp = add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        let user = env.get_account(0);
+        let (expected0, expected1) = pair.get_lp_value(lp / 2);
+        let balance0 = token0.balance_of(&user);
+        let balance1 = token1.balance_of(&user);
+
+        let (amount0, amount1) = pair.remove_liquidity(lp / 2, expected0, expected1);
+
+        assert_eq!((amount0, amount1), (expected0, expected1));
+        assert_eq!(token0.balance_of(&user), balance0 + U256::from(amount0.as_u128()));
+        assert_eq!(token1.balance_of(&user), balance1 + U256::from(amount1.as_u128()));
+        assert_eq!(pair.lp_balance_of(&user), lp - lp / 2);
+    }
+
+    #[test]
+    fn test_lp_transfer_and_approval() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        let lp = add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        let (owner, spender, recipient) = (env.get_account(0), env.get_account(2), env.get_account(3));
+
+        pair.transfer(&recipient, &U256::from(1_000u64));
+        assert_eq!(pair.lp_balance_of(&recipient), U512::from(1_000u64));
+        assert_eq!(pair.lp_balance_of(&owner), lp - U512::from(1_000u64));
+
+        pair.approve(&spender, &U256::from(500u64));
+        assert_eq!(pair.allowance(&owner, &spender), U256::from(500u64));
+        env.set_caller(spender);
+        pair.transfer_from(&owner, &recipient, &U256::from(500u64));
+        assert_eq!(pair.lp_balance_of(&recipient), U512::from(1_500u64));
+        assert!(
+            pair.try_transfer_from(&owner, &recipient, &U256::from(501u64)).is_err(),
+            "Should revert: above allowance"
+        );
+
+        // The recipient can redeem the LP it received
+        env.set_caller(recipient);
+        let (amount0, amount1) = pair.remove_liquidity(U512::from(1_500u64), U512::zero(), U512::zero());
+        assert!(amount0 > U512::zero() && amount1 > U512::zero());
+    }
+
+    #[test]
+    fn test_protocol_fees_accrue_and_claim() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        add_initial_liquidity(&mut pair, &mut token0, &mut token1);
+        let treasury = env.get_account(1);
+
+        let mut config = PoolConfig::with_defaults();
+        config.protocol_swap_fee_bps = U256::from(5000u64);
+        pair.set_config(config);
+
+        // 0.3% of 100 tokens is 0.3 tokens, half of it to the treasury
+        let amount_in = U512::from(100_000_000u128);
+        token0.approve(&pair.address(), &U256::from(amount_in.as_u128()));
+        pair.swap(token0.address(), amount_in, U512::zero());
+        assert_eq!(pair.get_protocol_fees(), (U512::from(150_000u64), U512::zero()));
+
+        pair.claim_protocol_fees();
+        assert_eq!(token0.balance_of(&treasury), U256::from(150_000u64));
+        assert_eq!(pair.get_protocol_fees(), (U512::zero(), U512::zero()));
+    }
+
+    #[test]
+    fn test_stable_curve_swaps_near_peg() {
+        let (env, mut pair, mut token0, mut token1) = setup();
+        pair.set_stable_curve(100);
+        assert_eq!(pair.curve_type(), CurveType::Stable);
+        assert_eq!(pair.get_amp(), 100);
+
+        let amount = U512::from(1_000_000_000u128);
+        token0.approve(&pair.address(), &U256::from(amount.as_u128()));
+        token1.approve(&pair.address(), &U256::from(amount.as_u128()));
+        pair.add_liquidity(amount, amount, U512::zero(), U512::zero(), U512::zero());
+
+        let amount_in = U512::from(100_000_000u128);
+        let quoted = pair.quote_amount_out(token0.address(), amount_in);
+        assert!(quoted > math::get_amount_out(amount_in, amount, amount, 30));
+        assert!(quoted < amount_in);
+
+        let user = env.get_account(0);
+        let balance1 = token1.balance_of(&user);
+        token0.approve(&pair.address(), &U256::from(amount_in.as_u128()));
+        let amount_out = pair.swap(token0.address(), amount_in, quoted);
+        assert_eq!(amount_out, quoted);
+        assert_eq!(token1.balance_of(&user), balance1 + U256::from(amount_out.as_u128()));
+
+        // Exact-out quotes follow the same curve
+        let wanted = U512::from(50_000_000u128);
+        let needed = pair.quote_amount_in(token0.address(), wanted);
+        assert!(pair.quote_amount_out(token1.address(), needed) >= wanted);
+
+        // The curve cannot change once liquidity exists
+        assert!(pair.try_set_stable_curve(200).is_err(), "Should revert: liquidity present");
+    }
+
+    #[test]
+    fn test_stable_curve_requires_matching_decimals() {
+        let env = odra_test::env();
+        env.set_caller(env.get_account(0));
+        let token_a = deploy_token(&env, "wUSDC");
+        let token_b = TestToken::deploy(
+            &env,
+            TestTokenInitArgs {
+                name: "wETH".to_string(),
+                symbol: "wETH".to_string(),
+                decimals: 18,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let mut pair = GhostPoolPair::deploy(
+            &env,
+            GhostPoolPairInitArgs {
+                token_a: token_a.address(),
+                token_b: token_b.address(),
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+            },
+        );
+
+        assert!(pair.try_set_stable_curve(100).is_err(), "Should revert: decimals differ");
+        assert_eq!(pair.curve_type(), CurveType::ConstantProduct);
+    }
+
+    #[test]
+    fn test_amp_ramp_rules() {
+        let (env, mut pair, _, _) = setup();
+        assert_eq!(pair.curve_type(), CurveType::ConstantProduct);
+        assert!(pair.try_ramp_amp(200, env.block_time() + MIN_RAMP_TIME_MS).is_err(), "Should revert: not stable");
+        assert!(pair.try_set_stable_curve(0).is_err(), "Should revert: zero amp");
+        pair.set_stable_curve(100);
+
+        // Ramps wait a day after the previous change and last at least a day
+        assert!(pair.try_ramp_amp(200, env.block_time() + 2 * MIN_RAMP_TIME_MS).is_err(), "Should revert: too soon");
+        env.advance_block_time(MIN_RAMP_TIME_MS);
+        assert!(pair.try_ramp_amp(200, env.block_time() + MIN_RAMP_TIME_MS - 1).is_err(), "Should revert: too short");
+        assert!(pair.try_ramp_amp(1001, env.block_time() + MIN_RAMP_TIME_MS).is_err(), "Should revert: change > 10x");
+
+        env.set_caller(env.get_account(1));
+        assert!(pair.try_ramp_amp(200, env.block_time() + MIN_RAMP_TIME_MS).is_err(), "Should revert: not admin");
+        env.set_caller(env.get_account(0));
+
+        // Linear ramp from 100 to 300 over two days
+        let start = env.block_time();
+        pair.ramp_amp(300, start + 2 * MIN_RAMP_TIME_MS);
+        env.advance_block_time(MIN_RAMP_TIME_MS);
+        assert_eq!(pair.get_amp(), 200);
+
+        pair.stop_amp_ramp();
+        env.advance_block_time(MIN_RAMP_TIME_MS);
+        assert_eq!(pair.get_amp(), 200);
+        let ramp = pair.get_amp_ramp();
+        assert_eq!((ramp.initial_amp, ramp.future_amp), (200, 200));
+    }
+}
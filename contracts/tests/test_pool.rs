@@ -4,9 +4,16 @@ use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
 use odra::host::{Deployer, HostRef};
 use odra::prelude::*;
 
+use ghost_pool::auction_simulator::{AuctionSimulator, AuctionSimulatorInitArgs};
 use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
 use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
 
+/// Validator used by all test pools (localnet node-1)
+fn test_validator() -> PublicKey {
+    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+    PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+}
+
 /// Setup test environment with pool and test token
 fn setup() -> (
     odra::host::HostEnv,
@@ -28,8 +35,7 @@ fn setup() -> (
     );
 
     // Use a real validator from localnet (node-1)
-    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
-    let validator = PublicKey::from_hex(validator_hex).expect("Invalid validator key");
+    let validator = test_validator();
 
     // Deploy pool
     let treasury = env.get_account(1);
@@ -38,7 +44,7 @@ fn setup() -> (
     let pool = GhostPoolPool::deploy(
         &env,
         GhostPoolPoolInitArgs {
-            token_address: test_token.address().clone(),
+            token_address: test_token.address(),
             validator,
             treasury,
             admin,
@@ -48,6 +54,46 @@ fn setup() -> (
     (env, pool, test_token)
 }
 
+/// Setup test environment with staking routed to an auction simulator
+fn setup_with_simulator() -> (
+    odra::host::HostEnv,
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::test_token::TestTokenHostRef,
+    ghost_pool::auction_simulator::AuctionSimulatorHostRef,
+) {
+    let (env, mut pool, token) = setup();
+
+    // 7 eras of unbonding (~14h with 2h eras), 500 CSPR minimum delegation
+    let auction = AuctionSimulator::deploy(
+        &env,
+        AuctionSimulatorInitArgs {
+            unbonding_delay: 7,
+            minimum_delegation: U512::from(500_000_000_000u128),
+        },
+    );
+
+    env.set_caller(env.get_account(0));
+    pool.set_auction_simulator(auction.address());
+
+    (env, pool, token, auction)
+}
+
+/// Helper: Pay `amount` of staking rewards to the pool and compound them once unbonded
+fn harvest_rewards(
+    env: &odra::host::HostEnv,
+    pool: &mut ghost_pool::pool::GhostPoolPoolHostRef,
+    auction: &mut ghost_pool::auction_simulator::AuctionSimulatorHostRef,
+    amount: U512,
+) -> U512 {
+    let pool_addr = pool.address();
+    auction.with_tokens(amount).distribute_rewards(pool_addr, test_validator());
+    pool.compound();
+
+    env.advance_block_time(ghost_pool::types::UNBONDING_PERIOD_MS + 1000);
+    auction.advance_eras(7);
+    pool.compound()
+}
+
 /// Helper: Add initial liquidity to the pool
 fn add_initial_liquidity(
     env: &odra::host::HostEnv,
@@ -55,16 +101,19 @@ fn add_initial_liquidity(
     token: &mut ghost_pool::test_token::TestTokenHostRef,
 ) -> U512 {
     let user = env.get_account(0);
-    let cspr_amount = U512::from(1000_000_000_000u128); // 1000 CSPR
-    let token_amount = U512::from(1000_000_000u128); // 1000 tokens
+    let cspr_amount = U512::from(1_000_000_000_000u128); // 1000 CSPR
+    let token_amount = U512::from(1_000_000_000u128); // 1000 tokens
 
     // Approve tokens for pool
-    let pool_addr = pool.address().clone();
+    let pool_addr = pool.address();
     token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
 
     // Add liquidity
     env.set_caller(user);
-    pool.with_tokens(cspr_amount).add_liquidity(token_amount, U512::zero())
+    let (_, _, lp) = pool
+        .with_tokens(cspr_amount)
+        .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
+    lp
 }
 
 #[cfg(test)]
@@ -89,16 +138,18 @@ mod pool_tests {
         let (env, pool, mut token) = setup();
 
         let user = env.get_account(0);
-        let cspr_amount = U512::from(1000_000_000_000u128); // 1000 CSPR
-        let token_amount = U512::from(1000_000_000u128); // 1000 tokens
+        let cspr_amount = U512::from(1_000_000_000_000u128); // 1000 CSPR
+        let token_amount = U512::from(1_000_000_000u128); // 1000 tokens
 
         // Approve tokens for pool
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
 
         // Add liquidity
         env.set_caller(user);
-        let lp_received = pool.with_tokens(cspr_amount).add_liquidity(token_amount, U512::zero());
+        let (_, _, lp_received) = pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
 
         // Verify LP tokens received (sqrt(1000 * 1000) - 1000 minimum)
         assert!(lp_received > U512::zero());
@@ -149,18 +200,22 @@ mod add_liquidity_tests {
         let cspr_amount = U512::from(500_000_000_000u128); // 500 CSPR
         let token_amount = U512::from(500_000_000u128); // 500 tokens
 
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
         env.set_caller(user);
-        let lp2 = pool.with_tokens(cspr_amount).add_liquidity(token_amount, U512::zero());
+        let (cspr_used, token_used, lp2) = pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
 
         // Verify LP tokens received
         assert!(lp2 > U512::zero());
+        assert_eq!(cspr_used, cspr_amount);
+        assert_eq!(token_used, token_amount);
 
         // Verify reserves updated
         let (reserve_cspr, reserve_token) = pool.get_reserves();
-        assert_eq!(reserve_cspr, U512::from(1500_000_000_000u128));
-        assert_eq!(reserve_token, U512::from(1500_000_000u128));
+        assert_eq!(reserve_cspr, U512::from(1_500_000_000_000u128));
+        assert_eq!(reserve_token, U512::from(1_500_000_000u128));
     }
 
     #[test]
@@ -173,20 +228,88 @@ mod add_liquidity_tests {
         // Second deposit with different ratio (more tokens than CSPR ratio)
         let user = env.get_account(0);
         let cspr_amount = U512::from(500_000_000_000u128); // 500 CSPR
-        let token_amount = U512::from(1000_000_000u128); // 1000 tokens (double the ratio)
+        let token_amount = U512::from(1_000_000_000u128); // 1000 tokens (double the ratio)
 
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
         env.set_caller(user);
-        let lp2 = pool.with_tokens(cspr_amount).add_liquidity(token_amount, U512::zero());
+        let tokens_before = token.balance_of(&user);
+        let (cspr_used, token_used, lp2) = pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
 
-        // LP minted should be based on the smaller ratio (CSPR in this case)
+        // Only the tokens matching the CSPR side are pulled
         assert!(lp2 > U512::zero());
+        assert_eq!(cspr_used, cspr_amount);
+        assert_eq!(token_used, U512::from(500_000_000u128));
+        assert_eq!(
+            tokens_before - token.balance_of(&user),
+            U256::from(token_used.as_u128())
+        );
+
+        // Nothing is donated to existing LPs
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_cspr, U512::from(1_500_000_000_000u128));
+        assert_eq!(reserve_token, U512::from(1_500_000_000u128));
+    }
+
+    #[test]
+    fn test_add_liquidity_refunds_surplus_cspr() {
+        let (env, mut pool, mut token) = setup();
+        let _lp1 = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        // More CSPR than the tokens can pair with
+        let user = env.get_account(0);
+        let cspr_amount = U512::from(1_000_000_000_000u128); // 1000 CSPR
+        let token_amount = U512::from(500_000_000u128); // 500 tokens
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
+        env.set_caller(user);
+        let balance_before = env.balance_of(&user);
+        let (cspr_used, token_used, _) = pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
+
+        assert_eq!(cspr_used, U512::from(500_000_000_000u128));
+        assert_eq!(token_used, token_amount);
+        assert_eq!(balance_before - env.balance_of(&user), cspr_used);
 
-        // Reserves should still update with what was provided
         let (reserve_cspr, reserve_token) = pool.get_reserves();
-        assert_eq!(reserve_cspr, U512::from(1500_000_000_000u128));
-        assert_eq!(reserve_token, U512::from(2000_000_000u128));
+        assert_eq!(reserve_cspr, U512::from(1_500_000_000_000u128));
+        assert_eq!(reserve_token, U512::from(1_500_000_000u128));
+    }
+
+    #[test]
+    fn test_add_liquidity_amount_minimums() {
+        let (env, mut pool, mut token) = setup();
+        let _lp1 = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let user = env.get_account(0);
+        let cspr_amount = U512::from(500_000_000_000u128); // 500 CSPR
+        let token_amount = U512::from(1_000_000_000u128); // 1000 tokens
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
+        env.set_caller(user);
+
+        // Only 500 tokens would be used
+        let result = pool.with_tokens(cspr_amount).try_add_liquidity(
+            token_amount,
+            U512::from(600_000_000u128),
+            U512::zero(),
+            U512::zero(),
+        );
+        assert!(result.is_err(), "Should revert: token minimum not met");
+
+        // Only 250 CSPR would be used against 250 tokens
+        let result = pool.with_tokens(cspr_amount).try_add_liquidity(
+            U512::from(250_000_000u128),
+            U512::zero(),
+            U512::from(300_000_000_000u128),
+            U512::zero(),
+        );
+        assert!(result.is_err(), "Should revert: CSPR minimum not met");
     }
 
     #[test]
@@ -202,13 +325,13 @@ mod add_liquidity_tests {
         let token_amount = U512::from(100_000_000u128); // 100 tokens
         let min_lp = U512::from(999_999_999_999u128); // Unreasonably high
 
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
         env.set_caller(user);
 
         // Should revert due to slippage protection
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            pool.with_tokens(cspr_amount).add_liquidity(token_amount, min_lp)
+            pool.with_tokens(cspr_amount).add_liquidity(token_amount, U512::zero(), U512::zero(), min_lp)
         }));
         assert!(result.is_err(), "Should revert due to slippage protection");
     }
@@ -218,15 +341,15 @@ mod add_liquidity_tests {
         let (env, pool, mut token) = setup();
 
         let user = env.get_account(0);
-        let token_amount = U512::from(1000_000_000u128);
+        let token_amount = U512::from(1_000_000_000u128);
 
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
         env.set_caller(user);
 
         // Should revert with zero CSPR
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            pool.with_tokens(U512::zero()).add_liquidity(token_amount, U512::zero())
+            pool.with_tokens(U512::zero()).add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero())
         }));
         assert!(result.is_err(), "Should revert with zero CSPR");
     }
@@ -236,13 +359,13 @@ mod add_liquidity_tests {
         let (env, pool, _token) = setup();
 
         let user = env.get_account(0);
-        let cspr_amount = U512::from(1000_000_000_000u128);
+        let cspr_amount = U512::from(1_000_000_000_000u128);
 
         env.set_caller(user);
 
         // Should revert with zero tokens
         let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-            pool.with_tokens(cspr_amount).add_liquidity(U512::zero(), U512::zero())
+            pool.with_tokens(cspr_amount).add_liquidity(U512::zero(), U512::zero(), U512::zero(), U512::zero())
         }));
         assert!(result.is_err(), "Should revert with zero tokens");
     }
@@ -296,7 +419,7 @@ mod swap_tests {
         let token_in = U512::from(10_000_000u128); // 10 tokens
 
         // Approve additional tokens
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_in.as_u128()));
 
         env.set_caller(user);
@@ -344,7 +467,7 @@ mod swap_tests {
         let user = env.get_account(0);
         let token_in = U512::from(900_000_000u128); // 900 tokens (would need ~90% of CSPR)
 
-        let pool_addr = pool.address().clone();
+        let pool_addr = pool.address();
         token.approve(&pool_addr, &U256::from(token_in.as_u128()));
         env.set_caller(user);
 
@@ -601,6 +724,7 @@ mod claim_withdrawal_tests {
 #[cfg(test)]
 mod compound_tests {
     use super::*;
+    use ghost_pool::types::UNBONDING_PERIOD_MS;
 
     #[test]
     fn test_compound_no_rewards() {
@@ -616,32 +740,73 @@ mod compound_tests {
 
     #[test]
     fn test_compound_with_rewards() {
-        let (env, mut pool, mut token) = setup();
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
 
         // Add initial liquidity
         let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
 
         // Get initial reserves
         let (initial_reserve_cspr, _) = pool.get_reserves();
+        let treasury = env.get_account(1);
+        let treasury_before = env.balance_of(&treasury);
 
-        // Note: In test environment, staking rewards are simulated
-        // The compound function checks delegated_amount vs tracked staked
-        // In real scenario, rewards accumulate from validator
+        // Validator pays 10 CSPR of rewards into the pool's delegation
+        let rewards = harvest_rewards(&env, &mut pool, &mut auction, U512::from(10_000_000_000u128));
 
-        // Compound (may return 0 if no simulated rewards)
-        let rewards = pool.compound();
+        // 10% protocol fee goes to the treasury, the rest to the reserve
+        assert_eq!(rewards, U512::from(9_000_000_000u128));
+        let (new_reserve_cspr, _) = pool.get_reserves();
+        assert_eq!(new_reserve_cspr, initial_reserve_cspr + rewards);
+        assert_eq!(env.balance_of(&treasury), treasury_before + U512::from(1_000_000_000u128));
+        assert_eq!(pool.get_harvested_rewards(), U512::zero());
 
-        // If rewards exist, reserve should increase
-        if rewards > U512::zero() {
-            let (new_reserve_cspr, _) = pool.get_reserves();
-            assert!(new_reserve_cspr > initial_reserve_cspr);
-        }
+        // Harvested rewards are no longer pending
+        assert_eq!(pool.compound(), U512::zero());
+    }
+
+    #[test]
+    fn test_compound_waits_for_rewards_to_unbond() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let (reserve_before, _) = pool.get_reserves();
+        let (_, buffer_before) = pool.get_staking_info();
+        let treasury = env.get_account(1);
+        let treasury_before = env.balance_of(&treasury);
+
+        let pool_addr = pool.address();
+        let rewards = U512::from(10_000_000_000u128);
+        auction.with_tokens(rewards).distribute_rewards(pool_addr, test_validator());
+
+        // Rewards are undelegated but not credited while they unbond
+        assert_eq!(pool.compound(), U512::zero());
+        assert_eq!(pool.get_harvested_rewards(), rewards);
+        assert_eq!(pool.get_reserves().0, reserve_before);
+        assert_eq!(pool.get_staking_info().1, buffer_before);
+        assert_eq!(pool.claim_protocol_fees(), (U512::zero(), U512::zero()));
+        assert_eq!(env.balance_of(&treasury), treasury_before);
+        assert_eq!(pool.get_solvency_report().cspr_deficit, U512::zero());
+
+        // Compounding again before the delay credits nothing
+        assert_eq!(pool.compound(), U512::zero());
+
+        // Back in the purse but not yet credited: not a surplus to skim
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        auction.advance_eras(7);
+        env.set_caller(env.get_account(0));
+        assert_eq!(pool.skim(env.get_account(2)).0, U512::zero());
+
+        let rewards_to_pool = pool.compound();
+        assert_eq!(rewards_to_pool, U512::from(9_000_000_000u128));
+        assert_eq!(pool.get_reserves().0, reserve_before + rewards_to_pool);
+        assert_eq!(env.balance_of(&treasury), treasury_before + U512::from(1_000_000_000u128));
+        assert_eq!(pool.get_solvency_report().cspr_deficit, U512::zero());
     }
 }
 
 #[cfg(test)]
 mod withdrawal_tests {
     use super::*;
+    use ghost_pool::types::{WithdrawalStatus, UNBONDING_PERIOD_MS};
 
     #[test]
     fn test_get_user_withdrawals_empty() {
@@ -651,4 +816,1447 @@ mod withdrawal_tests {
         let withdrawals = pool.get_user_withdrawals(user);
         assert!(withdrawals.is_empty());
     }
+
+    /// Helper: queue `count` withdrawals of equal size for account 0
+    fn queue_withdrawals(
+        env: &odra::host::HostEnv,
+        pool: &mut ghost_pool::pool::GhostPoolPoolHostRef,
+        token: &mut ghost_pool::test_token::TestTokenHostRef,
+        count: u64,
+    ) -> Vec<u64> {
+        let lp = add_initial_liquidity(env, pool, token);
+        let portion = lp / U512::from(count + 1);
+
+        env.set_caller(env.get_account(0));
+        (0..count)
+            .map(|_| pool.remove_liquidity(portion, U512::zero(), U512::zero()))
+            .collect()
+    }
+
+    #[test]
+    fn test_user_withdrawals_page() {
+        let (env, mut pool, mut token) = setup();
+        let ids = queue_withdrawals(&env, &mut pool, &mut token, 5);
+        let user = env.get_account(0);
+
+        let page = pool.get_user_withdrawals_page(user, 1, 2);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].id, ids[1]);
+        assert_eq!(page[1].id, ids[2]);
+
+        // Pages past the end are truncated
+        let page = pool.get_user_withdrawals_page(user, 4, 10);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, ids[4]);
+        assert!(pool.get_user_withdrawals_page(user, 5, 10).is_empty());
+    }
+
+    #[test]
+    fn test_user_withdrawals_by_status_and_counts() {
+        let (env, mut pool, mut token) = setup();
+        let ids = queue_withdrawals(&env, &mut pool, &mut token, 3);
+        let user = env.get_account(0);
+
+        let unbonding = pool.get_user_withdrawals_by_status(user, WithdrawalStatus::Unbonding, 0, 10);
+        assert_eq!(unbonding.len(), 3);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (3, 0));
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        pool.claim_withdrawal(ids[1]);
+
+        let claimable = pool.get_user_withdrawals_by_status(user, WithdrawalStatus::Claimable, 0, 10);
+        assert_eq!(claimable.len(), 2);
+        let claimed = pool.get_user_withdrawals_by_status(user, WithdrawalStatus::Claimed, 0, 10);
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].id, ids[1]);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (2, 1));
+    }
+
+    #[test]
+    fn test_prune_claimed_withdrawals() {
+        let (env, mut pool, mut token) = setup();
+        let ids = queue_withdrawals(&env, &mut pool, &mut token, 3);
+        let user = env.get_account(0);
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        pool.claim_withdrawal(ids[0]);
+        pool.claim_withdrawal(ids[2]);
+
+        let pruned = pool.prune_claimed_withdrawals(0, 10);
+        assert_eq!(pruned, 2);
+
+        let remaining = pool.get_user_withdrawals(user);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, ids[1]);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (1, 0));
+
+        // Pruned requests can still be looked up by ID
+        assert!(pool.get_withdrawal(ids[0]).claimed);
+    }
+
+    #[test]
+    fn test_global_queue_views() {
+        let (env, mut pool, mut token) = setup();
+        assert_eq!(pool.next_claimable_time(), None);
+        assert_eq!(pool.total_pending_withdrawals(), U512::zero());
+
+        let ids = queue_withdrawals(&env, &mut pool, &mut token, 2);
+        let first = pool.get_withdrawal(ids[0]);
+        let second = pool.get_withdrawal(ids[1]);
+
+        assert_eq!(pool.pending_withdrawal_count(), 2);
+        assert_eq!(pool.total_pending_withdrawals(), first.cspr_amount + second.cspr_amount);
+        assert_eq!(pool.next_claimable_time(), Some(first.claimable_time));
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        pool.claim_withdrawal(ids[0]);
+
+        assert_eq!(pool.pending_withdrawal_count(), 1);
+        assert_eq!(pool.total_pending_withdrawals(), second.cspr_amount);
+        assert_eq!(pool.next_claimable_time(), Some(second.claimable_time));
+
+        pool.claim_withdrawal(ids[1]);
+        assert_eq!(pool.next_claimable_time(), None);
+    }
+}
+
+// ============ PROTOCOL FEE TESTS ============
+
+#[cfg(test)]
+mod protocol_fee_tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_switch_off_by_default() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let cspr_in = U512::from(100_000_000_000u128); // 100 CSPR
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+
+        let (fees_cspr, fees_token) = pool.get_protocol_fees();
+        assert_eq!(fees_cspr, U512::zero());
+        assert_eq!(fees_token, U512::zero());
+    }
+
+    #[test]
+    fn test_fee_switch_diverts_swap_fee() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        // Divert half of the 0.3% swap fee
+        env.set_caller(env.get_account(0));
+        pool.set_protocol_swap_fee(U256::from(5000u64));
+
+        let (reserve_cspr_before, _) = pool.get_reserves();
+        let cspr_in = U512::from(100_000_000_000u128); // 100 CSPR
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+
+        // 0.3% of 100 CSPR = 0.3 CSPR fee, half of it to the treasury
+        let expected_cut = U512::from(150_000_000u128);
+        let (fees_cspr, fees_token) = pool.get_protocol_fees();
+        assert_eq!(fees_cspr, expected_cut);
+        assert_eq!(fees_token, U512::zero());
+
+        let (reserve_cspr_after, _) = pool.get_reserves();
+        assert_eq!(reserve_cspr_after, reserve_cspr_before + cspr_in - expected_cut);
+
+        // Token side accrues in tokens
+        let token_in = U512::from(10_000_000u128); // 10 tokens
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(token_in.as_u128()));
+        pool.swap_token_for_cspr(token_in, U512::zero());
+
+        let (_, fees_token) = pool.get_protocol_fees();
+        assert_eq!(fees_token, U512::from(15_000u128));
+    }
+
+    #[test]
+    fn test_claim_protocol_fees_pays_treasury() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        pool.set_protocol_swap_fee(U256::from(5000u64));
+        pool.with_tokens(U512::from(100_000_000_000u128)).swap_cspr_for_token(U512::zero());
+
+        let treasury = env.get_account(1);
+        let balance_before = env.balance_of(&treasury);
+
+        let (claimed_cspr, claimed_token) = pool.claim_protocol_fees();
+        assert_eq!(claimed_cspr, U512::from(150_000_000u128));
+        assert_eq!(claimed_token, U512::zero());
+        assert_eq!(env.balance_of(&treasury), balance_before + claimed_cspr);
+
+        let (fees_cspr, _) = pool.get_protocol_fees();
+        assert_eq!(fees_cspr, U512::zero());
+    }
+
+    #[test]
+    fn test_set_protocol_swap_fee_restricted() {
+        let (env, mut pool, _token) = setup();
+
+        // Non-admin cannot flip the switch
+        env.set_caller(env.get_account(2));
+        let result = pool.try_set_protocol_swap_fee(U256::from(1000u64));
+        assert!(result.is_err(), "Should revert: not admin");
+
+        // Admin cannot exceed the cap
+        env.set_caller(env.get_account(0));
+        let result = pool.try_set_protocol_swap_fee(U256::from(5001u64));
+        assert!(result.is_err(), "Should revert: fee above maximum");
+    }
+}
+
+// ============ RECONCILIATION TESTS ============
+
+#[cfg(test)]
+mod reconciliation_tests {
+    use super::*;
+    use ghost_pool::types::UNBONDING_PERIOD_MS;
+
+    #[test]
+    fn test_solvency_report_balanced() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let report = pool.get_solvency_report();
+        assert_eq!(report.tracked_reserve_cspr, U512::from(1_000_000_000_000u128));
+        assert_eq!(report.actual_purse_cspr, report.tracked_buffer_cspr);
+        assert_eq!(report.actual_delegated_cspr, report.tracked_staked_cspr);
+        assert_eq!(report.actual_token_balance, report.tracked_reserve_token);
+        assert_eq!(report.cspr_surplus, U512::zero());
+        assert_eq!(report.cspr_deficit, U512::zero());
+        assert_eq!(report.token_surplus, U512::zero());
+        assert_eq!(report.token_deficit, U512::zero());
+    }
+
+    #[test]
+    fn test_solvency_report_counts_pending_withdrawals() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        let withdrawal = pool.get_withdrawal(withdrawal_id);
+
+        let report = pool.get_solvency_report();
+        assert_eq!(report.pending_withdrawal_cspr, withdrawal.cspr_amount);
+        assert!(report.unbonding_cspr > U512::zero());
+        assert_eq!(report.cspr_deficit, U512::zero());
+        assert_eq!(report.cspr_surplus, U512::zero());
+    }
+
+    #[test]
+    fn test_skim_sends_direct_token_transfer() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        // Donate tokens directly, bypassing the pool
+        let donation = U256::from(5_000_000u64);
+        let pool_addr = pool.address();
+        token.transfer(&pool_addr, &donation);
+
+        let report = pool.get_solvency_report();
+        assert_eq!(report.token_surplus, U512::from(5_000_000u64));
+
+        let recipient = env.get_account(3);
+        env.set_caller(env.get_account(0));
+        let (cspr_skimmed, token_skimmed) = pool.skim(recipient);
+        assert_eq!(cspr_skimmed, U512::zero());
+        assert_eq!(token_skimmed, U512::from(5_000_000u64));
+        assert_eq!(token.balance_of(&recipient), donation);
+        assert_eq!(pool.get_solvency_report().token_surplus, U512::zero());
+    }
+
+    #[test]
+    fn test_sync_credits_direct_token_transfer() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let (_, reserve_token_before) = pool.get_reserves();
+        let pool_addr = pool.address();
+        token.transfer(&pool_addr, &U256::from(5_000_000u64));
+
+        env.set_caller(env.get_account(0));
+        pool.sync();
+
+        let (_, reserve_token_after) = pool.get_reserves();
+        assert_eq!(reserve_token_after, reserve_token_before + U512::from(5_000_000u64));
+    }
+
+    #[test]
+    fn test_sync_waits_for_unbonding() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        let (reserve_cspr_before, _) = pool.get_reserves();
+
+        // Undelegated CSPR is still in flight
+        assert!(pool.try_sync().is_err(), "Should revert: unbonding in progress");
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        pool.sync();
+
+        let (reserve_cspr_after, _) = pool.get_reserves();
+        assert_eq!(reserve_cspr_after, reserve_cspr_before);
+    }
+
+    #[test]
+    fn test_sync_and_skim_restricted() {
+        let (env, mut pool, _token) = setup();
+
+        env.set_caller(env.get_account(2));
+        assert!(pool.try_sync().is_err(), "Should revert: not admin");
+        assert!(pool.try_skim(env.get_account(2)).is_err(), "Should revert: not admin");
+    }
+}
+
+// ============ LOSS HANDLING TESTS ============
+
+#[cfg(test)]
+mod loss_tests {
+    use super::*;
+    use ghost_pool::types::PAYOUT_INDEX_SCALE;
+
+    #[test]
+    fn test_no_loss_without_shortfall() {
+        let (env, mut pool, mut token) = setup();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let (reserve_cspr_before, _) = pool.get_reserves();
+        assert_eq!(pool.get_staking_shortfall(), U512::zero());
+        assert_eq!(pool.realize_loss(), U512::zero());
+
+        let (reserve_cspr_after, _) = pool.get_reserves();
+        assert_eq!(reserve_cspr_after, reserve_cspr_before);
+        assert_eq!(pool.get_payout_index(), U512::from(PAYOUT_INDEX_SCALE));
+    }
+
+    #[test]
+    fn test_withdrawal_payout_without_loss() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+
+        let withdrawal = pool.get_withdrawal(withdrawal_id);
+        assert_eq!(withdrawal.payout_index, U512::from(PAYOUT_INDEX_SCALE));
+        assert_eq!(pool.get_withdrawal_payout(withdrawal_id), withdrawal.cspr_amount);
+    }
+}
+
+// ============ STAKING BACKEND TESTS ============
+
+#[cfg(test)]
+mod staking_backend_tests {
+    use super::*;
+    use ghost_pool::types::UNBONDING_PERIOD_MS;
+
+    #[test]
+    fn test_liquidity_is_delegated_to_simulator() {
+        let (env, mut pool, mut token, auction) = setup_with_simulator();
+        let _lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let (staked, buffer) = pool.get_staking_info();
+        assert_eq!(staked, U512::from(900_000_000_000u128));
+        assert_eq!(buffer, U512::from(100_000_000_000u128));
+
+        let pool_addr = pool.address();
+        assert_eq!(auction.delegated_amount(pool_addr, test_validator()), staked);
+    }
+
+    #[test]
+    fn test_stake_below_minimum_stays_buffered() {
+        let (env, pool, mut token, auction) = setup_with_simulator();
+
+        // 400 CSPR leaves only 360 CSPR of excess, below the 500 CSPR minimum
+        let cspr_amount = U512::from(400_000_000_000u128);
+        let token_amount = U512::from(400_000_000u128);
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(token_amount.as_u128()));
+        env.set_caller(env.get_account(0));
+        pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
+
+        let (staked, buffer) = pool.get_staking_info();
+        assert_eq!(staked, U512::zero());
+        assert_eq!(buffer, cspr_amount);
+        assert_eq!(auction.delegated_amount(pool_addr, test_validator()), U512::zero());
+    }
+
+    #[test]
+    fn test_claim_waits_for_simulated_unbonding() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        let withdrawal = pool.get_withdrawal(withdrawal_id);
+
+        // The request has matured, but the auction has not paid the CSPR back yet
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        assert!(pool.try_claim_withdrawal(withdrawal_id).is_err(), "Should revert: CSPR still unbonding");
+
+        auction.advance_eras(7);
+        let claimed = pool.claim_withdrawal(withdrawal_id);
+        assert_eq!(claimed, withdrawal.cspr_amount);
+    }
+
+    #[test]
+    fn test_slashing_is_shared_with_queue() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        let queued = pool.get_withdrawal(withdrawal_id).cspr_amount;
+        let (reserve_before, _) = pool.get_reserves();
+        let (staked_before, _) = pool.get_staking_info();
+
+        // Validator is slashed by 10%
+        let pool_addr = pool.address();
+        let slashed = auction.slash(pool_addr, test_validator(), 1000);
+        assert_eq!(pool.get_staking_shortfall(), slashed);
+
+        // Compounding realizes the shortfall instead of harvesting rewards
+        assert_eq!(pool.compound(), U512::zero());
+
+        let queue_loss = (slashed * queued) / (reserve_before + queued);
+        let (reserve_after, _) = pool.get_reserves();
+        let (staked_after, _) = pool.get_staking_info();
+        assert_eq!(staked_after, staked_before - slashed);
+        assert_eq!(reserve_after, reserve_before - (slashed - queue_loss));
+        assert_eq!(pool.get_staking_shortfall(), U512::zero());
+
+        // The queued withdrawal pays out its share of the loss less
+        let payout = pool.get_withdrawal_payout(withdrawal_id);
+        let expected = queued - queue_loss;
+        assert!(payout <= expected && payout + U512::from(2u64) >= expected);
+    }
+
+    #[test]
+    fn test_simulator_requires_admin() {
+        let (env, mut pool, _token) = setup();
+
+        env.set_caller(env.get_account(2));
+        let result = pool.try_set_auction_simulator(env.get_account(3));
+        assert!(result.is_err(), "Should revert: not admin");
+    }
+}
+
+// ============ WITHDRAWAL TRANSFER TESTS ============
+
+#[cfg(test)]
+mod withdrawal_transfer_tests {
+    use super::*;
+    use ghost_pool::types::UNBONDING_PERIOD_MS;
+
+    /// Helper: queue one withdrawal for account 0
+    fn queue_withdrawal(
+        env: &odra::host::HostEnv,
+        pool: &mut ghost_pool::pool::GhostPoolPoolHostRef,
+        token: &mut ghost_pool::test_token::TestTokenHostRef,
+    ) -> u64 {
+        let lp = add_initial_liquidity(env, pool, token);
+        env.set_caller(env.get_account(0));
+        pool.remove_liquidity(lp / 2, U512::zero(), U512::zero())
+    }
+
+    #[test]
+    fn test_transfer_moves_ownership() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let owner = env.get_account(0);
+        let buyer = env.get_account(2);
+
+        pool.transfer_withdrawal(withdrawal_id, buyer);
+
+        assert_eq!(pool.get_withdrawal(withdrawal_id).user, buyer);
+        assert!(pool.get_user_withdrawals(owner).is_empty());
+        let bought = pool.get_user_withdrawals(buyer);
+        assert_eq!(bought.len(), 1);
+        assert_eq!(bought[0].id, withdrawal_id);
+        assert_eq!(pool.get_user_withdrawal_counts(owner), (0, 0));
+        assert_eq!(pool.get_user_withdrawal_counts(buyer), (1, 0));
+    }
+
+    #[test]
+    fn test_new_owner_claims() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let buyer = env.get_account(2);
+        let cspr_amount = pool.get_withdrawal(withdrawal_id).cspr_amount;
+
+        pool.transfer_withdrawal(withdrawal_id, buyer);
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+
+        // Previous owner can no longer claim
+        let result = pool.try_claim_withdrawal(withdrawal_id);
+        assert!(result.is_err(), "Should revert: not your withdrawal");
+
+        env.set_caller(buyer);
+        let balance_before = env.balance_of(&buyer);
+        let claimed = pool.claim_withdrawal(withdrawal_id);
+        assert_eq!(claimed, cspr_amount);
+        assert_eq!(env.balance_of(&buyer), balance_before + claimed);
+    }
+
+    #[test]
+    fn test_approved_spender_transfers() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let spender = env.get_account(2);
+        let recipient = env.get_account(3);
+
+        pool.approve_withdrawal(withdrawal_id, Some(spender));
+        assert_eq!(pool.get_withdrawal_approved(withdrawal_id), Some(spender));
+
+        env.set_caller(spender);
+        pool.transfer_withdrawal(withdrawal_id, recipient);
+
+        assert_eq!(pool.get_withdrawal(withdrawal_id).user, recipient);
+        // Approval does not survive the transfer
+        assert_eq!(pool.get_withdrawal_approved(withdrawal_id), None);
+    }
+
+    #[test]
+    fn test_transfer_requires_owner_or_approval() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let stranger = env.get_account(2);
+
+        env.set_caller(stranger);
+        let result = pool.try_transfer_withdrawal(withdrawal_id, stranger);
+        assert!(result.is_err(), "Should revert: not approved");
+
+        let result = pool.try_approve_withdrawal(withdrawal_id, Some(stranger));
+        assert!(result.is_err(), "Should revert: not your withdrawal");
+    }
+
+    #[test]
+    fn test_claimed_withdrawal_not_transferable() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        pool.claim_withdrawal(withdrawal_id);
+
+        let result = pool.try_transfer_withdrawal(withdrawal_id, env.get_account(2));
+        assert!(result.is_err(), "Should revert: already claimed");
+    }
+}
+
+// ============ BATCH CLAIM TESTS ============
+
+#[cfg(test)]
+mod batch_claim_tests {
+    use super::*;
+    use ghost_pool::types::UNBONDING_PERIOD_MS;
+
+    #[test]
+    fn test_claim_all_matured_by_relayer() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let relayer = env.get_account(2);
+
+        env.set_caller(user);
+        let id1 = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        let id2 = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        let expected = pool.get_withdrawal(id1).cspr_amount + pool.get_withdrawal(id2).cspr_amount;
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+
+        // Requested after the first two matured; still unbonding
+        let id3 = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+
+        env.set_caller(relayer);
+        let user_balance_before = env.balance_of(&user);
+        let relayer_balance_before = env.balance_of(&relayer);
+        let claimed = pool.claim_all_matured(user, 0);
+
+        assert_eq!(claimed, vec![id1, id2]);
+        assert_eq!(env.balance_of(&user), user_balance_before + expected);
+        assert!(env.balance_of(&relayer) <= relayer_balance_before);
+
+        assert!(pool.get_withdrawal(id1).claimed);
+        assert!(pool.get_withdrawal(id2).claimed);
+        assert!(!pool.get_withdrawal(id3).claimed);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (1, 2));
+    }
+
+    #[test]
+    fn test_claim_all_matured_from_offset() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+
+        env.set_caller(user);
+        let id1 = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        let id2 = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+
+        // Positions before the offset are left for a later page
+        assert_eq!(pool.claim_all_matured(user, 1), vec![id2]);
+        assert!(!pool.get_withdrawal(id1).claimed);
+        assert_eq!(pool.claim_all_matured(user, 0), vec![id1]);
+        assert!(pool.claim_all_matured(user, 2).is_empty());
+    }
+
+    #[test]
+    fn test_claim_all_matured_nothing_due() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+
+        env.set_caller(user);
+        pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+
+        let claimed = pool.claim_all_matured(user, 0);
+        assert!(claimed.is_empty());
+        assert_eq!(pool.get_user_withdrawal_counts(user), (1, 0));
+    }
+}
+
+// ============ CANCEL WITHDRAWAL TESTS ============
+
+#[cfg(test)]
+mod cancel_withdrawal_tests {
+    use super::*;
+    use ghost_pool::types::{WithdrawalStatus, UNBONDING_PERIOD_MS};
+
+    /// Helper: queue one withdrawal for account 0 and let its CSPR return, returning its ID
+    fn queue_withdrawal(
+        env: &odra::host::HostEnv,
+        pool: &mut ghost_pool::pool::GhostPoolPoolHostRef,
+        token: &mut ghost_pool::test_token::TestTokenHostRef,
+    ) -> u64 {
+        let lp = add_initial_liquidity(env, pool, token);
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        withdrawal_id
+    }
+
+    #[test]
+    fn test_cancel_reverts_while_unbonding() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        env.set_caller(env.get_account(0));
+        let withdrawal_id = pool.remove_liquidity(lp / 2, U512::zero(), U512::zero());
+        let request = pool.get_withdrawal(withdrawal_id);
+
+        // Half the pool exceeds the buffer, so the CSPR is still with the auction
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::zero());
+        assert!(result.is_err(), "Should revert: CSPR still unbonding");
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        assert!(pool.cancel_withdrawal(withdrawal_id, U512::zero()) > U512::zero());
+    }
+
+    #[test]
+    fn test_cancel_cannot_take_cspr_matured_for_others() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        env.set_caller(env.get_account(0));
+
+        // The older request matures; the newer one is still unbonding
+        let older = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        let newer = pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+        let request = pool.get_withdrawal(newer);
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        let result = pool.try_cancel_withdrawal(newer, U512::zero());
+        assert!(result.is_err(), "Should revert: newer request still unbonding");
+
+        // The matured CSPR is still there for its owner
+        let payout = pool.get_withdrawal(older).cspr_amount;
+        assert_eq!(pool.claim_withdrawal(older), payout);
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        assert!(pool.cancel_withdrawal(newer, U512::zero()) > U512::zero());
+    }
+
+    #[test]
+    fn test_cancel_clears_approval() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let request = pool.get_withdrawal(withdrawal_id);
+        let spender = env.get_account(2);
+        pool.approve_withdrawal(withdrawal_id, Some(spender));
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        pool.cancel_withdrawal(withdrawal_id, U512::zero());
+
+        assert_eq!(pool.get_withdrawal_approved(withdrawal_id), None);
+    }
+
+    #[test]
+    fn test_full_cancel_reenters_pool() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let request = pool.get_withdrawal(withdrawal_id);
+        let (reserve_cspr_before, reserve_token_before) = pool.get_reserves();
+        let lp_before = pool.lp_balance_of(&user);
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        let lp_minted = pool.cancel_withdrawal(withdrawal_id, U512::zero());
+
+        assert!(lp_minted > U512::zero());
+        assert_eq!(pool.lp_balance_of(&user), lp_before + lp_minted);
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_cspr, reserve_cspr_before + request.cspr_amount);
+        assert_eq!(reserve_token, reserve_token_before + request.token_amount);
+
+        assert_eq!(pool.get_withdrawal_status(withdrawal_id), WithdrawalStatus::Cancelled);
+        assert_eq!(pool.total_pending_withdrawals(), U512::zero());
+        assert_eq!(pool.pending_withdrawal_count(), 0);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (0, 1));
+    }
+
+    #[test]
+    fn test_partial_cancel_with_limited_allowance() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let request = pool.get_withdrawal(withdrawal_id);
+
+        let pool_addr = pool.address();
+        let half_tokens = request.token_amount / 2;
+        token.approve(&pool_addr, &U256::from(half_tokens.as_u128()));
+        pool.cancel_withdrawal(withdrawal_id, U512::zero());
+
+        // The rest of the request stays queued
+        let remaining = pool.get_withdrawal(withdrawal_id);
+        assert!(remaining.is_open());
+        assert_eq!(remaining.token_amount, request.token_amount - half_tokens);
+        assert_eq!(
+            remaining.cspr_amount,
+            request.cspr_amount - request.cspr_amount * half_tokens / request.token_amount
+        );
+        assert_eq!(pool.total_pending_withdrawals(), remaining.cspr_amount);
+        assert_eq!(pool.get_user_withdrawal_counts(user), (1, 0));
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        assert_eq!(pool.claim_withdrawal(withdrawal_id), remaining.cspr_amount);
+    }
+
+    #[test]
+    fn test_cancel_requires_tokens() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+
+        // No allowance granted
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::zero());
+        assert!(result.is_err(), "Should revert: no tokens to re-enter with");
+    }
+
+    #[test]
+    fn test_cancel_slippage_and_ownership() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let request = pool.get_withdrawal(withdrawal_id);
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::MAX);
+        assert!(result.is_err(), "Should revert: slippage");
+
+        env.set_caller(env.get_account(2));
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::zero());
+        assert!(result.is_err(), "Should revert: not your withdrawal");
+    }
+
+    #[test]
+    fn test_cancelled_withdrawal_cannot_be_claimed() {
+        let (env, mut pool, mut token) = setup();
+        let withdrawal_id = queue_withdrawal(&env, &mut pool, &mut token);
+        let request = pool.get_withdrawal(withdrawal_id);
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(request.token_amount.as_u128()));
+        pool.cancel_withdrawal(withdrawal_id, U512::zero());
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        let result = pool.try_claim_withdrawal(withdrawal_id);
+        assert!(result.is_err(), "Should revert: cancelled");
+        assert!(pool.claim_all_matured(env.get_account(0), 0).is_empty());
+
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::zero());
+        assert!(result.is_err(), "Should revert: already closed");
+    }
+}
+
+// ============ ZAP TESTS ============
+
+#[cfg(test)]
+mod zap_tests {
+    use super::*;
+    use ghost_pool::pool::PoolError;
+    use ghost_pool::types::{ZapAsset, UNBONDING_PERIOD_MS};
+
+    #[test]
+    fn test_zap_in_cspr_matches_quote() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(2);
+        let cspr_in = U512::from(100_000_000_000u128); // 100 CSPR
+
+        let (swapped, token_received, expected_lp) = pool.quote_zap_in_cspr(cspr_in);
+        assert!(swapped > U512::zero() && swapped < cspr_in / 2);
+        assert!(token_received > U512::zero());
+
+        let (reserve_cspr_before, reserve_token_before) = pool.get_reserves();
+        let balance_before = env.balance_of(&user);
+
+        env.set_caller(user);
+        let lp = pool.with_tokens(cspr_in).zap_in_cspr(U512::zero());
+
+        assert_eq!(lp, expected_lp);
+        assert_eq!(pool.lp_balance_of(&user), lp);
+
+        // Dust is refunded; CSPR kept by the pool equals what the user paid
+        let cspr_kept = balance_before - env.balance_of(&user);
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+        assert_eq!(reserve_cspr, reserve_cspr_before + cspr_kept);
+        assert!(cspr_in - cspr_kept < U512::from(1_000u64));
+
+        // Tokens bought by the swap went back into the pool, minus any refunded dust
+        let token_dust = U512::from(token.balance_of(&user).as_u128());
+        assert_eq!(reserve_token, reserve_token_before - token_dust);
+    }
+
+    #[test]
+    fn test_zap_in_cspr_slippage() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let cspr_in = U512::from(100_000_000_000u128);
+
+        let (_, _, expected_lp) = pool.quote_zap_in_cspr(cspr_in);
+        let result = pool.with_tokens(cspr_in).try_zap_in_cspr(expected_lp + 1);
+        assert!(result.is_err(), "Should revert: slippage");
+    }
+
+    #[test]
+    fn test_zap_in_cspr_requires_liquidity() {
+        let (_env, pool, _token) = setup();
+        let cspr_in = U512::from(100_000_000_000u128);
+
+        assert_eq!(pool.quote_zap_in_cspr(cspr_in), (U512::zero(), U512::zero(), U512::zero()));
+        let result = pool.with_tokens(cspr_in).try_zap_in_cspr(U512::zero());
+        assert!(result.is_err(), "Should revert: empty pool");
+    }
+
+    #[test]
+    fn test_zap_in_token_matches_quote() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let token_in = U512::from(100_000_000u128); // 100 tokens
+        let lp_before = pool.lp_balance_of(&user);
+        let tokens_before = token.balance_of(&user);
+
+        let (swapped, cspr_received, expected_lp) = pool.quote_zap_in_token(token_in);
+        assert!(swapped > U512::zero() && swapped < token_in / 2);
+        assert!(cspr_received > U512::zero());
+
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(token_in.as_u128()));
+        let lp = pool.zap_in_token(token_in, U512::zero());
+
+        assert_eq!(lp, expected_lp);
+        assert_eq!(pool.lp_balance_of(&user), lp_before + lp);
+        let tokens_spent = U512::from((tokens_before - token.balance_of(&user)).as_u128());
+        assert!(tokens_spent <= token_in);
+        assert!(token_in - tokens_spent < U512::from(1_000u64));
+    }
+
+    #[test]
+    fn test_zap_out_to_token() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let (reserve_cspr_before, _) = pool.get_reserves();
+        let (_, token_share) = pool.get_lp_value(lp / 2);
+        let tokens_before = token.balance_of(&user);
+
+        let (token_out, withdrawal_id) = pool.zap_out(lp / 2, ZapAsset::Token, U512::zero());
+
+        assert!(withdrawal_id.is_none());
+        assert!(token_out > token_share);
+        assert_eq!(U512::from((token.balance_of(&user) - tokens_before).as_u128()), token_out);
+        // The CSPR share was swapped into the pool, not withdrawn
+        assert_eq!(pool.get_reserves().0, reserve_cspr_before);
+        assert_eq!(pool.pending_withdrawal_count(), 0);
+    }
+
+    #[test]
+    fn test_zap_out_to_cspr_through_queue() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let (cspr_share, _) = pool.get_lp_value(lp / 2);
+        let tokens_before = token.balance_of(&user);
+
+        let (cspr_out, withdrawal_id) = pool.zap_out(lp / 2, ZapAsset::Cspr, U512::zero());
+        let withdrawal_id = withdrawal_id.expect("CSPR zap-out queues a withdrawal");
+
+        assert!(cspr_out > cspr_share);
+        assert_eq!(token.balance_of(&user), tokens_before);
+        let request = pool.get_withdrawal(withdrawal_id);
+        assert_eq!(request.cspr_amount, cspr_out);
+        assert_eq!(request.token_amount, U512::zero());
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        assert_eq!(pool.claim_withdrawal(withdrawal_id), cspr_out);
+    }
+
+    #[test]
+    fn test_zap_out_to_cspr_cannot_be_cancelled() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let (_, withdrawal_id) = pool.zap_out(lp / 2, ZapAsset::Cspr, U512::zero());
+        let withdrawal_id = withdrawal_id.expect("CSPR zap-out queues a withdrawal");
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        let pool_addr = pool.address();
+        token.approve(&pool_addr, &U256::from(1_000_000_000u64));
+        let result = pool.try_cancel_withdrawal(withdrawal_id, U512::zero());
+        assert_eq!(result.unwrap_err(), PoolError::CsprOnlyWithdrawal.into());
+        assert!(pool.get_withdrawal(withdrawal_id).is_open());
+    }
+
+    #[test]
+    fn test_zap_out_slippage() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let result = pool.try_zap_out(lp / 2, ZapAsset::Token, U512::MAX);
+        assert!(result.is_err(), "Should revert: slippage");
+        let result = pool.try_zap_out(lp / 2, ZapAsset::Cspr, U512::MAX);
+        assert!(result.is_err(), "Should revert: slippage");
+    }
+}
+
+// ============ ADMIN SETTINGS TESTS ============
+
+#[cfg(test)]
+mod admin_settings_tests {
+    use super::*;
+    use ghost_pool::types::PoolConfig;
+
+    #[test]
+    fn test_set_config_and_treasury() {
+        let (env, mut pool, _token) = setup();
+        env.set_caller(env.get_account(0));
+
+        let mut config = PoolConfig::with_defaults();
+        config.swap_fee_bps = U256::from(50u64);
+        pool.set_config(config.clone());
+        pool.set_treasury(env.get_account(4));
+
+        assert_eq!(pool.get_config(), config);
+        assert_eq!(pool.treasury(), env.get_account(4));
+
+        config.buffer_target_bps = U256::from(10_001u64);
+        let result = pool.try_set_config(config);
+        assert!(result.is_err(), "Should revert: buffer target above 100%");
+
+        env.set_caller(env.get_account(2));
+        let result = pool.try_set_treasury(env.get_account(2));
+        assert!(result.is_err(), "Should revert: not admin");
+    }
+
+    #[test]
+    fn test_set_validator_requires_no_stake() {
+        let (env, mut pool, mut token) = setup();
+        let other = PublicKey::from_hex("0106ca7c39cd272dbf21a86eeb3b36b7c26e2e9b94af64292419f7862936bca2ca")
+            .expect("Invalid validator key");
+
+        env.set_caller(env.get_account(0));
+        pool.set_validator(other.clone());
+        assert_eq!(pool.validator(), other);
+
+        // 1000 CSPR deposit stakes 900 CSPR
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let result = pool.try_set_validator(test_validator());
+        assert!(result.is_err(), "Should revert: stake active");
+    }
+}
+
+#[cfg(test)]
+mod lp_earnings_tests {
+    use super::*;
+    use ghost_pool::types::LpEarnings;
+
+    /// Swap fee an LP holding `lp` of `total_lp` earns on `amount_in` (0.3%, no protocol cut)
+    fn fee_share(amount_in: U512, lp: U512, total_lp: U512) -> U512 {
+        (amount_in * U512::from(30u64) / U512::from(10_000u64)) * lp / total_lp
+    }
+
+    fn assert_close(actual: U512, expected: U512) {
+        assert!(
+            actual <= expected + U512::one() && actual + U512::one() >= expected,
+            "{} vs {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_new_lp_has_no_earnings() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let earnings = pool.get_lp_earnings(env.get_account(0));
+        assert_eq!(earnings, LpEarnings::default());
+    }
+
+    #[test]
+    fn test_swap_fees_attributed_per_asset() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let total_lp = pool.lp_total_supply();
+
+        let cspr_in = U512::from(100_000_000_000u128); // 100 CSPR
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+
+        let token_in = U512::from(10_000_000u128); // 10 tokens
+        token.approve(&pool.address(), &U256::from(token_in.as_u128()));
+        pool.swap_token_for_cspr(token_in, U512::zero());
+
+        let earnings = pool.get_lp_earnings(user);
+        assert_close(earnings.fees_cspr, fee_share(cspr_in, lp, total_lp));
+        assert_close(earnings.fees_token, fee_share(token_in, lp, total_lp));
+        assert_eq!(earnings.staking_rewards, U512::zero());
+    }
+
+    #[test]
+    fn test_transfer_checkpoints_earnings() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let owner = env.get_account(0);
+        let buyer = env.get_account(2);
+        let total_lp = pool.lp_total_supply();
+
+        // Fees before the transfer stay with the seller
+        let first_swap = U512::from(100_000_000_000u128);
+        pool.with_tokens(first_swap).swap_cspr_for_token(U512::zero());
+        let half = lp / 2;
+        pool.transfer(&buyer, &U256::from(half.as_u128()));
+        assert_eq!(pool.get_lp_earnings(buyer), LpEarnings::default());
+
+        // Later fees are shared by balance
+        let second_swap = U512::from(200_000_000_000u128);
+        pool.with_tokens(second_swap).swap_cspr_for_token(U512::zero());
+
+        let seller_earnings = pool.get_lp_earnings(owner);
+        let buyer_earnings = pool.get_lp_earnings(buyer);
+        assert_close(
+            seller_earnings.fees_cspr,
+            fee_share(first_swap, lp, total_lp) + fee_share(second_swap, lp - half, total_lp),
+        );
+        assert_close(buyer_earnings.fees_cspr, fee_share(second_swap, half, total_lp));
+    }
+
+    #[test]
+    fn test_burn_keeps_earned_fees() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let total_lp = pool.lp_total_supply();
+
+        let cspr_in = U512::from(100_000_000_000u128);
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+        let earned = pool.get_lp_earnings(user).fees_cspr;
+        assert_close(earned, fee_share(cspr_in, lp, total_lp));
+
+        pool.remove_liquidity(lp, U512::zero(), U512::zero());
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+        assert_eq!(pool.get_lp_earnings(user).fees_cspr, earned);
+    }
+
+    #[test]
+    fn test_staking_rewards_attributed() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        let user = env.get_account(0);
+        let total_lp = pool.lp_total_supply();
+
+        let rewards_to_pool = harvest_rewards(&env, &mut pool, &mut auction, U512::from(10_000_000_000u128));
+
+        let earnings = pool.get_lp_earnings(user);
+        assert_close(earnings.staking_rewards, rewards_to_pool * lp / total_lp);
+        assert_eq!(earnings.fees_cspr, U512::zero());
+        assert_eq!(earnings.fees_token, U512::zero());
+    }
+}
+
+#[cfg(test)]
+mod pool_stats_tests {
+    use super::*;
+    use ghost_pool::math;
+    use ghost_pool::types::{DAY_MS, YEAR_MS};
+
+    #[test]
+    fn test_stats_empty_pool() {
+        let (_env, pool, _token) = setup();
+
+        let stats = pool.get_pool_stats();
+        assert_eq!(stats.window_start, stats.window_end);
+        assert_eq!(stats.totals.volume_cspr, U512::zero());
+        assert_eq!(stats.fee_apr_bps, 0);
+        assert_eq!(stats.staking_apr_bps, 0);
+        assert_eq!(stats.total_apy_bps, 0);
+    }
+
+    #[test]
+    fn test_stats_record_swaps() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let cspr_in = U512::from(100_000_000_000u128); // 100 CSPR
+        pool.with_tokens(cspr_in).swap_cspr_for_token(U512::zero());
+        let token_in = U512::from(10_000_000u128); // 10 tokens
+        token.approve(&pool.address(), &U256::from(token_in.as_u128()));
+        pool.swap_token_for_cspr(token_in, U512::zero());
+
+        env.advance_block_time(DAY_MS);
+        let stats = pool.get_pool_stats();
+        assert_eq!(stats.totals.volume_cspr, cspr_in);
+        assert_eq!(stats.totals.volume_token, token_in);
+        assert_eq!(stats.totals.fees_cspr, U512::from(300_000_000u128));
+        assert_eq!(stats.totals.fees_token, U512::from(30_000u128));
+        assert_eq!(stats.window_end - stats.window_start, DAY_MS);
+
+        // One day of fees annualized against both sides of the pool
+        let (reserve_cspr, reserve_token) = (stats.state.reserve_cspr, stats.state.reserve_token);
+        let fees_value = stats.totals.fees_cspr + stats.totals.fees_token * reserve_cspr / reserve_token;
+        let expected = fees_value * U512::from(YEAR_MS) * U512::from(10_000u64)
+            / (reserve_cspr * U512::from(2u64) * U512::from(DAY_MS));
+        assert_eq!(U512::from(stats.fee_apr_bps), expected);
+        assert_eq!(stats.staking_apr_bps, 0);
+        assert!(stats.total_apy_bps >= stats.fee_apr_bps);
+    }
+
+    #[test]
+    fn test_stats_window_rolls_over() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        pool.with_tokens(U512::from(100_000_000_000u128)).swap_cspr_for_token(U512::zero());
+        env.advance_block_time(8 * DAY_MS);
+
+        let second_swap = U512::from(50_000_000_000u128);
+        pool.with_tokens(second_swap).swap_cspr_for_token(U512::zero());
+
+        let stats = pool.get_pool_stats();
+        assert_eq!(stats.totals.volume_cspr, second_swap);
+        assert!(stats.window_end - stats.window_start < 7 * DAY_MS);
+    }
+
+    #[test]
+    fn test_stats_record_staking_rewards() {
+        let (env, mut pool, mut token, mut auction) = setup_with_simulator();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        env.advance_block_time(DAY_MS);
+        let rewards_to_pool = harvest_rewards(&env, &mut pool, &mut auction, U512::from(10_000_000_000u128));
+
+        let stats = pool.get_pool_stats();
+        assert_eq!(stats.totals.staking_rewards, rewards_to_pool);
+        assert!(stats.staking_apr_bps > 0);
+        assert_eq!(stats.fee_apr_bps, 0);
+    }
+
+    #[test]
+    fn test_compounded_apy() {
+        assert_eq!(math::compounded_apy_bps(0, 365), 0);
+        // 10% APR compounded daily is ~10.5156%
+        assert_eq!(math::compounded_apy_bps(1000, 365), 1051);
+        assert_eq!(math::compounded_apy_bps(1000, 1), 1000);
+        assert_eq!(math::compounded_apy_bps(u64::MAX, 365), u64::MAX);
+    }
+}
+
+#[cfg(test)]
+mod pool_state_tests {
+    use super::*;
+    use ghost_pool::types::{CurveType, PoolConfig};
+
+    #[test]
+    fn test_pool_state_matches_individual_getters() {
+        let (env, mut pool, mut token) = setup();
+        let lp = add_initial_liquidity(&env, &mut pool, &mut token);
+        pool.remove_liquidity(lp / 4, U512::zero(), U512::zero());
+
+        let state = pool.get_pool_state();
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+        let (staked, buffer) = pool.get_staking_info();
+
+        assert_eq!(state.token_address, token.address());
+        assert_eq!((state.reserve_cspr, state.reserve_token), (reserve_cspr, reserve_token));
+        assert_eq!((state.staked_cspr, state.buffer_cspr), (staked, buffer));
+        assert_eq!(state.lp_total_supply, pool.lp_total_supply());
+        assert_eq!(state.config, pool.get_config());
+        assert_eq!(state.curve, CurveType::ConstantProduct);
+        assert_eq!(state.validator, test_validator());
+        assert_eq!(state.liquid_staking, None);
+        assert_eq!(state.treasury, env.get_account(1));
+        assert_eq!(state.admin, env.get_account(0));
+        assert_eq!(state.pending_withdrawal_cspr, pool.total_pending_withdrawals());
+        assert_eq!((state.protocol_fees_cspr, state.protocol_fees_token), pool.get_protocol_fees());
+    }
+
+    #[test]
+    fn test_pool_state_of_new_pool() {
+        let (_env, pool, _token) = setup();
+
+        let state = pool.get_pool_state();
+        assert_eq!(state.reserve_cspr, U512::zero());
+        assert_eq!(state.unbonding_cspr, U512::zero());
+        assert_eq!(state.lp_total_supply, U512::zero());
+        assert_eq!(state.config, PoolConfig::with_defaults());
+    }
+}
+
+// ============ DECIMALS TESTS ============
+
+/// Setup a pool paired with a token of `decimals` decimals (1M whole tokens to account 0)
+fn setup_with_decimals(decimals: u8) -> (
+    odra::host::HostEnv,
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::test_token::TestTokenHostRef,
+) {
+    let env = odra_test::env();
+    env.set_caller(env.get_account(0));
+
+    let token = TestToken::deploy(
+        &env,
+        TestTokenInitArgs {
+            name: "Test Token".to_string(),
+            symbol: "TT".to_string(),
+            decimals,
+            initial_supply: U256::from(1_000_000u64) * U256::from(10u64).pow(U256::from(decimals)),
+        },
+    );
+    let pool = GhostPoolPool::deploy(
+        &env,
+        GhostPoolPoolInitArgs {
+            token_address: token.address(),
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin: env.get_account(0),
+        },
+    );
+
+    (env, pool, token)
+}
+
+/// `amount` whole tokens in base units of a `decimals`-decimal token
+fn whole_tokens(amount: u64, decimals: u8) -> U512 {
+    U512::from(amount) * U512::from(10u64).pow(U512::from(decimals))
+}
+
+#[cfg(test)]
+mod decimals_tests {
+    use super::*;
+    use ghost_pool::types::{MINIMUM_LIQUIDITY, PRICE_DECIMALS};
+
+    const CSPR: u64 = 1_000_000_000;
+
+    /// Deposit 1000 CSPR against `tokens` whole tokens as account 0
+    fn deposit(
+        pool: &ghost_pool::pool::GhostPoolPoolHostRef,
+        token: &mut ghost_pool::test_token::TestTokenHostRef,
+        tokens: u64,
+        decimals: u8,
+    ) -> U512 {
+        let token_amount = whole_tokens(tokens, decimals);
+        token.approve(&pool.address(), &U256::from(token_amount.as_u128()));
+        let (_, _, lp) = pool
+            .with_tokens(U512::from(1000 * CSPR))
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
+        lp
+    }
+
+    #[test]
+    fn test_first_deposit_lp_independent_of_decimals() {
+        for decimals in [6u8, 9, 18] {
+            let (_env, pool, mut token) = setup_with_decimals(decimals);
+            assert_eq!(pool.token_decimals(), decimals);
+
+            // sqrt(1000 CSPR * 1000 tokens) in 9-decimal units
+            let lp = deposit(&pool, &mut token, 1000, decimals);
+            assert_eq!(pool.lp_total_supply(), U512::from(1000 * CSPR), "decimals {}", decimals);
+            assert_eq!(lp, U512::from(1000 * CSPR - MINIMUM_LIQUIDITY), "decimals {}", decimals);
+
+            // Later deposits stay proportional
+            let lp = deposit(&pool, &mut token, 1000, decimals);
+            assert_eq!(lp, U512::from(1000 * CSPR), "decimals {}", decimals);
+        }
+    }
+
+    #[test]
+    fn test_first_deposit_dust_rejected_for_18_decimals() {
+        let (env, pool, mut token) = setup_with_decimals(18);
+
+        // 10^8 base units are below one 9-decimal unit
+        token.approve(&pool.address(), &U256::from(100_000_000u64));
+        env.set_caller(env.get_account(0));
+        let result = pool
+            .with_tokens(U512::from(1000 * CSPR))
+            .try_add_liquidity(U512::from(100_000_000u64), U512::zero(), U512::zero(), U512::zero());
+        assert!(result.is_err(), "Should revert: initial liquidity too low");
+    }
+
+    #[test]
+    fn test_spot_price_normalized_for_decimals() {
+        for decimals in [6u8, 9, 18] {
+            let (_env, pool, mut token) = setup_with_decimals(decimals);
+            deposit(&pool, &mut token, 2500, decimals);
+
+            // 2.5 tokens per CSPR regardless of the token's decimals
+            let spot = pool.get_spot_price();
+            assert_eq!(spot, U512::from(25u64) * U512::from(10u64).pow(U512::from(PRICE_DECIMALS - 1)));
+            assert_eq!(pool.get_price_with_decimals(decimals), whole_tokens(25, decimals) / 10);
+            assert_eq!(pool.get_price_with_decimals(2), U512::from(250u64));
+        }
+    }
+
+    #[test]
+    fn test_spot_price_of_empty_pool() {
+        let (_env, pool, _token) = setup_with_decimals(6);
+
+        assert_eq!(pool.get_spot_price(), U512::zero());
+        assert_eq!(pool.get_price_with_decimals(6), U512::zero());
+        assert!(pool.try_get_price_with_decimals(37).is_err(), "Should revert: too many decimals");
+    }
+
+    #[test]
+    fn test_spot_price_follows_swaps() {
+        let (env, pool, mut token) = setup_with_decimals(18);
+        deposit(&pool, &mut token, 1000, 18);
+        let before = pool.get_spot_price();
+
+        // Buying tokens with CSPR makes CSPR cheaper in tokens
+        env.set_caller(env.get_account(0));
+        pool.with_tokens(U512::from(100 * CSPR)).swap_cspr_for_token(U512::zero());
+        assert!(pool.get_spot_price() < before);
+
+        let (reserve_cspr, reserve_token) = pool.get_reserves();
+        let expected = reserve_token * U512::from(CSPR) / reserve_cspr;
+        assert_eq!(pool.get_spot_price(), expected);
+    }
+
+    #[test]
+    fn test_stable_curve_requires_cspr_decimals() {
+        let (_env, mut pool, _token) = setup_with_decimals(6);
+        assert!(pool.try_set_stable_curve(100).is_err(), "Should revert: token decimals differ from CSPR");
+
+        let (_env, mut pool, _token) = setup_with_decimals(9);
+        pool.set_stable_curve(100);
+        assert_eq!(pool.get_amp(), 100);
+    }
+}
+
+// ============ SWAP QUOTE TESTS ============
+
+#[cfg(test)]
+mod swap_quote_tests {
+    use super::*;
+
+    const CSPR: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_detailed_quote_matches_swap() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let cspr_in = U512::from(10 * CSPR);
+
+        let quote = pool.quote_cspr_for_token_detailed(cspr_in, U256::from(50u64));
+        assert_eq!(quote.amount_in, cspr_in);
+        assert_eq!(quote.amount_out, pool.quote_cspr_for_token(cspr_in));
+        assert_eq!(quote.fee_paid, cspr_in * U512::from(30u64) / U512::from(10_000u64));
+        assert_eq!(quote.min_amount_out, quote.amount_out * U512::from(9950u64) / U512::from(10_000u64));
+        assert_eq!(quote.spot_price_before, pool.get_spot_price());
+        assert!(quote.spot_price_after < quote.spot_price_before);
+        assert!(quote.buffer_sufficient);
+
+        let token_out = pool.with_tokens(cspr_in).swap_cspr_for_token(quote.min_amount_out);
+        assert_eq!(token_out, quote.amount_out);
+        assert_eq!(pool.get_spot_price(), quote.spot_price_after);
+    }
+
+    #[test]
+    fn test_price_impact_grows_with_trade_size() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        // A tiny trade barely moves the price; its cost is the 0.3% fee, reported apart
+        let small = pool.quote_cspr_for_token_detailed(U512::from(CSPR / 10), U256::zero());
+        assert!(small.price_impact_bps <= 1);
+        assert_eq!(small.fee_paid, U512::from(CSPR / 10 * 30 / 10000));
+        assert_eq!(small.min_amount_out, small.amount_out);
+
+        // 10% of the reserve moves the price about 9%
+        let large = pool.quote_cspr_for_token_detailed(U512::from(100 * CSPR), U256::zero());
+        assert!(large.price_impact_bps > 900 && large.price_impact_bps < 1000);
+
+        let token_quote = pool.quote_token_for_cspr_detailed(U512::from(100_000u64), U256::zero());
+        assert!(token_quote.price_impact_bps <= 1);
+        assert!(token_quote.spot_price_after > token_quote.spot_price_before);
+    }
+
+    #[test]
+    fn test_token_quote_reports_buffer() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+        let (_, buffer) = pool.get_staking_info();
+        token.approve(&pool.address(), &U256::from(1_000_000_000u64));
+
+        // Selling half the token reserve asks for more CSPR than the buffer holds
+        let token_in = U512::from(500_000_000u64);
+        let quote = pool.quote_token_for_cspr_detailed(token_in, U256::from(100u64));
+        assert_eq!(quote.buffer_sufficient, quote.amount_out <= buffer);
+        assert_eq!(quote.buffer_sufficient, pool.try_swap_token_for_cspr(token_in, U512::zero()).is_ok());
+
+        let token_in = U512::from(1_000_000u64);
+        let quote = pool.quote_token_for_cspr_detailed(token_in, U256::from(100u64));
+        assert!(quote.buffer_sufficient);
+        assert_eq!(pool.swap_token_for_cspr(token_in, quote.min_amount_out), quote.amount_out);
+    }
+
+    #[test]
+    fn test_detailed_quote_on_empty_pool() {
+        let (_env, pool, _token) = setup();
+
+        let quote = pool.quote_cspr_for_token_detailed(U512::from(CSPR), U256::from(50u64));
+        assert_eq!(quote.amount_out, U512::zero());
+        assert_eq!(quote.spot_price_before, U512::zero());
+        assert_eq!(quote.price_impact_bps, 0);
+        assert_eq!(quote.min_amount_out, U512::zero());
+    }
+
+    #[test]
+    fn test_detailed_quote_rejects_slippage_over_100_percent() {
+        let (env, mut pool, mut token) = setup();
+        add_initial_liquidity(&env, &mut pool, &mut token);
+
+        let result = pool.try_quote_cspr_for_token_detailed(U512::from(CSPR), U256::from(10_001u64));
+        assert!(result.is_err(), "Should revert: slippage above 100%");
+    }
 }
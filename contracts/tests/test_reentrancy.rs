@@ -0,0 +1,113 @@
+//! Tests for the pool reentrancy guard, using a token that calls back into the pool mid-transfer
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::reentrant_token::{ReentrantToken, ReentrantTokenInitArgs, ReentryCall};
+
+fn test_validator() -> PublicKey {
+    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+    PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+}
+
+fn reentrant_call() -> OdraError {
+    OdraError::ExecutionError(ExecutionError::ReentrantCall)
+}
+
+/// Deploy a pool paired with a (disarmed) reentrant token and seed it with liquidity
+fn setup() -> (
+    odra::host::HostEnv,
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::reentrant_token::ReentrantTokenHostRef,
+) {
+    let env = odra_test::env();
+    let admin = env.get_account(0);
+    env.set_caller(admin);
+
+    let mut token = ReentrantToken::deploy(
+        &env,
+        ReentrantTokenInitArgs {
+            name: "Evil Token".to_string(),
+            symbol: "EVIL".to_string(),
+            decimals: 6,
+            initial_supply: U256::from(1_000_000_000_000u128),
+        },
+    );
+    let pool = GhostPoolPool::deploy(
+        &env,
+        GhostPoolPoolInitArgs {
+            token_address: token.address(),
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin,
+        },
+    );
+
+    token.approve(&pool.address(), &U256::from(10_000_000_000u128));
+    pool.with_tokens(U512::from(1_000_000_000_000u128))
+        .add_liquidity(U512::from(1_000_000_000u128), U512::zero(), U512::zero(), U512::zero());
+
+    (env, pool, token)
+}
+
+#[cfg(test)]
+mod reentrancy_tests {
+    use super::*;
+
+    #[test]
+    fn test_reentry_from_transfer_from_reverts() {
+        let (_env, mut pool, mut token) = setup();
+        let reserves_before = pool.get_reserves();
+
+        // swap_token_for_cspr pulls tokens with transfer_from, which re-enters a swap
+        token.arm(pool.address(), ReentryCall::SwapTokenForCspr);
+        let result = pool.try_swap_token_for_cspr(U512::from(1_000_000u64), U512::zero());
+        assert_eq!(result.unwrap_err(), reentrant_call());
+        assert_eq!(pool.get_reserves(), reserves_before);
+    }
+
+    #[test]
+    fn test_reentry_from_transfer_reverts() {
+        let (_env, pool, mut token) = setup();
+        let reserves_before = pool.get_reserves();
+
+        // swap_cspr_for_token pays out with transfer, which re-enters remove_liquidity
+        token.arm(pool.address(), ReentryCall::RemoveLiquidity);
+        let result = pool
+            .with_tokens(U512::from(1_000_000_000u64))
+            .try_swap_cspr_for_token(U512::zero());
+        assert_eq!(result.unwrap_err(), reentrant_call());
+        assert_eq!(pool.get_reserves(), reserves_before);
+    }
+
+    #[test]
+    fn test_reentry_into_unrelated_entry_point_reverts() {
+        let (_env, pool, mut token) = setup();
+
+        // The lock is module-wide: re-entering compound from add_liquidity is rejected too
+        token.arm(pool.address(), ReentryCall::Compound);
+        let result = pool
+            .with_tokens(U512::from(1_000_000_000u64))
+            .try_add_liquidity(U512::from(1_000_000u64), U512::zero(), U512::zero(), U512::zero());
+        assert_eq!(result.unwrap_err(), reentrant_call());
+    }
+
+    #[test]
+    fn test_guard_released_after_call() {
+        let (_env, mut pool, mut token) = setup();
+
+        token.arm(pool.address(), ReentryCall::SwapTokenForCspr);
+        assert!(pool.try_swap_token_for_cspr(U512::from(1_000_000u64), U512::zero()).is_err());
+
+        // A reverted call leaves no lock behind; sequential calls go through
+        token.disarm();
+        let cspr_out = pool.swap_token_for_cspr(U512::from(1_000_000u64), U512::zero());
+        assert!(cspr_out > U512::zero());
+        let token_out = pool
+            .with_tokens(U512::from(1_000_000_000u64))
+            .swap_cspr_for_token(U512::zero());
+        assert!(token_out > U512::zero());
+    }
+}
@@ -0,0 +1,373 @@
+//! Tests for the Ghost Pool Router
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::factory::{GhostPoolFactory, GhostPoolFactoryInitArgs};
+use ghost_pool::pair::{GhostPoolPair, GhostPoolPairInitArgs};
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::router::{GhostPoolRouter, GhostPoolRouterInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+
+#[cfg(test)]
+mod router_tests {
+    use super::*;
+
+    const DEADLINE: u64 = u64::MAX;
+
+    struct Fixture {
+        env: odra::host::HostEnv,
+        factory: ghost_pool::factory::GhostPoolFactoryHostRef,
+        router: ghost_pool::router::GhostPoolRouterHostRef,
+        token_a: ghost_pool::test_token::TestTokenHostRef,
+        token_b: ghost_pool::test_token::TestTokenHostRef,
+        pool_a: ghost_pool::pool::GhostPoolPoolHostRef,
+        pool_b: ghost_pool::pool::GhostPoolPoolHostRef,
+    }
+
+    fn validator() -> PublicKey {
+        let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+        PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+    }
+
+    /// Helper: deploy a token and a factory pool seeded with 1000 CSPR of liquidity
+    fn deploy_pool(
+        env: &odra::host::HostEnv,
+        factory: &mut ghost_pool::factory::GhostPoolFactoryHostRef,
+        symbol: &str,
+        token_liquidity: u128,
+    ) -> (ghost_pool::test_token::TestTokenHostRef, ghost_pool::pool::GhostPoolPoolHostRef) {
+        let mut token = TestToken::deploy(
+            env,
+            TestTokenInitArgs {
+                name: symbol.to_string(),
+                symbol: symbol.to_string(),
+                decimals: 6,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let pool = GhostPoolPool::deploy(
+            env,
+            GhostPoolPoolInitArgs {
+                token_address: token.address(),
+                validator: validator(),
+                treasury: env.get_account(1),
+                admin: factory.address(),
+            },
+        );
+        factory.register_pool(pool.address());
+
+        let cspr_amount = U512::from(1_000_000_000_000u128); // 1000 CSPR
+        let token_amount = U512::from(token_liquidity);
+        token.approve(&pool.address(), &U256::from(token_liquidity));
+        pool
+            .with_tokens(cspr_amount)
+            .add_liquidity(token_amount, U512::zero(), U512::zero(), U512::zero());
+
+        (token, pool)
+    }
+
+    fn setup() -> Fixture {
+        let env = odra_test::env();
+        env.set_caller(env.get_account(0));
+
+        let mut factory = GhostPoolFactory::deploy(
+            &env,
+            GhostPoolFactoryInitArgs {
+                admin: env.get_account(0),
+                treasury: env.get_account(1),
+                validators: vec![validator()],
+            },
+        );
+        // 1 CSPR = 1 A, 1 CSPR = 2 B
+        let (token_a, pool_a) = deploy_pool(&env, &mut factory, "tA", 1_000_000_000);
+        let (token_b, pool_b) = deploy_pool(&env, &mut factory, "tB", 2_000_000_000);
+
+        let router = GhostPoolRouter::deploy(
+            &env,
+            GhostPoolRouterInitArgs {
+                factory: factory.address(),
+            },
+        );
+
+        Fixture { env, factory, router, token_a, token_b, pool_a, pool_b }
+    }
+
+    fn a_to_b(f: &Fixture) -> Vec<Option<Address>> {
+        vec![Some(f.token_a.address()), None, Some(f.token_b.address())]
+    }
+
+    #[test]
+    fn test_get_amounts_out_chains_pool_quotes() {
+        let f = setup();
+        let amount_in = U512::from(10_000_000u128); // 10 A
+
+        let amounts = f.router.get_amounts_out(amount_in, a_to_b(&f));
+
+        let cspr = f.pool_a.quote_token_for_cspr(amount_in);
+        let token_b = f.pool_b.quote_cspr_for_token(cspr);
+        assert_eq!(amounts, vec![amount_in, cspr, token_b]);
+    }
+
+    #[test]
+    fn test_swap_exact_tokens_for_tokens() {
+        let mut f = setup();
+        let user = f.env.get_account(0);
+        let recipient = f.env.get_account(3);
+        let amount_in = U512::from(10_000_000u128); // 10 A
+        let quoted = f.router.get_amounts_out(amount_in, a_to_b(&f));
+
+        let router_addr = f.router.address();
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+        let balance_a = f.token_a.balance_of(&user);
+        let path = a_to_b(&f);
+        let amounts = f
+            .router
+            .swap_exact_tokens_for_tokens(amount_in, quoted[2], path, recipient, DEADLINE);
+
+        assert_eq!(amounts, quoted);
+        assert_eq!(f.token_a.balance_of(&user), balance_a - U256::from(amount_in.as_u128()));
+        assert_eq!(f.token_b.balance_of(&recipient), U256::from(quoted[2].as_u128()));
+        // Nothing is left behind in the router
+        assert_eq!(f.token_a.balance_of(&router_addr), U256::zero());
+        assert_eq!(f.env.balance_of(&router_addr), U512::zero());
+    }
+
+    #[test]
+    fn test_swap_tokens_for_exact_tokens() {
+        let mut f = setup();
+        let recipient = f.env.get_account(3);
+        let amount_out = U512::from(15_000_000u128); // 15 B
+        let quoted = f.router.get_amounts_in(amount_out, a_to_b(&f));
+        assert_eq!(quoted[2], amount_out);
+
+        let router_addr = f.router.address();
+        f.token_a.approve(&router_addr, &U256::from(quoted[0].as_u128()));
+        let path = a_to_b(&f);
+        let amounts = f
+            .router
+            .swap_tokens_for_exact_tokens(amount_out, quoted[0], path, recipient, DEADLINE);
+
+        assert_eq!(amounts, quoted);
+        assert!(f.token_b.balance_of(&recipient) >= U256::from(amount_out.as_u128()));
+
+        // Asking for the same output with a lower input cap fails
+        let path = a_to_b(&f);
+        let result = f
+            .router
+            .try_swap_tokens_for_exact_tokens(amount_out, quoted[0] / 2, path, recipient, DEADLINE);
+        assert!(result.is_err(), "Should revert: excessive input");
+    }
+
+    #[test]
+    fn test_swap_exact_cspr_for_tokens() {
+        let f = setup();
+        let recipient = f.env.get_account(3);
+        let cspr_in = U512::from(10_000_000_000u128); // 10 CSPR
+        let path = vec![None, Some(f.token_b.address())];
+        let quoted = f.router.get_amounts_out(cspr_in, path.clone());
+
+        let amounts = f
+            .router
+            .with_tokens(cspr_in)
+            .swap_exact_cspr_for_tokens(U512::zero(), path, recipient, DEADLINE);
+
+        assert_eq!(amounts, quoted);
+        assert_eq!(f.token_b.balance_of(&recipient), U256::from(quoted[1].as_u128()));
+    }
+
+    #[test]
+    fn test_swap_cspr_for_exact_tokens_refunds() {
+        let f = setup();
+        let user = f.env.get_account(0);
+        let amount_out = U512::from(10_000_000u128); // 10 B
+        let path = vec![None, Some(f.token_b.address())];
+        let quoted = f.router.get_amounts_in(amount_out, path.clone());
+
+        let balance_before = f.env.balance_of(&user);
+        let attached = quoted[0] * 2;
+        f.router.with_tokens(attached).swap_cspr_for_exact_tokens(amount_out, path, user, DEADLINE);
+
+        assert_eq!(balance_before - f.env.balance_of(&user), quoted[0]);
+    }
+
+    #[test]
+    fn test_swap_exact_tokens_for_cspr() {
+        let mut f = setup();
+        let recipient = f.env.get_account(3);
+        let amount_in = U512::from(10_000_000u128); // 10 A
+        let path = vec![Some(f.token_a.address()), None];
+        let quoted = f.router.get_amounts_out(amount_in, path.clone());
+
+        let router_addr = f.router.address();
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+        let balance_before = f.env.balance_of(&recipient);
+        f.router.swap_exact_tokens_for_cspr(amount_in, U512::zero(), path, recipient, DEADLINE);
+
+        assert_eq!(f.env.balance_of(&recipient), balance_before + quoted[1]);
+    }
+
+    #[test]
+    fn test_cspr_reaches_contracts_through_receive_cspr() {
+        let mut f = setup();
+        let amount_in = U512::from(10_000_000u128); // 10 A
+        let path = vec![Some(f.token_a.address()), None];
+        let quoted = f.router.get_amounts_out(amount_in, path.clone());
+        let router_addr = f.router.address();
+
+        // A contract recipient is paid through its payable `receive_cspr`
+        let receiver = GhostPoolRouter::deploy(
+            &f.env,
+            GhostPoolRouterInitArgs {
+                factory: f.factory.address(),
+            },
+        );
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+        f.router
+            .swap_exact_tokens_for_cspr(amount_in, U512::zero(), path.clone(), receiver.address(), DEADLINE);
+        assert_eq!(f.env.balance_of(&receiver.address()), quoted[1]);
+        assert_eq!(f.env.balance_of(&router_addr), U512::zero());
+
+        // A contract without it cannot be paid
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+        let token_b = f.token_b.address();
+        let result = f
+            .router
+            .try_swap_exact_tokens_for_cspr(amount_in, U512::zero(), path, token_b, DEADLINE);
+        assert!(result.is_err(), "Should revert: recipient cannot receive CSPR");
+    }
+
+    #[test]
+    fn test_swap_tokens_for_exact_cspr() {
+        let mut f = setup();
+        let recipient = f.env.get_account(3);
+        let cspr_out = U512::from(5_000_000_000u128); // 5 CSPR
+        let path = vec![Some(f.token_b.address()), None];
+        let quoted = f.router.get_amounts_in(cspr_out, path.clone());
+
+        let router_addr = f.router.address();
+        f.token_b.approve(&router_addr, &U256::from(quoted[0].as_u128()));
+        let balance_before = f.env.balance_of(&recipient);
+        f.router.swap_tokens_for_exact_cspr(cspr_out, quoted[0], path, recipient, DEADLINE);
+
+        assert!(f.env.balance_of(&recipient) >= balance_before + cspr_out);
+    }
+
+    #[test]
+    fn test_min_out_and_deadline() {
+        let mut f = setup();
+        let user = f.env.get_account(0);
+        let amount_in = U512::from(10_000_000u128);
+        let quoted = f.router.get_amounts_out(amount_in, a_to_b(&f));
+
+        let router_addr = f.router.address();
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+
+        let path = a_to_b(&f);
+        let result = f
+            .router
+            .try_swap_exact_tokens_for_tokens(amount_in, quoted[2] + 1, path, user, DEADLINE);
+        assert!(result.is_err(), "Should revert: insufficient output");
+
+        f.env.advance_block_time(10_000);
+        let deadline = f.env.block_time() - 1;
+        let path = a_to_b(&f);
+        let result = f
+            .router
+            .try_swap_exact_tokens_for_tokens(amount_in, U512::zero(), path, user, deadline);
+        assert!(result.is_err(), "Should revert: expired");
+    }
+
+    #[test]
+    fn test_invalid_paths_rejected() {
+        let mut f = setup();
+        let user = f.env.get_account(0);
+        let amount_in = U512::from(10_000_000u128);
+
+        // A direct token hop needs a registered pair
+        let direct = vec![Some(f.token_a.address()), Some(f.token_b.address())];
+        assert!(f.router.try_get_amounts_out(amount_in, direct).is_err());
+
+        // CSPR to CSPR is not a hop
+        let cspr_only = vec![None, None];
+        assert!(f.router.try_get_amounts_out(amount_in, cspr_only).is_err());
+
+        // Unregistered token
+        let unknown = vec![Some(f.env.get_account(5)), None];
+        assert!(f.router.try_get_amounts_out(amount_in, unknown).is_err());
+
+        // Endpoints must match the entry point
+        let path = vec![Some(f.token_a.address()), None];
+        let result = f
+            .router
+            .try_swap_exact_tokens_for_tokens(amount_in, U512::zero(), path, user, DEADLINE);
+        assert!(result.is_err(), "Should revert: path ends in CSPR");
+    }
+
+    /// Helper: deploy and register an A/B pair with 1000 A / 1000 B of liquidity
+    fn add_pair(f: &mut Fixture) -> ghost_pool::pair::GhostPoolPairHostRef {
+        let mut pair = GhostPoolPair::deploy(
+            &f.env,
+            GhostPoolPairInitArgs {
+                token_a: f.token_a.address(),
+                token_b: f.token_b.address(),
+                treasury: f.env.get_account(1),
+                admin: f.factory.address(),
+            },
+        );
+        f.factory.register_pair(pair.address());
+
+        let amount = U512::from(1_000_000_000u128);
+        f.token_a.approve(&pair.address(), &U256::from(amount.as_u128()));
+        f.token_b.approve(&pair.address(), &U256::from(amount.as_u128()));
+        pair.add_liquidity(amount, amount, U512::zero(), U512::zero(), U512::zero());
+        pair
+    }
+
+    #[test]
+    fn test_direct_pair_hop() {
+        let mut f = setup();
+        let pair = add_pair(&mut f);
+        let recipient = f.env.get_account(3);
+        let amount_in = U512::from(10_000_000u128); // 10 A
+        let direct = vec![Some(f.token_a.address()), Some(f.token_b.address())];
+
+        let quoted = f.router.get_amounts_out(amount_in, direct.clone());
+        assert_eq!(quoted[1], pair.quote_amount_out(f.token_a.address(), amount_in));
+
+        let router_addr = f.router.address();
+        f.token_a.approve(&router_addr, &U256::from(amount_in.as_u128()));
+        let amounts = f
+            .router
+            .swap_exact_tokens_for_tokens(amount_in, quoted[1], direct, recipient, DEADLINE);
+
+        assert_eq!(amounts, quoted);
+        assert_eq!(f.token_b.balance_of(&recipient), U256::from(quoted[1].as_u128()));
+    }
+
+    #[test]
+    fn test_path_mixes_pools_and_pairs() {
+        let mut f = setup();
+        add_pair(&mut f);
+        let recipient = f.env.get_account(3);
+        let cspr_in = U512::from(10_000_000_000u128); // 10 CSPR
+
+        // CSPR -> A through the staked pool, then A -> B through the pair
+        let path = vec![None, Some(f.token_a.address()), Some(f.token_b.address())];
+        let quoted = f.router.get_amounts_out(cspr_in, path.clone());
+
+        f.router
+            .with_tokens(cspr_in)
+            .swap_exact_cspr_for_tokens(quoted[2], path.clone(), recipient, DEADLINE);
+        assert_eq!(f.token_b.balance_of(&recipient), U256::from(quoted[2].as_u128()));
+
+        // Exact output along the same mixed path
+        let amount_out = U512::from(1_000_000u128); // 1 B
+        let needed = f.router.get_amounts_in(amount_out, path.clone());
+        f.router
+            .with_tokens(needed[0])
+            .swap_cspr_for_exact_tokens(amount_out, path, recipient, DEADLINE);
+        assert!(f.token_b.balance_of(&recipient) >= U256::from((quoted[2] + amount_out).as_u128()));
+    }
+}
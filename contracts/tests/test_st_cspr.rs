@@ -0,0 +1,320 @@
+//! Tests for the stCSPR liquid staking token and pools staking through it
+
+use odra::casper_types::{AsymmetricType, PublicKey, U256, U512};
+use odra::host::{Deployer, HostRef};
+use odra::prelude::*;
+
+use ghost_pool::auction_simulator::{AuctionSimulator, AuctionSimulatorInitArgs};
+use ghost_pool::pool::{GhostPoolPool, GhostPoolPoolInitArgs};
+use ghost_pool::st_cspr::{StakedCspr, StakedCsprInitArgs};
+use ghost_pool::test_token::{TestToken, TestTokenInitArgs};
+use ghost_pool::types::{ST_CSPR_UNIT, UNBONDING_PERIOD_MS};
+
+fn test_validator() -> PublicKey {
+    let validator_hex = "01fed662dc7f1f7af43ad785ba07a8cc05b7a96f9ee69613cfde43bc56bec1140b";
+    PublicKey::from_hex(validator_hex).expect("Invalid validator key")
+}
+
+fn cspr(amount: u64) -> U512 {
+    U512::from(amount) * U512::from(ST_CSPR_UNIT)
+}
+
+/// Deploy stCSPR with staking routed to an auction simulator (500 CSPR minimum)
+fn setup() -> (
+    odra::host::HostEnv,
+    ghost_pool::st_cspr::StakedCsprHostRef,
+    ghost_pool::auction_simulator::AuctionSimulatorHostRef,
+) {
+    let env = odra_test::env();
+
+    let auction = AuctionSimulator::deploy(
+        &env,
+        AuctionSimulatorInitArgs {
+            unbonding_delay: 7,
+            minimum_delegation: cspr(500),
+        },
+    );
+
+    env.set_caller(env.get_account(0));
+    let mut st_cspr = StakedCspr::deploy(
+        &env,
+        StakedCsprInitArgs {
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin: env.get_account(0),
+        },
+    );
+    st_cspr.set_auction_simulator(auction.address());
+
+    (env, st_cspr, auction)
+}
+
+/// Deploy a pool and a token next to `st_cspr`, with the pool staking through it
+fn setup_pool(
+    env: &odra::host::HostEnv,
+    st_cspr: &ghost_pool::st_cspr::StakedCsprHostRef,
+) -> (
+    ghost_pool::pool::GhostPoolPoolHostRef,
+    ghost_pool::test_token::TestTokenHostRef,
+) {
+    env.set_caller(env.get_account(0));
+    let token = TestToken::deploy(
+        env,
+        TestTokenInitArgs {
+            name: "Test USDC".to_string(),
+            symbol: "tUSDC".to_string(),
+            decimals: 6,
+            initial_supply: U256::from(1_000_000_000_000u128),
+        },
+    );
+    let mut pool = GhostPoolPool::deploy(
+        env,
+        GhostPoolPoolInitArgs {
+            token_address: token.address(),
+            validator: test_validator(),
+            treasury: env.get_account(1),
+            admin: env.get_account(0),
+        },
+    );
+    pool.set_liquid_staking(st_cspr.address());
+
+    (pool, token)
+}
+
+#[cfg(test)]
+mod st_cspr_tests {
+    use super::*;
+
+    #[test]
+    fn test_first_deposit_mints_one_to_one() {
+        let (env, st_cspr, auction) = setup();
+        let user = env.get_account(2);
+
+        env.set_caller(user);
+        let shares = st_cspr.with_tokens(cspr(1000)).deposit();
+
+        assert_eq!(shares, cspr(1000));
+        assert_eq!(st_cspr.balance_of(&user), cspr(1000));
+        assert_eq!(st_cspr.exchange_rate(), U512::from(ST_CSPR_UNIT));
+        assert_eq!(auction.delegated_amount(st_cspr.address(), test_validator()), cspr(1000));
+    }
+
+    #[test]
+    fn test_deposit_below_minimum_stays_idle() {
+        let (env, st_cspr, auction) = setup();
+
+        env.set_caller(env.get_account(2));
+        st_cspr.with_tokens(cspr(100)).deposit();
+        assert_eq!(auction.delegated_amount(st_cspr.address(), test_validator()), U512::zero());
+        assert_eq!(st_cspr.total_backing(), cspr(100));
+
+        // Delegated together once the minimum is reached
+        env.set_caller(env.get_account(3));
+        st_cspr.with_tokens(cspr(400)).deposit();
+        assert_eq!(auction.delegated_amount(st_cspr.address(), test_validator()), cspr(500));
+    }
+
+    #[test]
+    fn test_compound_raises_exchange_rate() {
+        let (env, mut st_cspr, auction) = setup();
+        let user = env.get_account(2);
+        let treasury = env.get_account(1);
+
+        env.set_caller(user);
+        st_cspr.with_tokens(cspr(1000)).deposit();
+
+        // 100 CSPR of rewards, 10% of them to the treasury
+        env.set_caller(env.get_account(0));
+        auction
+            .with_tokens(cspr(100))
+            .distribute_rewards(st_cspr.address(), test_validator());
+        let rewards = st_cspr.compound();
+        assert_eq!(rewards, cspr(100));
+
+        // The treasury's shares are worth its 10 CSPR cut, holders keep the other 90
+        let treasury_value = st_cspr.shares_to_cspr(st_cspr.balance_of(&treasury));
+        assert!(treasury_value <= cspr(10) && treasury_value + U512::from(2u64) >= cspr(10));
+        let user_value = st_cspr.shares_to_cspr(st_cspr.balance_of(&user));
+        assert!(user_value >= cspr(1090) - U512::from(2u64) && user_value <= cspr(1090));
+        assert!(st_cspr.exchange_rate() > U512::from(ST_CSPR_UNIT));
+
+        // Later deposits mint fewer shares per CSPR
+        env.set_caller(env.get_account(3));
+        let shares = st_cspr.with_tokens(cspr(1090)).deposit();
+        assert!(shares < cspr(1090));
+    }
+
+    #[test]
+    fn test_unstake_from_idle_is_claimable_immediately() {
+        let (env, mut st_cspr, _auction) = setup();
+        let user = env.get_account(2);
+
+        env.set_caller(user);
+        st_cspr.with_tokens(cspr(100)).deposit();
+
+        let balance_before = env.balance_of(&user);
+        let request_id = st_cspr.request_unstake(cspr(40));
+        assert_eq!(st_cspr.balance_of(&user), cspr(60));
+
+        let paid = st_cspr.claim_unstake(request_id);
+        assert_eq!(paid, cspr(40));
+        assert_eq!(env.balance_of(&user), balance_before + cspr(40));
+    }
+
+    #[test]
+    fn test_unstake_waits_for_unbonding() {
+        let (env, mut st_cspr, mut auction) = setup();
+        let user = env.get_account(2);
+
+        env.set_caller(user);
+        st_cspr.with_tokens(cspr(1000)).deposit();
+        let request_id = st_cspr.request_unstake(cspr(300));
+
+        let request = st_cspr.get_unstake_request(request_id);
+        assert_eq!(request.cspr_amount, cspr(300));
+        assert!(st_cspr.try_claim_unstake(request_id).is_err(), "Should revert: still unbonding");
+
+        env.advance_block_time(UNBONDING_PERIOD_MS + 1000);
+        auction.advance_eras(7);
+
+        // Anyone can claim; the CSPR goes to the owner
+        let balance_before = env.balance_of(&user);
+        env.set_caller(env.get_account(3));
+        st_cspr.claim_unstake(request_id);
+        assert_eq!(env.balance_of(&user), balance_before + cspr(300));
+        assert!(st_cspr.try_claim_unstake(request_id).is_err(), "Should revert: already claimed");
+    }
+
+    #[test]
+    fn test_unstake_more_than_balance_fails() {
+        let (env, mut st_cspr, _auction) = setup();
+
+        env.set_caller(env.get_account(2));
+        st_cspr.with_tokens(cspr(100)).deposit();
+        assert!(st_cspr.try_request_unstake(cspr(101)).is_err(), "Should revert: insufficient balance");
+    }
+
+    #[test]
+    fn test_pool_stakes_through_st_cspr() {
+        let (env, st_cspr, _auction) = setup();
+        let (pool, mut token) = setup_pool(&env, &st_cspr);
+
+        env.set_caller(env.get_account(0));
+        token.approve(&pool.address(), &U256::from(1_000_000_000u128));
+        pool.with_tokens(cspr(1000))
+            .add_liquidity(U512::from(1_000_000_000u128), U512::zero(), U512::zero(), U512::zero());
+
+        // The buffer excess is held as stCSPR instead of a direct delegation
+        let (staked, buffer) = pool.get_staking_info();
+        assert!(staked > U512::zero());
+        assert_eq!(staked + buffer, cspr(1000));
+        assert_eq!(st_cspr.balance_of(&pool.address()), staked);
+        assert_eq!(pool.liquid_staking(), Some(st_cspr.address()));
+    }
+
+    #[test]
+    fn test_add_liquidity_with_st_cspr_skips_buffer() {
+        let (env, mut st_cspr, _auction) = setup();
+        let (mut pool, mut token) = setup_pool(&env, &st_cspr);
+        let user = env.get_account(0);
+
+        env.set_caller(user);
+        st_cspr.with_tokens(cspr(1000)).deposit();
+        st_cspr.approve(&pool.address(), &U256::from(cspr(1000).as_u128()));
+        token.approve(&pool.address(), &U256::from(1_000_000_000u128));
+
+        let (shares_used, tokens_used, lp) = pool.add_liquidity_st_cspr(
+            cspr(1000),
+            U512::from(1_000_000_000u128),
+            U512::zero(),
+            U512::zero(),
+            U512::zero(),
+        );
+        assert_eq!(shares_used, cspr(1000));
+        assert_eq!(tokens_used, U512::from(1_000_000_000u128));
+        assert!(lp > U512::zero());
+
+        // Credited as stake, nothing unbonded or buffered
+        let (staked, buffer) = pool.get_staking_info();
+        assert_eq!(staked, cspr(1000));
+        assert_eq!(buffer, U512::zero());
+        assert_eq!(pool.get_reserves(), (cspr(1000), U512::from(1_000_000_000u128)));
+        assert_eq!(st_cspr.balance_of(&pool.address()), cspr(1000));
+    }
+
+    #[test]
+    fn test_add_liquidity_st_cspr_requires_route() {
+        let (env, mut st_cspr, _auction) = setup();
+        let user = env.get_account(0);
+
+        env.set_caller(user);
+        let token = TestToken::deploy(
+            &env,
+            TestTokenInitArgs {
+                name: "Test USDC".to_string(),
+                symbol: "tUSDC".to_string(),
+                decimals: 6,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let mut pool = GhostPoolPool::deploy(
+            &env,
+            GhostPoolPoolInitArgs {
+                token_address: token.address(),
+                validator: test_validator(),
+                treasury: env.get_account(1),
+                admin: user,
+            },
+        );
+        st_cspr.with_tokens(cspr(100)).deposit();
+        st_cspr.approve(&pool.address(), &U256::from(cspr(100).as_u128()));
+
+        let result = pool.try_add_liquidity_st_cspr(
+            cspr(100),
+            U512::from(1_000_000u128),
+            U512::zero(),
+            U512::zero(),
+            U512::zero(),
+        );
+        assert!(result.is_err(), "Should revert: liquid staking disabled");
+    }
+
+    #[test]
+    fn test_set_liquid_staking_requires_matching_validator() {
+        let env = odra_test::env();
+        let other_validator =
+            PublicKey::from_hex("0106ca7c39cd272dbf21a86eeb3b36b7c26e2e9b94af64292419f7862936bca2ca")
+                .expect("Invalid validator key");
+
+        env.set_caller(env.get_account(0));
+        let st_cspr = StakedCspr::deploy(
+            &env,
+            StakedCsprInitArgs {
+                validator: other_validator,
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+            },
+        );
+        let token = TestToken::deploy(
+            &env,
+            TestTokenInitArgs {
+                name: "Test USDC".to_string(),
+                symbol: "tUSDC".to_string(),
+                decimals: 6,
+                initial_supply: U256::from(1_000_000_000_000u128),
+            },
+        );
+        let mut pool = GhostPoolPool::deploy(
+            &env,
+            GhostPoolPoolInitArgs {
+                token_address: token.address(),
+                validator: test_validator(),
+                treasury: env.get_account(1),
+                admin: env.get_account(0),
+            },
+        );
+
+        let result = pool.try_set_liquid_staking(st_cspr.address());
+        assert!(result.is_err(), "Should revert: validator mismatch");
+    }
+}
@@ -0,0 +1,198 @@
+//! Tests for the StableSwap invariant math
+
+use odra::casper_types::U512;
+
+use ghost_pool::math;
+use ghost_pool::stable_math;
+
+#[cfg(test)]
+mod stable_math_tests {
+    use super::*;
+
+    /// Deterministic xorshift64 generator so fuzz failures reproduce
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            self.0 = x;
+            x
+        }
+
+        fn below(&mut self, bound: u64) -> u64 {
+            self.next() % bound
+        }
+
+        /// Balance of about 10^exponent
+        fn balance(&mut self, exponent: u32) -> u128 {
+            let mantissa = 1 + self.below(9) as u128;
+            mantissa * 10u128.pow(exponent) + self.next() as u128 % 10u128.pow(exponent - 3)
+        }
+
+        /// Two balances from 1 to ~10^19 tokens (9 decimals) at most ~10^6 apart
+        fn balances(&mut self) -> (u128, u128) {
+            let exponent = 9 + self.below(19) as u32;
+            let other = (exponent + self.below(11) as u32).saturating_sub(5).clamp(9, 27);
+            (self.balance(exponent), self.balance(other))
+        }
+
+        fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+            items[self.below(items.len() as u64) as usize]
+        }
+    }
+
+    /// Reference invariant: solves `2A(x + y) + D = 2AD + D^3 / (4xy)` by bisection in f64
+    fn reference_d(x: f64, y: f64, amp: u64) -> f64 {
+        let ann = 2.0 * amp as f64;
+        let sum = x + y;
+        let (mut low, mut high) = (0.0, sum);
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+            let residual = ann * sum + mid - ann * mid - mid * mid * mid / (4.0 * x * y);
+            if residual > 0.0 {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// Reference balance of the other side for invariant `d`, by bisection in f64
+    fn reference_y(x: f64, d: f64, amp: u64) -> f64 {
+        let ann = 2.0 * amp as f64;
+        let residual = |y: f64| ann * (x + y) + d - ann * d - d * d * d / (4.0 * x * y);
+        let (mut low, mut high) = (0.0, d);
+        while residual(high) <= 0.0 {
+            high *= 2.0;
+        }
+        for _ in 0..200 {
+            let mid = (low + high) / 2.0;
+            if residual(mid) > 0.0 {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        low
+    }
+
+    fn to_f64(value: U512) -> f64 {
+        value.as_u128() as f64
+    }
+
+    fn relative_error(actual: f64, expected: f64) -> f64 {
+        (actual - expected).abs() / expected.max(1.0)
+    }
+
+    const AMPS: [u64; 7] = [1, 10, 100, 1_000, 5_000, 100_000, 1_000_000];
+    const FEES: [u64; 4] = [0, 4, 30, 1_000];
+
+    #[test]
+    fn test_balanced_invariant_is_sum() {
+        let x = U512::from(1_000_000_000_000u128);
+        for amp in AMPS {
+            let d = stable_math::get_d(x, x, amp);
+            assert!(d >= x * 2 - 1 && d <= x * 2 + 1, "amp {}: D {} != 2x", amp, d);
+        }
+        assert_eq!(stable_math::get_d(U512::zero(), U512::zero(), 100), U512::zero());
+    }
+
+    #[test]
+    fn test_stable_beats_constant_product_near_peg() {
+        let reserve = U512::from(1_000_000_000_000_000u128);
+        let amount_in = U512::from(10_000_000_000_000u128);
+
+        let constant_product = math::get_amount_out(amount_in, reserve, reserve, 30);
+        let stable = stable_math::get_amount_out(amount_in, reserve, reserve, 100, 30);
+        let flatter = stable_math::get_amount_out(amount_in, reserve, reserve, 1_000, 30);
+
+        assert!(stable > constant_product);
+        assert!(flatter > stable);
+        // Never more than the input after fees
+        assert!(flatter <= amount_in * 9970 / 10000);
+    }
+
+    #[test]
+    fn test_amount_in_zero_when_infeasible() {
+        let reserve = U512::from(1_000_000u64);
+        assert_eq!(stable_math::get_amount_in(reserve, reserve, reserve, 100, 30), U512::zero());
+        assert_eq!(stable_math::get_amount_in(U512::zero(), reserve, reserve, 100, 30), U512::zero());
+        assert_eq!(stable_math::get_amount_out(U512::zero(), reserve, reserve, 100, 30), U512::zero());
+    }
+
+    #[test]
+    fn test_fuzz_invariant_against_reference() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..2_000 {
+            let amp = rng.pick(&AMPS);
+            let (x, y) = rng.balances();
+
+            let d = stable_math::get_d(U512::from(x), U512::from(y), amp);
+            let expected = reference_d(x as f64, y as f64, amp);
+            assert!(
+                relative_error(to_f64(d), expected) < 1e-8,
+                "get_d({}, {}, {}) = {}, reference {}",
+                x, y, amp, d, expected
+            );
+
+            let y_solved = stable_math::get_y(U512::from(x), d, amp);
+            let expected_y = reference_y(x as f64, to_f64(d), amp);
+            assert!(
+                relative_error(to_f64(y_solved), expected_y) < 1e-8,
+                "get_y({}, {}, {}) = {}, reference {}",
+                x, d, amp, y_solved, expected_y
+            );
+        }
+    }
+
+    #[test]
+    fn test_fuzz_swaps_against_reference() {
+        let mut rng = XorShift(0xD1B5_4A32_D192_ED03);
+        for _ in 0..2_000 {
+            let amp = rng.pick(&AMPS);
+            let fee = rng.pick(&FEES);
+            let (reserve_in, reserve_out) = rng.balances();
+            let amount_in = 1 + rng.next() as u128 % reserve_in;
+
+            let out = stable_math::get_amount_out(
+                U512::from(amount_in),
+                U512::from(reserve_in),
+                U512::from(reserve_out),
+                amp,
+                fee,
+            );
+            assert!(out < U512::from(reserve_out));
+
+            // Reference output with the same fee on the input
+            let in_with_fee = (amount_in as f64) * (10_000 - fee) as f64 / 10_000.0;
+            let d = reference_d(reserve_in as f64, reserve_out as f64, amp);
+            let expected = reserve_out as f64 - reference_y(reserve_in as f64 + in_with_fee, d, amp);
+            assert!(
+                (to_f64(out) - expected).abs() <= reserve_out as f64 * 1e-8 + 2.0,
+                "get_amount_out({}, {}, {}, {}, {}) = {}, reference {}",
+                amount_in, reserve_in, reserve_out, amp, fee, out, expected
+            );
+
+            // The invariant never decreases across a swap
+            let d_before = stable_math::get_d(U512::from(reserve_in), U512::from(reserve_out), amp);
+            let d_after = stable_math::get_d(
+                U512::from(reserve_in + amount_in),
+                U512::from(reserve_out) - out,
+                amp,
+            );
+            assert!(d_after >= d_before || relative_error(to_f64(d_after), to_f64(d_before)) < 1e-9);
+
+            // Exact-out quotes buy at least the requested amount
+            if out > U512::zero() {
+                let needed = stable_math::get_amount_in(out, U512::from(reserve_in), U512::from(reserve_out), amp, fee);
+                assert!(needed > U512::zero());
+                let bought = stable_math::get_amount_out(needed, U512::from(reserve_in), U512::from(reserve_out), amp, fee);
+                assert!(bought >= out, "get_amount_in({}) = {} buys only {}", out, needed, bought);
+            }
+        }
+    }
+}